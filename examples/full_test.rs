@@ -1,7 +1,5 @@
 use weex_rust_sdk::WeexClient;
 use weex_rust_sdk::types::{Side, OrderType};
-use rust_decimal::Decimal;
-use std::str::FromStr;
 
 #[tokio::main]
 async fn main() {
@@ -17,9 +15,9 @@ async fn main() {
     
     let client = WeexClient::builder()
         .base_url("https://api-contract.weex.com")
-        .api_key(api_key)
-        .secret_key(secret)
-        .passphrase(passphrase)
+        .api_key(&api_key)
+        .secret_key(&secret)
+        .passphrase(&passphrase)
         .build()
         .expect("Failed to build client");
 
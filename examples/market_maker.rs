@@ -1,11 +1,11 @@
-use weex_rust_sdk::{WeexClient, Strategy, Exchange, OrderManager, Context};
+use weex_rust_sdk::{Strategy, OrderManager, Context};
 use weex_rust_sdk::spot::market::Ticker;
 use async_trait::async_trait;
 
 // 1. Define your AI Agent Strategy
 struct MarketMakerBot {
     spread: f64,
-    manager: OrderManager,
+    _manager: OrderManager,
 }
 
 #[async_trait]
@@ -33,7 +33,7 @@ async fn main() {
     
     let mut bot = MarketMakerBot {
         spread: 0.01,
-        manager: OrderManager::new(),
+        _manager: OrderManager::new(),
     };
     
     // In a real scenario, this loop receives WS events
@@ -48,6 +48,6 @@ async fn main() {
         best_bid: Decimal::from_str("90499.0").unwrap(),
     };
     
-    let mut ctx = Context { exchange_name: "WEEX".to_string() };
+    let mut ctx = Context { exchange_name: "WEEX".to_string(), ..Default::default() };
     bot.on_tick(data, &mut ctx).await;
 }
@@ -0,0 +1,490 @@
+use crate::future::account::FundingPayment;
+use crate::future::order::Fill;
+use crate::state::TradeRecord;
+use crate::types::{PositionSide, Side};
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// How closing fills are matched against open lots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CostBasis {
+    /// Close the oldest lots first
+    #[default]
+    Fifo,
+    /// Close against the size-weighted average entry price
+    AverageCost,
+}
+
+/// A position from flat (or a flip) back to flat (or the next flip)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoundTrip {
+    /// Trade id of the fill that opened the position
+    pub id: String,
+    pub symbol: String,
+    pub side: PositionSide,
+    pub opened_at: DateTime<Utc>,
+    pub closed_at: DateTime<Utc>,
+    /// Total quantity opened over the life of the position
+    pub size: Decimal,
+    pub avg_entry_price: Decimal,
+    pub avg_exit_price: Decimal,
+    /// Price PnL before fees and funding
+    pub gross_pnl: Decimal,
+    pub fees: Decimal,
+    /// Funding received (positive) or paid (negative) while the position was open
+    pub funding: Decimal,
+}
+
+impl RoundTrip {
+    /// Formula: net = gross - fees + funding
+    pub fn net_pnl(&self) -> Decimal {
+        self.gross_pnl - self.fees + self.funding
+    }
+
+    /// Journal entry for this round trip with `pnl` set to the net result
+    pub fn to_trade_record(&self) -> TradeRecord {
+        TradeRecord {
+            id: self.id.clone(),
+            timestamp: self.closed_at,
+            symbol: self.symbol.clone(),
+            side: self.side.to_string(),
+            size: self.size.to_f64().unwrap_or_default(),
+            price: self.avg_exit_price.to_f64().unwrap_or_default(),
+            pnl: self.net_pnl().to_f64(),
+            status: "closed".to_string(),
+        }
+    }
+}
+
+/// Realized PnL totals for one symbol or the whole ledger
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PnlSummary {
+    pub round_trips: u64,
+    pub winning_trips: u64,
+    /// Price PnL realized so far, including partial closes of open positions
+    pub realized_pnl: Decimal,
+    pub fees: Decimal,
+    pub funding: Decimal,
+}
+
+impl PnlSummary {
+    pub fn net_pnl(&self) -> Decimal {
+        self.realized_pnl - self.fees + self.funding
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        if self.round_trips == 0 {
+            return 0.0;
+        }
+        self.winning_trips as f64 / self.round_trips as f64
+    }
+
+    fn add(&mut self, other: &PnlSummary) {
+        self.round_trips += other.round_trips;
+        self.winning_trips += other.winning_trips;
+        self.realized_pnl += other.realized_pnl;
+        self.fees += other.fees;
+        self.funding += other.funding;
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Lot {
+    price: Decimal,
+    size: Decimal,
+}
+
+/// Currently open position for a symbol
+#[derive(Debug, Clone)]
+struct OpenTrip {
+    id: String,
+    side: PositionSide,
+    opened_at: DateTime<Utc>,
+    lots: VecDeque<Lot>,
+    opened_size: Decimal,
+    opened_value: Decimal,
+    closed_size: Decimal,
+    closed_value: Decimal,
+    gross_pnl: Decimal,
+    fees: Decimal,
+    funding: Decimal,
+}
+
+impl OpenTrip {
+    fn size(&self) -> Decimal {
+        self.lots.iter().map(|l| l.size).sum()
+    }
+
+    fn direction(&self) -> Decimal {
+        match self.side {
+            PositionSide::Long => Decimal::ONE,
+            PositionSide::Short => Decimal::NEGATIVE_ONE,
+        }
+    }
+}
+
+/// Open trips are keyed by symbol and, for hedge-mode fills, the position side
+type TripKey = (String, Option<PositionSide>);
+
+/// Round-trip PnL ledger built from raw fills and funding payments
+///
+/// Fills must be applied in chronological order. A fill larger than the open
+/// position closes the round trip and opens a new one in the other direction
+/// with the remainder; its fee is split pro rata between the two. Fills that
+/// carry a `position_side` (hedge mode) only ever open or close that side, so a
+/// long and a short on the same symbol are tracked as separate round trips.
+#[derive(Debug, Clone, Default)]
+pub struct PnlLedger {
+    basis: CostBasis,
+    open: HashMap<TripKey, OpenTrip>,
+    closed: Vec<RoundTrip>,
+    /// Per-symbol totals, including partial closes and funding received while flat
+    summaries: BTreeMap<String, PnlSummary>,
+}
+
+impl PnlLedger {
+    pub fn new(basis: CostBasis) -> Self {
+        PnlLedger {
+            basis,
+            ..Default::default()
+        }
+    }
+
+    /// Build a ledger from fills and funding, applying both in timestamp order
+    pub fn from_history(basis: CostBasis, fills: &[Fill], funding: &[FundingPayment]) -> Self {
+        let mut ledger = PnlLedger::new(basis);
+        let mut fills: Vec<&Fill> = fills.iter().collect();
+        let mut funding: Vec<&FundingPayment> = funding.iter().collect();
+        fills.sort_by_key(|f| f.timestamp);
+        funding.sort_by_key(|p| p.timestamp);
+
+        let mut payments = funding.into_iter().peekable();
+        for fill in fills {
+            while let Some(p) = payments.next_if(|p| p.timestamp <= fill.timestamp) {
+                ledger.apply_funding(p);
+            }
+            ledger.apply_fill(fill);
+        }
+        for p in payments {
+            ledger.apply_funding(p);
+        }
+        ledger
+    }
+
+    pub fn basis(&self) -> CostBasis {
+        self.basis
+    }
+
+    /// Apply one fill, returning the round trip it closed, if any
+    pub fn apply_fill(&mut self, fill: &Fill) -> Option<RoundTrip> {
+        if fill.size <= Decimal::ZERO {
+            return None;
+        }
+        let fill_side = match fill.side {
+            Side::Buy => PositionSide::Long,
+            Side::Sell => PositionSide::Short,
+        };
+        let key: TripKey = (fill.symbol.clone(), fill.position_side);
+        let mut remaining = fill.size;
+        let mut closed = None;
+
+        let reduces = match fill.position_side {
+            Some(side) => side != fill_side,
+            None => self.open.get(&key).is_some_and(|t| t.side != fill_side),
+        };
+        if let Some(mut trip) = reduces.then(|| self.open.remove(&key)).flatten() {
+            let qty = remaining.min(trip.size());
+            let fee = fill.fee * qty / fill.size;
+            let pnl = self.close_lots(&mut trip, qty, fill.price);
+
+            trip.closed_size += qty;
+            trip.closed_value += qty * fill.price;
+            trip.gross_pnl += pnl;
+            trip.fees += fee;
+            let summary = self.summaries.entry(fill.symbol.clone()).or_default();
+            summary.realized_pnl += pnl;
+            summary.fees += fee;
+            remaining -= qty;
+
+            if trip.size().is_zero() {
+                let round_trip = finish(&fill.symbol, trip, fill.timestamp);
+                summary.round_trips += 1;
+                if round_trip.net_pnl() > Decimal::ZERO {
+                    summary.winning_trips += 1;
+                }
+                self.closed.push(round_trip.clone());
+                closed = Some(round_trip);
+            } else {
+                self.open.insert(key.clone(), trip);
+            }
+        }
+
+        // A hedge-mode close never flips into the other side
+        if remaining > Decimal::ZERO && fill.position_side.is_some() && reduces {
+            tracing::warn!(
+                "Fill {} closes {} more {} {} than is open",
+                fill.trade_id, remaining, fill.symbol, fill.position_side.unwrap_or(fill_side)
+            );
+            let fee = fill.fee * remaining / fill.size;
+            self.summaries.entry(fill.symbol.clone()).or_default().fees += fee;
+        } else if remaining > Decimal::ZERO {
+            let fee = fill.fee * remaining / fill.size;
+            let basis = self.basis;
+            let trip = self.open.entry(key).or_insert_with(|| OpenTrip {
+                id: fill.trade_id.clone(),
+                side: fill_side,
+                opened_at: fill.timestamp,
+                lots: VecDeque::new(),
+                opened_size: Decimal::ZERO,
+                opened_value: Decimal::ZERO,
+                closed_size: Decimal::ZERO,
+                closed_value: Decimal::ZERO,
+                gross_pnl: Decimal::ZERO,
+                fees: Decimal::ZERO,
+                funding: Decimal::ZERO,
+            });
+            add_lot(trip, basis, fill.price, remaining);
+            trip.opened_size += remaining;
+            trip.opened_value += remaining * fill.price;
+            trip.fees += fee;
+            self.summaries.entry(fill.symbol.clone()).or_default().fees += fee;
+        }
+        closed
+    }
+
+    /// Apply a funding payment to the open position of its symbol
+    ///
+    /// With both sides open in hedge mode the payment is split by position size.
+    /// Funding that arrives while flat still counts toward the symbol totals.
+    pub fn apply_funding(&mut self, payment: &FundingPayment) {
+        let total: Decimal = self.trips(&payment.symbol).map(|t| t.size()).sum();
+        for ((symbol, _), trip) in self.open.iter_mut() {
+            if symbol == &payment.symbol && !total.is_zero() {
+                trip.funding += payment.amount * trip.size() / total;
+            }
+        }
+        self.summaries.entry(payment.symbol.clone()).or_default().funding += payment.amount;
+    }
+
+    fn trips<'a>(&'a self, symbol: &'a str) -> impl Iterator<Item = &'a OpenTrip> + 'a {
+        self.open.iter().filter(move |((s, _), _)| s == symbol).map(|(_, t)| t)
+    }
+
+    /// Remove `qty` from the open lots and return the realized price PnL
+    fn close_lots(&self, trip: &mut OpenTrip, qty: Decimal, exit_price: Decimal) -> Decimal {
+        let direction = trip.direction();
+        let mut left = qty;
+        let mut pnl = Decimal::ZERO;
+        while left > Decimal::ZERO {
+            let Some(lot) = trip.lots.front_mut() else { break };
+            let take = left.min(lot.size);
+            pnl += take * (exit_price - lot.price) * direction;
+            lot.size -= take;
+            left -= take;
+            if lot.size.is_zero() {
+                trip.lots.pop_front();
+            }
+        }
+        pnl
+    }
+
+    /// Closed round trips, oldest first
+    pub fn round_trips(&self) -> &[RoundTrip] {
+        &self.closed
+    }
+
+    /// Signed open position for `symbol` (positive long, negative short), net of both hedge-mode sides
+    pub fn position(&self, symbol: &str) -> Decimal {
+        self.trips(symbol).map(|t| t.size() * t.direction()).sum()
+    }
+
+    /// Open size on one side of `symbol`, whether tracked in hedge or one-way mode
+    pub fn side_position(&self, symbol: &str, side: PositionSide) -> Decimal {
+        self.trips(symbol).filter(|t| t.side == side).map(|t| t.size()).sum()
+    }
+
+    /// Cost-basis entry price of the remaining open position
+    ///
+    /// In hedge mode with both sides open this averages over both; use
+    /// `side_avg_entry_price` for one side.
+    pub fn avg_entry_price(&self, symbol: &str) -> Option<Decimal> {
+        avg_lot_price(self.trips(symbol))
+    }
+
+    /// Cost-basis entry price of the open position on one side of `symbol`
+    pub fn side_avg_entry_price(&self, symbol: &str, side: PositionSide) -> Option<Decimal> {
+        avg_lot_price(self.trips(symbol).filter(|t| t.side == side))
+    }
+
+    pub fn symbol_summary(&self, symbol: &str) -> PnlSummary {
+        self.summaries.get(symbol).cloned().unwrap_or_default()
+    }
+
+    /// Totals per symbol, sorted by symbol
+    pub fn summaries(&self) -> &BTreeMap<String, PnlSummary> {
+        &self.summaries
+    }
+
+    pub fn total(&self) -> PnlSummary {
+        let mut total = PnlSummary::default();
+        for summary in self.summaries.values() {
+            total.add(summary);
+        }
+        total
+    }
+}
+
+fn avg_lot_price<'a>(trips: impl Iterator<Item = &'a OpenTrip>) -> Option<Decimal> {
+    let (value, size) = trips
+        .flat_map(|t| t.lots.iter())
+        .fold((Decimal::ZERO, Decimal::ZERO), |(v, s), l| (v + l.price * l.size, s + l.size));
+    if size.is_zero() {
+        return None;
+    }
+    Some(value / size)
+}
+
+fn add_lot(trip: &mut OpenTrip, basis: CostBasis, price: Decimal, size: Decimal) {
+    match (basis, trip.lots.front_mut()) {
+        (CostBasis::AverageCost, Some(lot)) => {
+            let total = lot.size + size;
+            lot.price = (lot.price * lot.size + price * size) / total;
+            lot.size = total;
+        }
+        _ => trip.lots.push_back(Lot { price, size }),
+    }
+}
+
+fn finish(symbol: &str, trip: OpenTrip, closed_at: DateTime<Utc>) -> RoundTrip {
+    let avg = |value: Decimal, size: Decimal| if size.is_zero() { Decimal::ZERO } else { value / size };
+    RoundTrip {
+        id: trip.id,
+        symbol: symbol.to_string(),
+        side: trip.side,
+        opened_at: trip.opened_at,
+        closed_at,
+        size: trip.opened_size,
+        avg_entry_price: avg(trip.opened_value, trip.opened_size),
+        avg_exit_price: avg(trip.closed_value, trip.closed_size),
+        gross_pnl: trip.gross_pnl,
+        fees: trip.fees,
+        funding: trip.funding,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(id: &str, side: Side, price: i64, size: i64, fee: i64, minute: i64) -> Fill {
+        Fill {
+            trade_id: id.to_string(),
+            order_id: id.to_string(),
+            symbol: "cmt_btcusdt".to_string(),
+            side,
+            price: Decimal::from(price),
+            size: Decimal::from(size),
+            fee: Decimal::from(fee),
+            realized_pnl: Decimal::ZERO,
+            liquidation: false,
+            position_side: None,
+            timestamp: DateTime::<Utc>::default() + chrono::Duration::minutes(minute),
+        }
+    }
+
+    #[test]
+    fn test_partial_close_fifo_vs_average() {
+        let fills = [
+            fill("1", Side::Buy, 100, 1, 0, 0),
+            fill("2", Side::Buy, 200, 1, 0, 1),
+            fill("3", Side::Sell, 250, 1, 0, 2),
+        ];
+
+        let fifo = PnlLedger::from_history(CostBasis::Fifo, &fills, &[]);
+        assert_eq!(fifo.symbol_summary("cmt_btcusdt").realized_pnl, Decimal::from(150));
+        assert_eq!(fifo.avg_entry_price("cmt_btcusdt"), Some(Decimal::from(200)));
+
+        let avg = PnlLedger::from_history(CostBasis::AverageCost, &fills, &[]);
+        assert_eq!(avg.symbol_summary("cmt_btcusdt").realized_pnl, Decimal::from(100));
+        assert_eq!(avg.avg_entry_price("cmt_btcusdt"), Some(Decimal::from(150)));
+
+        assert!(fifo.round_trips().is_empty());
+        assert_eq!(fifo.position("cmt_btcusdt"), Decimal::ONE);
+    }
+
+    #[test]
+    fn test_round_trip_with_fees_and_funding() {
+        let fills = [
+            fill("1", Side::Sell, 300, 2, 2, 0),
+            fill("2", Side::Buy, 250, 1, 1, 10),
+            fill("3", Side::Buy, 200, 1, 1, 20),
+        ];
+        let funding = [FundingPayment {
+            symbol: "cmt_btcusdt".to_string(),
+            amount: Decimal::from(3),
+            timestamp: DateTime::<Utc>::default() + chrono::Duration::minutes(5),
+        }];
+        let ledger = PnlLedger::from_history(CostBasis::Fifo, &fills, &funding);
+
+        let trips = ledger.round_trips();
+        assert_eq!(trips.len(), 1);
+        let trip = &trips[0];
+        assert_eq!(trip.side, PositionSide::Short);
+        assert_eq!(trip.size, Decimal::from(2));
+        assert_eq!(trip.avg_exit_price, Decimal::from(225));
+        // (300 - 250) + (300 - 200) = 150, minus 4 fees, plus 3 funding
+        assert_eq!(trip.gross_pnl, Decimal::from(150));
+        assert_eq!(trip.net_pnl(), Decimal::from(149));
+        assert_eq!(ledger.total().net_pnl(), Decimal::from(149));
+        assert_eq!(ledger.position("cmt_btcusdt"), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_flip_splits_fee_and_opens_new_trip() {
+        let mut ledger = PnlLedger::new(CostBasis::Fifo);
+        assert!(ledger.apply_fill(&fill("1", Side::Buy, 100, 1, 1, 0)).is_none());
+
+        // Sell 3 closes the long and opens a 2 short; fee of 3 splits 1 / 2
+        let closed = ledger.apply_fill(&fill("2", Side::Sell, 120, 3, 3, 1)).unwrap();
+        assert_eq!(closed.side, PositionSide::Long);
+        assert_eq!(closed.gross_pnl, Decimal::from(20));
+        assert_eq!(closed.fees, Decimal::from(2));
+        assert_eq!(ledger.position("cmt_btcusdt"), Decimal::from(-2));
+
+        let closed = ledger.apply_fill(&fill("3", Side::Buy, 110, 2, 0, 2)).unwrap();
+        assert_eq!(closed.id, "2");
+        assert_eq!(closed.side, PositionSide::Short);
+        assert_eq!(closed.net_pnl(), Decimal::from(18));
+
+        let total = ledger.total();
+        assert_eq!(total.round_trips, 2);
+        assert_eq!(total.winning_trips, 2);
+        assert_eq!(total.net_pnl(), Decimal::from(36));
+    }
+
+    #[test]
+    fn test_hedge_mode_keeps_sides_apart() {
+        let hedged = |id: &str, side: Side, position: PositionSide, price: i64, minute: i64| Fill {
+            position_side: Some(position),
+            ..fill(id, side, price, 1, 0, minute)
+        };
+        let fills = [
+            hedged("1", Side::Buy, PositionSide::Long, 100, 0),
+            hedged("2", Side::Sell, PositionSide::Short, 110, 1),
+            // Closing the short must not touch the long lot
+            hedged("3", Side::Buy, PositionSide::Short, 90, 2),
+        ];
+        let ledger = PnlLedger::from_history(CostBasis::Fifo, &fills, &[]);
+
+        let trips = ledger.round_trips();
+        assert_eq!(trips.len(), 1);
+        assert_eq!(trips[0].side, PositionSide::Short);
+        assert_eq!(trips[0].gross_pnl, Decimal::from(20));
+        assert_eq!(ledger.side_position("cmt_btcusdt", PositionSide::Long), Decimal::ONE);
+        assert_eq!(ledger.side_avg_entry_price("cmt_btcusdt", PositionSide::Long), Some(Decimal::from(100)));
+        assert_eq!(ledger.position("cmt_btcusdt"), Decimal::ONE);
+    }
+}
@@ -0,0 +1,546 @@
+use crate::client::WeexClient;
+use crate::error::WeexError;
+use crate::retry::{is_retryable, RetryConfig};
+use crate::risk_gate::{OrderIntent, RiskRejection};
+use crate::state::StateManager;
+use crate::types::AILogStage;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+/// Longest accepted model name, in characters
+pub const MAX_MODEL_LEN: usize = 128;
+/// Longest accepted explanation, in characters
+pub const MAX_EXPLANATION_LEN: usize = 1000;
+/// Largest accepted `input` + `output`, in bytes of serialized JSON
+pub const MAX_PAYLOAD_BYTES: usize = 64 * 1024;
+
+/// One AI log record for `/capi/v2/order/uploadAiLog`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AiLogEntry {
+    /// Local id used to track the entry through the upload queue
+    pub id: String,
+    pub order_id: Option<i64>,
+    pub stage: AILogStage,
+    pub model: String,
+    pub input: Value,
+    pub output: Value,
+    pub explanation: String,
+    pub created_at: DateTime<Utc>,
+    /// Rejected upload attempts so far
+    #[serde(default)]
+    pub attempts: u32,
+}
+
+impl AiLogEntry {
+    pub fn builder(stage: AILogStage) -> AiLogEntryBuilder {
+        AiLogEntryBuilder::new(stage)
+    }
+
+    /// Check required fields and size limits
+    pub fn validate(&self) -> Result<(), WeexError> {
+        let invalid = |msg: String| Err(WeexError::InvalidAiLog(msg));
+        if self.model.trim().is_empty() {
+            return invalid("model is required".to_string());
+        }
+        if self.model.chars().count() > MAX_MODEL_LEN {
+            return invalid(format!("model longer than {} characters", MAX_MODEL_LEN));
+        }
+        if self.explanation.trim().is_empty() {
+            return invalid("explanation is required".to_string());
+        }
+        if self.explanation.chars().count() > MAX_EXPLANATION_LEN {
+            return invalid(format!("explanation longer than {} characters", MAX_EXPLANATION_LEN));
+        }
+        let payload = self.input.to_string().len() + self.output.to_string().len();
+        if payload > MAX_PAYLOAD_BYTES {
+            return invalid(format!("input and output are {} bytes, limit {}", payload, MAX_PAYLOAD_BYTES));
+        }
+        if let Some(oid) = self.order_id {
+            if oid <= 0 {
+                return invalid(format!("order id {} must be positive", oid));
+            }
+        }
+        Ok(())
+    }
+
+    /// Request body in the shape the API expects
+    pub fn request_body(&self) -> Value {
+        let mut body = serde_json::json!({
+            "stage": self.stage,
+            "model": self.model,
+            "input": self.input,
+            "output": self.output,
+            "explanation": self.explanation
+        });
+        if let Some(oid) = self.order_id {
+            body["orderId"] = Value::Number(oid.into());
+        }
+        body
+    }
+}
+
+/// Builder for [`AiLogEntry`]
+pub struct AiLogEntryBuilder {
+    stage: AILogStage,
+    order_id: Option<i64>,
+    model: Option<String>,
+    input: Value,
+    output: Value,
+    explanation: Option<String>,
+}
+
+impl AiLogEntryBuilder {
+    pub fn new(stage: AILogStage) -> Self {
+        AiLogEntryBuilder {
+            stage,
+            order_id: None,
+            model: None,
+            input: Value::Object(Default::default()),
+            output: Value::Object(Default::default()),
+            explanation: None,
+        }
+    }
+
+    pub fn order_id(mut self, order_id: i64) -> Self {
+        self.order_id = Some(order_id);
+        self
+    }
+
+    pub fn model(mut self, model: &str) -> Self {
+        self.model = Some(model.to_string());
+        self
+    }
+
+    pub fn input(mut self, input: Value) -> Self {
+        self.input = input;
+        self
+    }
+
+    pub fn output(mut self, output: Value) -> Self {
+        self.output = output;
+        self
+    }
+
+    pub fn explanation(mut self, explanation: &str) -> Self {
+        self.explanation = Some(explanation.to_string());
+        self
+    }
+
+    pub fn build(self) -> Result<AiLogEntry, WeexError> {
+        let created_at = Utc::now();
+        let entry = AiLogEntry {
+            id: format!("{}-{:08x}", created_at.timestamp_millis(), rand::random::<u32>()),
+            order_id: self.order_id,
+            stage: self.stage,
+            model: self.model.unwrap_or_default(),
+            input: self.input,
+            output: self.output,
+            explanation: self.explanation.unwrap_or_default(),
+            created_at,
+            attempts: 0,
+        };
+        entry.validate()?;
+        Ok(entry)
+    }
+}
+
+/// Strategy reasoning captured alongside a `Signal`
+///
+/// Pass to `Engine::execute_signal_with_decision` to have the engine log the
+/// decision, risk assessment and execution against the resulting order ID.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decision {
+    pub model: String,
+    /// Data the strategy decided on (indicators, prices, features)
+    pub input: Value,
+    pub explanation: String,
+}
+
+impl Decision {
+    pub fn new(model: &str, explanation: &str) -> Self {
+        Decision {
+            model: model.to_string(),
+            input: Value::Object(Default::default()),
+            explanation: explanation.to_string(),
+        }
+    }
+
+    pub fn with_input(mut self, input: Value) -> Self {
+        self.input = input;
+        self
+    }
+
+    /// Decision Making, Risk Assessment and Execution entries for a placed order
+    pub fn trail(
+        &self,
+        order: &OrderIntent,
+        mark_price: Option<rust_decimal::Decimal>,
+        order_id: Option<i64>,
+        response: &str,
+    ) -> Result<Vec<AiLogEntry>, WeexError> {
+        let order_json = order_json(order);
+        let response: Value = serde_json::from_str(response).unwrap_or_else(|_| Value::String(response.to_string()));
+        let stage = |stage: AILogStage| {
+            let builder = AiLogEntry::builder(stage).model(&self.model);
+            match order_id {
+                Some(oid) => builder.order_id(oid),
+                None => builder,
+            }
+        };
+
+        Ok(vec![
+            self.decision_entry(stage(AILogStage::DecisionMaking), order)?,
+            stage(AILogStage::RiskAssessment)
+                .input(serde_json::json!({ "order": order_json, "markPrice": mark_price.map(|p| p.to_string()) }))
+                .output(serde_json::json!({ "approved": true }))
+                .explanation("Order passed the pre-trade risk gate")
+                .build()?,
+            stage(AILogStage::Execution)
+                .input(order_json)
+                .output(response)
+                .explanation(&format!("Placed {} {} order for {} {}", order.order_type, order.side, order.size, order.symbol))
+                .build()?,
+        ])
+    }
+
+    /// Decision Making and Risk Assessment entries for an order the risk gate refused
+    pub fn rejection_trail(
+        &self,
+        order: &OrderIntent,
+        mark_price: Option<rust_decimal::Decimal>,
+        rejection: &RiskRejection,
+    ) -> Result<Vec<AiLogEntry>, WeexError> {
+        let stage = |stage: AILogStage| AiLogEntry::builder(stage).model(&self.model);
+        Ok(vec![
+            self.decision_entry(stage(AILogStage::DecisionMaking), order)?,
+            stage(AILogStage::RiskAssessment)
+                .input(serde_json::json!({ "order": order_json(order), "markPrice": mark_price.map(|p| p.to_string()) }))
+                .output(serde_json::json!({ "approved": false, "reason": rejection.to_string() }))
+                .explanation(&format!("Order rejected by the pre-trade risk gate: {}", rejection))
+                .build()?,
+        ])
+    }
+
+    fn decision_entry(&self, builder: AiLogEntryBuilder, order: &OrderIntent) -> Result<AiLogEntry, WeexError> {
+        builder
+            .input(self.input.clone())
+            .output(serde_json::json!({ "signal": order.side.to_string().to_uppercase(), "order": order_json(order) }))
+            .explanation(&self.explanation)
+            .build()
+    }
+}
+
+fn order_json(order: &OrderIntent) -> Value {
+    serde_json::json!({
+        "symbol": order.symbol,
+        "side": order.side,
+        "orderType": order.order_type,
+        "size": order.size.to_string(),
+        "price": order.price.map(|p| p.to_string()),
+    })
+}
+
+/// Rejected uploads after which `AiLogQueue` dead-letters an entry
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+
+/// Event in the AI log upload queue journal (`ai_log_queue.jsonl`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum AiLogRecord {
+    /// Entries queued together, written as one line
+    Enqueued { entries: Vec<AiLogEntry> },
+    /// The exchange rejected an upload of the entry
+    Rejected { id: String },
+    /// The exchange acknowledged the entry
+    Acked { id: String },
+    /// The entry was moved to `ai_log_rejected.jsonl`
+    DeadLettered { id: String },
+}
+
+/// Durable queue of AI log entries awaiting upload
+///
+/// Entries are journaled through `StateManager` before `enqueue` returns and removed
+/// only once the exchange acknowledges them, so nothing is lost across restarts.
+/// Network and 5xx failures pause the queue and retry with backoff; an entry the
+/// exchange rejects does not block the entries behind it and is retried until it
+/// has been rejected `max_attempts` times, then moved to `ai_log_rejected.jsonl`.
+pub struct AiLogQueue {
+    pending: Mutex<VecDeque<AiLogEntry>>,
+    store: Option<Arc<StateManager>>,
+    notify: Notify,
+    max_attempts: u32,
+    rejected: Mutex<Vec<AiLogEntry>>,
+}
+
+impl Default for AiLogQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AiLogQueue {
+    /// In-memory queue (entries are lost on exit)
+    pub fn new() -> Self {
+        AiLogQueue {
+            pending: Mutex::new(VecDeque::new()),
+            store: None,
+            notify: Notify::new(),
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            rejected: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Queue persisted through `store`, restoring entries left from a previous run
+    pub fn with_store(store: Arc<StateManager>) -> Result<Self, std::io::Error> {
+        let pending = store.load_ai_log_queue()?;
+        if !pending.is_empty() {
+            tracing::info!("Restored {} pending AI log entries", pending.len());
+        }
+        Ok(AiLogQueue {
+            pending: Mutex::new(pending.into()),
+            store: Some(store),
+            ..Self::new()
+        })
+    }
+
+    /// Dead-letter an entry once the exchange has rejected it this many times
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Validate, persist and queue an entry
+    pub fn enqueue(&self, entry: AiLogEntry) -> Result<(), WeexError> {
+        entry.validate()?;
+        self.record(&AiLogRecord::Enqueued { entries: vec![entry.clone()] })?;
+        self.lock().push_back(entry);
+        self.notify.notify_one();
+        Ok(())
+    }
+
+    /// Validate, persist and queue entries as one journal record
+    ///
+    /// Either every entry is queued or none is.
+    pub fn enqueue_all(&self, entries: Vec<AiLogEntry>) -> Result<(), WeexError> {
+        for entry in &entries {
+            entry.validate()?;
+        }
+        if entries.is_empty() {
+            return Ok(());
+        }
+        self.record(&AiLogRecord::Enqueued { entries: entries.clone() })?;
+        self.lock().extend(entries);
+        self.notify.notify_one();
+        Ok(())
+    }
+
+    /// Entries dead-lettered by this queue after too many rejections
+    pub fn rejected(&self) -> Vec<AiLogEntry> {
+        self.rejected.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn pending(&self) -> Vec<AiLogEntry> {
+        self.lock().iter().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Upload pending entries in order; returns how many were acknowledged
+    ///
+    /// Stops at the first retryable failure and returns it.
+    pub async fn flush(&self, client: &WeexClient) -> Result<usize, WeexError> {
+        let mut acked = 0;
+        for entry in self.pending() {
+            match client.upload_ai_log_entry(&entry).await {
+                Ok(_) => {
+                    self.record(&AiLogRecord::Acked { id: entry.id.clone() })?;
+                    self.lock().retain(|e| e.id != entry.id);
+                    acked += 1;
+                }
+                Err(e) if is_retryable(&e) => return Err(e),
+                Err(e) => {
+                    tracing::error!("AI log {} ({}) rejected: {}", entry.id, entry.stage, e);
+                    self.record(&AiLogRecord::Rejected { id: entry.id.clone() })?;
+                    let exhausted = {
+                        let mut pending = self.lock();
+                        let Some(p) = pending.iter_mut().find(|p| p.id == entry.id) else { continue };
+                        p.attempts += 1;
+                        let exhausted = (p.attempts >= self.max_attempts).then(|| p.clone());
+                        if exhausted.is_some() {
+                            pending.retain(|p| p.id != entry.id);
+                        }
+                        exhausted
+                    };
+                    if let Some(entry) = exhausted {
+                        self.dead_letter(entry)?;
+                    }
+                }
+            }
+        }
+        Ok(acked)
+    }
+
+    /// Upload in the background until acknowledged, backing off per `retry`
+    ///
+    /// `retry.max_attempts` is ignored: the queue keeps retrying network failures.
+    /// Rejected entries are retried every `retry.max_delay_ms` until dead-lettered.
+    pub fn spawn(self: Arc<Self>, client: WeexClient, retry: RetryConfig) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut delay = retry.base_delay_ms;
+            loop {
+                let wait = match self.flush(&client).await {
+                    Ok(_) if self.is_empty() => None,
+                    Ok(_) => Some(retry.max_delay_ms),
+                    Err(e) => {
+                        tracing::warn!("AI log upload failed, retrying in {}ms: {}", delay, e);
+                        let wait = delay;
+                        delay = (delay * 2).min(retry.max_delay_ms);
+                        Some(wait)
+                    }
+                };
+                match wait {
+                    Some(ms) => {
+                        tokio::select! {
+                            _ = tokio::time::sleep(Duration::from_millis(ms)) => {}
+                            _ = self.notify.notified() => {}
+                        }
+                    }
+                    None => {
+                        delay = retry.base_delay_ms;
+                        self.notify.notified().await;
+                    }
+                }
+            }
+        })
+    }
+
+    /// Move an entry out of the queue into the dead-letter file
+    fn dead_letter(&self, entry: AiLogEntry) -> Result<(), WeexError> {
+        tracing::error!(
+            "AI log {} ({}) dead-lettered after {} rejections",
+            entry.id, entry.stage, entry.attempts
+        );
+        if let Some(store) = &self.store {
+            store.dead_letter_ai_log(&entry)?;
+        }
+        self.record(&AiLogRecord::DeadLettered { id: entry.id.clone() })?;
+        self.rejected.lock().unwrap_or_else(|e| e.into_inner()).push(entry);
+        Ok(())
+    }
+
+    fn record(&self, record: &AiLogRecord) -> Result<(), WeexError> {
+        if let Some(store) = &self.store {
+            store.append_ai_log_record(record)?;
+        }
+        Ok(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<AiLogEntry>> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{http_stub, http_stub_responses};
+    use serde_json::json;
+
+    fn entry() -> AiLogEntry {
+        AiLogEntry::builder(AILogStage::DecisionMaking)
+            .model("gpt-5.2")
+            .input(json!({"rsi": 28}))
+            .output(json!({"signal": "BUY"}))
+            .explanation("RSI oversold")
+            .order_id(12345)
+            .build()
+            .unwrap()
+    }
+
+    fn client(base_url: &str) -> WeexClient {
+        WeexClient::builder()
+            .base_url(base_url)
+            .api_key("k")
+            .secret_key("s")
+            .passphrase("p")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_builder_validates() {
+        let body = entry().request_body();
+        assert_eq!(body["stage"], "Decision Making");
+        assert_eq!(body["orderId"], 12345);
+
+        let missing = AiLogEntry::builder(AILogStage::Execution).model("m").build();
+        assert!(matches!(missing, Err(WeexError::InvalidAiLog(_))));
+
+        let huge = AiLogEntry::builder(AILogStage::Execution)
+            .model("m")
+            .explanation("x")
+            .input(json!("a".repeat(MAX_PAYLOAD_BYTES)))
+            .build();
+        assert!(matches!(huge, Err(WeexError::InvalidAiLog(_))));
+    }
+
+    #[tokio::test]
+    async fn test_queue_survives_failure_and_drains_on_ack() {
+        let dir = std::env::temp_dir().join(format!("weex_ai_log_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let store = Arc::new(StateManager::new(&dir.to_string_lossy()));
+
+        let queue = AiLogQueue::with_store(store.clone()).unwrap();
+        queue.enqueue(entry()).unwrap();
+
+        // Nothing listening: the upload fails and the entry stays queued on disk
+        let err = queue.flush(&client("http://127.0.0.1:9")).await.unwrap_err();
+        assert!(matches!(err, WeexError::Http(_)));
+        let restored = AiLogQueue::with_store(store.clone()).unwrap();
+        assert_eq!(restored.len(), 1);
+
+        let (url, stub) = http_stub().await;
+        assert_eq!(restored.flush(&client(&url)).await.unwrap(), 1);
+        let (request_line, body) = stub.await.unwrap();
+        assert!(request_line.starts_with("POST /capi/v2/order/uploadAiLog"));
+        assert!(body.contains("Decision Making"));
+        assert!(AiLogQueue::with_store(store).unwrap().is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rejected_entry_is_dead_lettered() {
+        let dir = std::env::temp_dir().join(format!("weex_ai_log_dead_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let store = Arc::new(StateManager::new(&dir.to_string_lossy()));
+        let queue = AiLogQueue::with_store(store.clone()).unwrap().with_max_attempts(2);
+        queue.enqueue(entry()).unwrap();
+
+        let rejection = r#"{"code":"40001","msg":"invalid stage"}"#.to_string();
+        let (url, stub) = http_stub_responses(vec![rejection.clone(), rejection]).await;
+        assert_eq!(queue.flush(&client(&url)).await.unwrap(), 0);
+        assert_eq!(AiLogQueue::with_store(store.clone()).unwrap().pending()[0].attempts, 1);
+
+        assert_eq!(queue.flush(&client(&url)).await.unwrap(), 0);
+        stub.await.unwrap();
+        assert!(queue.is_empty());
+        assert_eq!(queue.rejected().len(), 1);
+        assert!(AiLogQueue::with_store(store.clone()).unwrap().is_empty());
+        assert_eq!(store.load_rejected_ai_logs().unwrap()[0].attempts, 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
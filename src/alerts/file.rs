@@ -0,0 +1,70 @@
+use super::{Alert, AlertSink};
+use crate::error::WeexError;
+use crate::state::append_jsonl;
+use async_trait::async_trait;
+
+/// Writes alerts locally: JSON lines to a file, or one readable line each to stdout
+pub struct FileSink {
+    /// `None` writes to stdout
+    path: Option<String>,
+}
+
+impl FileSink {
+    /// Append alerts as JSON lines to `path`
+    pub fn new(path: &str) -> Self {
+        FileSink { path: Some(path.to_string()) }
+    }
+
+    /// Print alerts to stdout
+    pub fn stdout() -> Self {
+        FileSink { path: None }
+    }
+}
+
+#[async_trait]
+impl AlertSink for FileSink {
+    fn name(&self) -> &str {
+        match self.path {
+            Some(_) => "file",
+            None => "stdout",
+        }
+    }
+
+    async fn send(&self, alert: &Alert) -> Result<(), WeexError> {
+        match &self.path {
+            Some(path) => Ok(append_jsonl(path, alert)?),
+            None => {
+                println!(
+                    "{} [{}] {}",
+                    alert.timestamp.format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+                    alert.severity.to_string().to_uppercase(),
+                    alert.plain_text()
+                );
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::AlertType;
+    use crate::state::read_jsonl;
+
+    #[tokio::test]
+    async fn test_file_sink_appends_json_lines() {
+        let path = std::env::temp_dir().join(format!("weex_alerts_{}.jsonl", std::process::id()));
+        let path = path.to_string_lossy().into_owned();
+        let _ = std::fs::remove_file(&path);
+
+        let sink = FileSink::new(&path);
+        sink.send(&Alert::new(AlertType::Error, "one")).await.unwrap();
+        sink.send(&Alert::new(AlertType::Info, "two")).await.unwrap();
+
+        let alerts: Vec<Alert> = read_jsonl(&path).unwrap();
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0].message, "one");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,260 @@
+//! Alert channels
+//!
+//! An [`Alerter`] fans alerts out to any number of [`AlertSink`]s, each with its
+//! own minimum severity and optional set of alert types. [`Alerter::spawn`] moves
+//! it onto a background task that deduplicates, rate limits and digests alerts.
+//! [`RuleEngine`] raises alerts from `EventHandler` streams according to a
+//! [`RuleSet`] loaded from a file.
+
+pub mod file;
+pub mod queue;
+pub mod rules;
+pub mod telegram;
+pub mod webhook;
+
+pub use file::FileSink;
+pub use queue::{AlertHandle, AlertPolicy, RateLimitedSink};
+pub use rules::{AlertRule, RuleCondition, RuleEngine, RuleSet};
+pub use telegram::{TelegramAlerter, TelegramConfig};
+pub use webhook::{WebhookFormat, WebhookSink};
+
+use crate::error::WeexError;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+use strum::{Display, EnumString};
+
+/// Alert types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumString, Serialize, Deserialize)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum AlertType {
+    Trade,
+    Position,
+    Error,
+    Info,
+}
+
+impl AlertType {
+    pub(crate) fn emoji(&self) -> &str {
+        match self {
+            AlertType::Trade => "💰",
+            AlertType::Position => "📊",
+            AlertType::Error => "🚨",
+            AlertType::Info => "ℹ️",
+        }
+    }
+
+    /// Severity used when an alert does not set one explicitly
+    pub fn default_severity(&self) -> Severity {
+        match self {
+            AlertType::Error => Severity::Critical,
+            AlertType::Trade | AlertType::Position | AlertType::Info => Severity::Info,
+        }
+    }
+}
+
+/// Alert severity, ordered from least to most urgent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Display, EnumString, Serialize, Deserialize)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+/// A single alert
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Alert {
+    pub alert_type: AlertType,
+    pub severity: Severity,
+    /// Message body; may contain Telegram HTML tags such as `<b>`
+    pub message: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Alert {
+    pub fn new(alert_type: AlertType, message: &str) -> Self {
+        Alert {
+            alert_type,
+            severity: alert_type.default_severity(),
+            message: message.to_string(),
+            timestamp: Utc::now(),
+        }
+    }
+
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    /// Message prefixed with the alert type's emoji
+    pub fn text(&self) -> String {
+        format!("{} {}", self.alert_type.emoji(), self.message)
+    }
+
+    /// `text()` with HTML tags removed, for sinks that do not render them
+    pub fn plain_text(&self) -> String {
+        let mut out = String::with_capacity(self.message.len() + 4);
+        let mut in_tag = false;
+        for c in self.text().chars() {
+            match c {
+                '<' => in_tag = true,
+                '>' if in_tag => in_tag = false,
+                _ if !in_tag => out.push(c),
+                _ => {}
+            }
+        }
+        out
+    }
+}
+
+/// Destination for alerts
+#[async_trait]
+pub trait AlertSink: Send + Sync {
+    /// Short name used in logs
+    fn name(&self) -> &str;
+    async fn send(&self, alert: &Alert) -> Result<(), WeexError>;
+}
+
+struct Route {
+    sink: Arc<dyn AlertSink>,
+    min_severity: Severity,
+    /// `None` routes every type
+    types: Option<HashSet<AlertType>>,
+}
+
+impl Route {
+    fn accepts(&self, alert: &Alert) -> bool {
+        let type_matches = match &self.types {
+            Some(types) => types.contains(&alert.alert_type),
+            None => true,
+        };
+        alert.severity >= self.min_severity && type_matches
+    }
+}
+
+/// Fan-out alerter routing each alert to the sinks that accept its type and severity
+#[derive(Default)]
+pub struct Alerter {
+    routes: Vec<Route>,
+}
+
+impl Alerter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Route every alert to `sink`
+    pub fn sink(self, sink: Arc<dyn AlertSink>) -> Self {
+        self.route(sink, Severity::Info, &[])
+    }
+
+    /// Route alerts of at least `min_severity` to `sink`, limited to `types` unless empty
+    pub fn route(mut self, sink: Arc<dyn AlertSink>, min_severity: Severity, types: &[AlertType]) -> Self {
+        self.routes.push(Route {
+            sink,
+            min_severity,
+            types: (!types.is_empty()).then(|| types.iter().copied().collect()),
+        });
+        self
+    }
+
+    /// Send to every matching sink
+    ///
+    /// A failing sink does not stop delivery to the others; the last error is returned.
+    pub async fn send(&self, alert: &Alert) -> Result<(), WeexError> {
+        let mut result = Ok(());
+        for route in self.routes.iter().filter(|r| r.accepts(alert)) {
+            if let Err(e) = route.sink.send(alert).await {
+                tracing::warn!("Alert sink {} failed: {}", route.sink.name(), e);
+                result = Err(e);
+            }
+        }
+        result
+    }
+
+    /// Send trade notification
+    pub async fn notify_trade(&self, symbol: &str, side: &str, size: f64, price: f64) -> Result<(), WeexError> {
+        self.send(&Alert::new(AlertType::Trade, &telegram::trade_message(symbol, side, size, price))).await
+    }
+
+    /// Send position update
+    pub async fn notify_position(&self, symbol: &str, size: f64, pnl: f64) -> Result<(), WeexError> {
+        self.send(&Alert::new(AlertType::Position, &telegram::position_message(symbol, size, pnl))).await
+    }
+
+    /// Send error alert
+    pub async fn notify_error(&self, error: &str) -> Result<(), WeexError> {
+        self.send(&Alert::new(AlertType::Error, error)).await
+    }
+
+    /// Send info message
+    pub async fn notify_info(&self, message: &str) -> Result<(), WeexError> {
+        self.send(&Alert::new(AlertType::Info, message)).await
+    }
+}
+
+/// Map a non-2xx response to an API error
+pub(crate) async fn check_status(resp: reqwest::Response) -> Result<(), WeexError> {
+    let status = resp.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    Err(WeexError::Api {
+        code: status.as_u16().to_string(),
+        msg: resp.text().await.unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Sink that records what it receives
+    #[derive(Default)]
+    pub(crate) struct MemorySink {
+        pub(crate) alerts: Mutex<Vec<Alert>>,
+    }
+
+    #[async_trait]
+    impl AlertSink for MemorySink {
+        fn name(&self) -> &str {
+            "memory"
+        }
+
+        async fn send(&self, alert: &Alert) -> Result<(), WeexError> {
+            self.alerts.lock().unwrap().push(alert.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_routes_by_type_and_severity() {
+        let all = Arc::new(MemorySink::default());
+        let critical = Arc::new(MemorySink::default());
+        let trades = Arc::new(MemorySink::default());
+        let alerter = Alerter::new()
+            .sink(all.clone())
+            .route(critical.clone(), Severity::Critical, &[])
+            .route(trades.clone(), Severity::Info, &[AlertType::Trade]);
+
+        alerter.notify_error("boom").await.unwrap();
+        alerter.notify_trade("cmt_btcusdt", "buy", 0.1, 50000.0).await.unwrap();
+        alerter.send(&Alert::new(AlertType::Position, "margin").with_severity(Severity::Critical)).await.unwrap();
+
+        assert_eq!(all.alerts.lock().unwrap().len(), 3);
+        assert_eq!(critical.alerts.lock().unwrap().len(), 2);
+        assert_eq!(trades.alerts.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_plain_text_strips_tags() {
+        let alert = Alert::new(AlertType::Trade, "<b>BUY</b> cmt_btcusdt");
+        assert_eq!(alert.plain_text(), "💰 BUY cmt_btcusdt");
+    }
+}
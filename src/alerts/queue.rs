@@ -0,0 +1,343 @@
+use super::{Alert, AlertSink, AlertType, Alerter, Severity};
+use crate::error::WeexError;
+use async_trait::async_trait;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Throttling and batching for a background alerter
+#[derive(Debug, Clone)]
+pub struct AlertPolicy {
+    /// Identical alerts (same type and message) within this window are sent once
+    pub dedup_window: Duration,
+    /// Sends allowed per sink in any 60s window; 0 disables the limit
+    pub max_per_minute: usize,
+    /// Alerts below this severity are batched into periodic digests
+    pub digest_below: Severity,
+    pub digest_interval: Duration,
+    /// Alerts waiting to be sent before new ones are dropped
+    pub queue_capacity: usize,
+}
+
+impl Default for AlertPolicy {
+    fn default() -> Self {
+        AlertPolicy {
+            dedup_window: Duration::from_secs(60),
+            max_per_minute: 20,
+            digest_below: Severity::Warning,
+            digest_interval: Duration::from_secs(60),
+            queue_capacity: 1024,
+        }
+    }
+}
+
+/// Non-blocking handle to a background alerter
+///
+/// Sends never wait on the network: alerts are queued and dropped with a warning
+/// if the queue is full. The worker exits, flushing any pending digest, once every
+/// handle has been dropped.
+#[derive(Clone)]
+pub struct AlertHandle {
+    tx: mpsc::Sender<Alert>,
+}
+
+impl AlertHandle {
+    /// Queue an alert; returns false if it was dropped
+    pub fn send(&self, alert: Alert) -> bool {
+        match self.tx.try_send(alert) {
+            Ok(()) => true,
+            Err(mpsc::error::TrySendError::Full(alert)) => {
+                tracing::warn!("Alert queue full, dropping: {}", alert.plain_text());
+                false
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => false,
+        }
+    }
+
+    /// Queue trade notification
+    pub fn notify_trade(&self, symbol: &str, side: &str, size: f64, price: f64) -> bool {
+        self.send(Alert::new(AlertType::Trade, &super::telegram::trade_message(symbol, side, size, price)))
+    }
+
+    /// Queue position update
+    pub fn notify_position(&self, symbol: &str, size: f64, pnl: f64) -> bool {
+        self.send(Alert::new(AlertType::Position, &super::telegram::position_message(symbol, size, pnl)))
+    }
+
+    /// Queue error alert
+    pub fn notify_error(&self, error: &str) -> bool {
+        self.send(Alert::new(AlertType::Error, error))
+    }
+
+    /// Queue info message
+    pub fn notify_info(&self, message: &str) -> bool {
+        self.send(Alert::new(AlertType::Info, message))
+    }
+}
+
+impl Alerter {
+    /// Move the alerter onto a background task with deduplication, per-sink rate
+    /// limiting and digests
+    pub fn spawn(mut self, policy: AlertPolicy) -> (AlertHandle, JoinHandle<()>) {
+        if policy.max_per_minute > 0 {
+            for route in &mut self.routes {
+                let inner = route.sink.clone();
+                route.sink = Arc::new(RateLimitedSink::new(inner, policy.max_per_minute));
+            }
+        }
+        let (tx, mut rx) = mpsc::channel(policy.queue_capacity.max(1));
+
+        let task = tokio::spawn(async move {
+            let mut throttle = Throttle::new(policy.clone());
+            let mut digest_tick = tokio::time::interval(policy.digest_interval);
+            digest_tick.tick().await;
+
+            loop {
+                tokio::select! {
+                    received = rx.recv() => match received {
+                        Some(alert) => {
+                            if let Some(alert) = throttle.admit(alert, Instant::now()) {
+                                let _ = self.send(&alert).await;
+                            }
+                        }
+                        None => break,
+                    },
+                    _ = digest_tick.tick() => {
+                        throttle.collect_suppressed(Some(Instant::now()));
+                        if let Some(digest) = throttle.take_digest() {
+                            let _ = self.send(&digest).await;
+                        }
+                    }
+                }
+            }
+            throttle.collect_suppressed(None);
+            if let Some(digest) = throttle.take_digest() {
+                let _ = self.send(&digest).await;
+            }
+        });
+        (AlertHandle { tx }, task)
+    }
+}
+
+/// Deduplication and digest batching
+///
+/// Suppressed repeats are reported either on the next copy let through after the
+/// window, or as a summary in the next digest once the window has passed.
+struct Throttle {
+    policy: AlertPolicy,
+    /// Last time each (type, message) was let through, repeats suppressed since, and their severity
+    recent: HashMap<(AlertType, String), (Instant, u64, Severity)>,
+    pending: Vec<Alert>,
+}
+
+impl Throttle {
+    fn new(policy: AlertPolicy) -> Self {
+        Throttle {
+            policy,
+            recent: HashMap::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Returns the alert to send now, or `None` if it was suppressed or queued for the digest
+    fn admit(&mut self, mut alert: Alert, now: Instant) -> Option<Alert> {
+        let window = self.policy.dedup_window;
+        let key = (alert.alert_type, alert.message.clone());
+        if let Some((at, repeats, _)) = self.recent.get_mut(&key) {
+            if now.duration_since(*at) < window {
+                *repeats += 1;
+                return None;
+            }
+            if *repeats > 0 {
+                alert.message = format!("{} (repeated {} times)", alert.message, repeats);
+            }
+        }
+        self.recent.insert(key, (now, 0, alert.severity));
+        // Entries with unreported repeats stay until reported
+        self.recent.retain(|_, (at, repeats, _)| now.duration_since(*at) < window || *repeats > 0);
+
+        if alert.severity < self.policy.digest_below {
+            self.pending.push(alert);
+            return None;
+        }
+        Some(alert)
+    }
+
+    /// Queue a summary for the digest of every alert whose repeats were suppressed
+    ///
+    /// Only entries whose window has passed by `now` are summarized; `None` takes all.
+    fn collect_suppressed(&mut self, now: Option<Instant>) {
+        let window = self.policy.dedup_window;
+        let mut summaries = Vec::new();
+        self.recent.retain(|(alert_type, message), (at, repeats, severity)| {
+            let expired = match now {
+                Some(now) => now.duration_since(*at) >= window,
+                None => true,
+            };
+            if !expired {
+                return true;
+            }
+            if *repeats > 0 {
+                let text = format!("{} (suppressed {} repeats)", message, repeats);
+                summaries.push(Alert::new(*alert_type, &text).with_severity(*severity));
+            }
+            false
+        });
+        self.pending.extend(summaries);
+    }
+
+    /// Combine pending low-severity alerts into one
+    fn take_digest(&mut self) -> Option<Alert> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        let alerts = std::mem::take(&mut self.pending);
+        let severity = alerts.iter().map(|a| a.severity).max().unwrap_or(Severity::Info);
+        let mut message = format!("<b>Digest</b>: {} alerts", alerts.len());
+        for alert in &alerts {
+            message.push_str(&format!("\n{} {}", alert.timestamp.format("%H:%M:%S"), alert.text()));
+        }
+        Some(Alert::new(AlertType::Info, &message).with_severity(severity))
+    }
+}
+
+/// Sink wrapper allowing at most `max_per_minute` sends in any 60s window
+///
+/// Alerts over the limit are dropped; the count is appended to the next alert
+/// that gets through.
+pub struct RateLimitedSink {
+    inner: Arc<dyn AlertSink>,
+    max_per_minute: usize,
+    sent: Mutex<VecDeque<Instant>>,
+    dropped: AtomicU64,
+}
+
+impl RateLimitedSink {
+    pub fn new(inner: Arc<dyn AlertSink>, max_per_minute: usize) -> Self {
+        RateLimitedSink {
+            inner,
+            max_per_minute,
+            sent: Mutex::new(VecDeque::new()),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Alerts dropped since the last successful send
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    fn try_acquire(&self, now: Instant) -> bool {
+        let mut sent = self.sent.lock().unwrap_or_else(|e| e.into_inner());
+        while sent.front().is_some_and(|t| now.duration_since(*t) >= Duration::from_secs(60)) {
+            sent.pop_front();
+        }
+        if sent.len() >= self.max_per_minute {
+            return false;
+        }
+        sent.push_back(now);
+        true
+    }
+}
+
+#[async_trait]
+impl AlertSink for RateLimitedSink {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn send(&self, alert: &Alert) -> Result<(), WeexError> {
+        if !self.try_acquire(Instant::now()) {
+            if self.dropped.fetch_add(1, Ordering::Relaxed) == 0 {
+                tracing::warn!("Alert sink {} rate limited", self.inner.name());
+            }
+            return Ok(());
+        }
+        let dropped = self.dropped.swap(0, Ordering::Relaxed);
+        if dropped == 0 {
+            return self.inner.send(alert).await;
+        }
+        let mut alert = alert.clone();
+        alert.message = format!("{}\n(+{} alerts dropped by rate limit)", alert.message, dropped);
+        self.inner.send(&alert).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::tests::MemorySink;
+
+    #[test]
+    fn test_dedup_and_digest() {
+        let mut throttle = Throttle::new(AlertPolicy::default());
+        let start = Instant::now();
+
+        assert!(throttle.admit(Alert::new(AlertType::Error, "boom"), start).is_some());
+        for i in 1..=5 {
+            assert!(throttle.admit(Alert::new(AlertType::Error, "boom"), start + Duration::from_secs(i)).is_none());
+        }
+        let later = throttle.admit(Alert::new(AlertType::Error, "boom"), start + Duration::from_secs(61)).unwrap();
+        assert_eq!(later.message, "boom (repeated 5 times)");
+
+        assert!(throttle.admit(Alert::new(AlertType::Info, "a"), start).is_none());
+        assert!(throttle.admit(Alert::new(AlertType::Trade, "b"), start).is_none());
+        let digest = throttle.take_digest().unwrap();
+        assert!(digest.message.starts_with("<b>Digest</b>: 2 alerts"));
+        assert!(throttle.take_digest().is_none());
+    }
+
+    #[test]
+    fn test_repeats_survive_other_alerts() {
+        let mut throttle = Throttle::new(AlertPolicy::default());
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        assert!(throttle.admit(Alert::new(AlertType::Error, "boom"), at(0)).is_some());
+        assert!(throttle.admit(Alert::new(AlertType::Error, "boom"), at(1)).is_none());
+        // Another alert after the window must not discard the pending repeat count
+        assert!(throttle.admit(Alert::new(AlertType::Error, "other"), at(90)).is_some());
+        let later = throttle.admit(Alert::new(AlertType::Error, "boom"), at(95)).unwrap();
+        assert_eq!(later.message, "boom (repeated 1 times)");
+
+        // A repeat that never recurs is summarized in the digest once its window passes
+        assert!(throttle.admit(Alert::new(AlertType::Error, "other"), at(100)).is_none());
+        throttle.collect_suppressed(Some(at(120)));
+        assert!(throttle.take_digest().is_none());
+        throttle.collect_suppressed(Some(at(151)));
+        let digest = throttle.take_digest().unwrap();
+        assert!(digest.message.contains("other (suppressed 1 repeats)"));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_sink_reports_drops() {
+        let memory = Arc::new(MemorySink::default());
+        let sink = RateLimitedSink::new(memory.clone(), 2);
+        for i in 0..5 {
+            sink.send(&Alert::new(AlertType::Error, &i.to_string())).await.unwrap();
+        }
+        assert_eq!(memory.alerts.lock().unwrap().len(), 2);
+        assert_eq!(sink.dropped(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_background_worker_flushes_on_shutdown() {
+        let memory = Arc::new(MemorySink::default());
+        let (handle, task) = Alerter::new().sink(memory.clone()).spawn(AlertPolicy::default());
+
+        for _ in 0..50 {
+            assert!(handle.notify_error("stuck in a loop"));
+        }
+        handle.notify_info("heartbeat");
+        drop(handle);
+        task.await.unwrap();
+
+        let alerts = memory.alerts.lock().unwrap();
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0].message, "stuck in a loop");
+        assert!(alerts[1].message.contains("heartbeat"));
+    }
+}
@@ -0,0 +1,427 @@
+use super::{Alert, AlertHandle, AlertType, Severity};
+use crate::error::WeexError;
+use crate::symbol::Symbol;
+use crate::ws::handler::{AccountEvent, MarketEvent};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+/// Direction a price must cross a level in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CrossDirection {
+    Up,
+    Down,
+    #[default]
+    Either,
+}
+
+/// What a rule watches for
+///
+/// Threshold conditions fire when they become true and re-arm once they are false
+/// again. A missing `symbol` matches every symbol.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RuleCondition {
+    PriceCrosses {
+        symbol: Symbol,
+        level: f64,
+        #[serde(default)]
+        direction: CrossDirection,
+    },
+    FundingRateAbove {
+        #[serde(default)]
+        symbol: Option<Symbol>,
+        threshold: f64,
+    },
+    PositionPnlBelow {
+        #[serde(default)]
+        symbol: Option<Symbol>,
+        threshold: f64,
+    },
+    MarginRatioAbove {
+        #[serde(default)]
+        symbol: Option<Symbol>,
+        threshold: f64,
+    },
+    /// Order update with a `rejected` or `failed` status
+    OrderRejected,
+    /// WebSocket down for at least `seconds`
+    WsDisconnected { seconds: u64 },
+}
+
+impl RuleCondition {
+    fn alert_type(&self) -> AlertType {
+        match self {
+            RuleCondition::PriceCrosses { .. } | RuleCondition::FundingRateAbove { .. } => AlertType::Info,
+            RuleCondition::PositionPnlBelow { .. } | RuleCondition::MarginRatioAbove { .. } => AlertType::Position,
+            RuleCondition::OrderRejected | RuleCondition::WsDisconnected { .. } => AlertType::Error,
+        }
+    }
+
+    fn default_severity(&self) -> Severity {
+        match self {
+            RuleCondition::PriceCrosses { .. }
+            | RuleCondition::FundingRateAbove { .. }
+            | RuleCondition::OrderRejected => Severity::Warning,
+            RuleCondition::PositionPnlBelow { .. }
+            | RuleCondition::MarginRatioAbove { .. }
+            | RuleCondition::WsDisconnected { .. } => Severity::Critical,
+        }
+    }
+}
+
+fn default_cooldown() -> u64 {
+    60
+}
+
+/// A named alert rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub name: String,
+    #[serde(flatten)]
+    pub condition: RuleCondition,
+    /// Overrides the condition's default severity
+    #[serde(default)]
+    pub severity: Option<Severity>,
+    /// Minimum seconds between firings for the same symbol
+    #[serde(default = "default_cooldown")]
+    pub cooldown_secs: u64,
+}
+
+/// Rules loaded from a JSON file
+///
+/// ```json
+/// { "rules": [
+///     { "name": "BTC 100k", "kind": "price_crosses", "symbol": "cmt_btcusdt", "level": 100000 },
+///     { "name": "Margin", "kind": "margin_ratio_above", "threshold": 0.8, "cooldown_secs": 300 }
+/// ] }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RuleSet {
+    pub rules: Vec<AlertRule>,
+}
+
+impl RuleSet {
+    pub fn from_json(json: &str) -> Result<Self, WeexError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn load(path: &str) -> Result<Self, WeexError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// Per-rule firing state
+struct RuleState {
+    rule: AlertRule,
+    /// Whether a threshold condition currently holds, per symbol
+    active: HashMap<String, bool>,
+    last_price: HashMap<String, f64>,
+    last_fired: HashMap<String, Instant>,
+}
+
+impl RuleState {
+    fn symbol_matches(&self, symbol: &str) -> bool {
+        let want = match &self.rule.condition {
+            RuleCondition::PriceCrosses { symbol, .. } => Some(symbol),
+            RuleCondition::FundingRateAbove { symbol, .. }
+            | RuleCondition::PositionPnlBelow { symbol, .. }
+            | RuleCondition::MarginRatioAbove { symbol, .. } => symbol.as_ref(),
+            RuleCondition::OrderRejected | RuleCondition::WsDisconnected { .. } => None,
+        };
+        match want {
+            Some(want) => want.matches(symbol),
+            None => true,
+        }
+    }
+
+    fn fire(&mut self, key: &str, now: Instant, detail: String) -> Option<Alert> {
+        let cooldown = Duration::from_secs(self.rule.cooldown_secs);
+        if let Some(last) = self.last_fired.get(key) {
+            if now.duration_since(*last) < cooldown {
+                return None;
+            }
+        }
+        self.last_fired.insert(key.to_string(), now);
+        let severity = self.rule.severity.unwrap_or_else(|| self.rule.condition.default_severity());
+        let message = format!("<b>{}</b>: {}", self.rule.name, detail);
+        Some(Alert::new(self.rule.condition.alert_type(), &message).with_severity(severity))
+    }
+
+    /// Fire on the transition into `holds`
+    ///
+    /// A firing held back by the cooldown is retried while the condition holds, so
+    /// the alert goes out once the cooldown ends rather than being lost.
+    fn threshold(&mut self, key: &str, holds: bool, now: Instant, detail: impl FnOnce() -> String) -> Option<Alert> {
+        if !holds {
+            self.active.insert(key.to_string(), false);
+            return None;
+        }
+        if self.active.get(key).copied().unwrap_or(false) {
+            return None;
+        }
+        let alert = self.fire(key, now, detail());
+        if alert.is_some() {
+            self.active.insert(key.to_string(), true);
+        }
+        alert
+    }
+}
+
+/// Evaluates [`AlertRule`]s against market and account events
+pub struct RuleEngine {
+    rules: Vec<RuleState>,
+    disconnected_since: Option<Instant>,
+}
+
+impl RuleEngine {
+    pub fn new(rules: RuleSet) -> Self {
+        RuleEngine {
+            rules: rules
+                .rules
+                .into_iter()
+                .map(|rule| RuleState {
+                    rule,
+                    active: HashMap::new(),
+                    last_price: HashMap::new(),
+                    last_fired: HashMap::new(),
+                })
+                .collect(),
+            disconnected_since: None,
+        }
+    }
+
+    pub fn on_market(&mut self, event: &MarketEvent, now: Instant) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        match event {
+            MarketEvent::Ticker { symbol, price } => {
+                for state in self.rules.iter_mut().filter(|s| s.symbol_matches(symbol)) {
+                    if let RuleCondition::PriceCrosses { level, direction, .. } = state.rule.condition {
+                        let Some(prev) = state.last_price.insert(symbol.clone(), *price) else { continue };
+                        let up = prev < level && *price >= level;
+                        let down = prev > level && *price <= level;
+                        let crossed = match direction {
+                            CrossDirection::Up => up,
+                            CrossDirection::Down => down,
+                            CrossDirection::Either => up || down,
+                        };
+                        if crossed {
+                            let way = if up { "above" } else { "below" };
+                            alerts.extend(state.fire(symbol, now, format!("{} crossed {} {} at {}", symbol, way, level, price)));
+                        }
+                    }
+                }
+            }
+            MarketEvent::FundingRate { symbol, rate } => {
+                for state in self.rules.iter_mut().filter(|s| s.symbol_matches(symbol)) {
+                    if let RuleCondition::FundingRateAbove { threshold, .. } = state.rule.condition {
+                        let detail = || format!("{} funding rate {} above {}", symbol, rate, threshold);
+                        alerts.extend(state.threshold(symbol, *rate >= threshold, now, detail));
+                    }
+                }
+            }
+            MarketEvent::Connection { connected: true } => {
+                self.disconnected_since = None;
+                for state in &mut self.rules {
+                    if let RuleCondition::WsDisconnected { .. } = state.rule.condition {
+                        state.active.clear();
+                    }
+                }
+            }
+            MarketEvent::Connection { connected: false } => {
+                self.disconnected_since.get_or_insert(now);
+            }
+            _ => {}
+        }
+        alerts
+    }
+
+    pub fn on_account(&mut self, event: &AccountEvent, now: Instant) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        match event {
+            AccountEvent::PositionUpdate { symbol, size, pnl, .. } => {
+                for state in self.rules.iter_mut().filter(|s| s.symbol_matches(symbol)) {
+                    if let RuleCondition::PositionPnlBelow { threshold, .. } = state.rule.condition {
+                        let holds = *size != 0.0 && *pnl <= threshold;
+                        let detail = || format!("{} PnL {:.2} below {}", symbol, pnl, threshold);
+                        alerts.extend(state.threshold(symbol, holds, now, detail));
+                    }
+                }
+            }
+            AccountEvent::MarginUpdate { symbol, margin_ratio } => {
+                for state in self.rules.iter_mut().filter(|s| s.symbol_matches(symbol)) {
+                    if let RuleCondition::MarginRatioAbove { threshold, .. } = state.rule.condition {
+                        let detail = || format!("{} margin ratio {:.4} above {}", symbol, margin_ratio, threshold);
+                        alerts.extend(state.threshold(symbol, *margin_ratio >= threshold, now, detail));
+                    }
+                }
+            }
+            AccountEvent::OrderUpdate { order_id, status, .. } => {
+                let status_lower = status.to_lowercase();
+                if status_lower.contains("reject") || status_lower.contains("fail") {
+                    for state in &mut self.rules {
+                        if let RuleCondition::OrderRejected = state.rule.condition {
+                            alerts.extend(state.fire("", now, format!("order {} {}", order_id, status)));
+                        }
+                    }
+                }
+            }
+            AccountEvent::BalanceUpdate { .. } => {}
+        }
+        alerts
+    }
+
+    /// Check time-based conditions (WebSocket downtime)
+    pub fn on_tick(&mut self, now: Instant) -> Vec<Alert> {
+        let Some(since) = self.disconnected_since else { return Vec::new() };
+        let down = now.duration_since(since);
+        let mut alerts = Vec::new();
+        for state in &mut self.rules {
+            if let RuleCondition::WsDisconnected { seconds } = state.rule.condition {
+                let holds = down >= Duration::from_secs(seconds);
+                let detail = || format!("WebSocket disconnected for {}s", down.as_secs());
+                alerts.extend(state.threshold("", holds, now, detail));
+            }
+        }
+        alerts
+    }
+
+    /// Evaluate events from `EventHandler` streams in the background, sending alerts through `alerts`
+    ///
+    /// Runs until both streams close.
+    pub fn spawn(
+        mut self,
+        mut market_rx: broadcast::Receiver<MarketEvent>,
+        mut account_rx: broadcast::Receiver<AccountEvent>,
+        alerts: AlertHandle,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(Duration::from_secs(1));
+            let (mut market_open, mut account_open) = (true, true);
+            while market_open || account_open {
+                let fired = tokio::select! {
+                    event = market_rx.recv(), if market_open => match event {
+                        Ok(event) => self.on_market(&event, Instant::now()),
+                        Err(broadcast::error::RecvError::Lagged(n)) => {
+                            tracing::warn!("Alert rules skipped {} market events", n);
+                            Vec::new()
+                        }
+                        Err(broadcast::error::RecvError::Closed) => {
+                            market_open = false;
+                            Vec::new()
+                        }
+                    },
+                    event = account_rx.recv(), if account_open => match event {
+                        Ok(event) => self.on_account(&event, Instant::now()),
+                        Err(broadcast::error::RecvError::Lagged(n)) => {
+                            tracing::warn!("Alert rules skipped {} account events", n);
+                            Vec::new()
+                        }
+                        Err(broadcast::error::RecvError::Closed) => {
+                            account_open = false;
+                            Vec::new()
+                        }
+                    },
+                    _ = tick.tick() => self.on_tick(Instant::now()),
+                };
+                for alert in fired {
+                    alerts.send(alert);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::tests::MemorySink;
+    use crate::alerts::{AlertPolicy, Alerter};
+    use crate::ws::handler::EventHandler;
+    use std::sync::Arc;
+
+    const RULES: &str = r#"{ "rules": [
+        { "name": "BTC 100k", "kind": "price_crosses", "symbol": "cmt_btcusdt", "level": 100000, "direction": "up" },
+        { "name": "Funding", "kind": "funding_rate_above", "threshold": 0.001 },
+        { "name": "Drawdown", "kind": "position_pnl_below", "threshold": -500, "cooldown_secs": 0 },
+        { "name": "Margin", "kind": "margin_ratio_above", "threshold": 0.8, "severity": "warning" },
+        { "name": "Rejects", "kind": "order_rejected" },
+        { "name": "WS down", "kind": "ws_disconnected", "seconds": 30 }
+    ] }"#;
+
+    #[test]
+    fn test_rules_fire_on_transitions() {
+        let mut engine = RuleEngine::new(RuleSet::from_json(RULES).unwrap());
+        let now = Instant::now();
+        let ticker = |price| MarketEvent::Ticker { symbol: "BTCUSDT".to_string(), price };
+
+        assert!(engine.on_market(&ticker(99000.0), now).is_empty());
+        let fired = engine.on_market(&ticker(100500.0), now);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].severity, Severity::Warning);
+        // Crossing back down does not match an "up" rule
+        assert!(engine.on_market(&ticker(99000.0), now).is_empty());
+
+        let position = |pnl| AccountEvent::PositionUpdate {
+            symbol: "cmt_ethusdt".to_string(),
+            size: 1.0,
+            entry_price: 3000.0,
+            pnl,
+        };
+        assert_eq!(engine.on_account(&position(-600.0), now).len(), 1);
+        assert!(engine.on_account(&position(-700.0), now).is_empty());
+        assert!(engine.on_account(&position(-100.0), now).is_empty());
+        assert_eq!(engine.on_account(&position(-650.0), now).len(), 1);
+
+        let margin = AccountEvent::MarginUpdate { symbol: "cmt_btcusdt".to_string(), margin_ratio: 0.9 };
+        assert_eq!(engine.on_account(&margin, now)[0].severity, Severity::Warning);
+
+        engine.on_market(&MarketEvent::Connection { connected: false }, now);
+        assert!(engine.on_tick(now + Duration::from_secs(10)).is_empty());
+        let fired = engine.on_tick(now + Duration::from_secs(31));
+        assert_eq!(fired[0].alert_type, AlertType::Error);
+        assert!(engine.on_tick(now + Duration::from_secs(40)).is_empty());
+    }
+
+    #[test]
+    fn test_cooldown_defers_threshold_alert() {
+        let rules = r#"{ "rules": [{ "name": "Margin", "kind": "margin_ratio_above", "threshold": 0.8, "cooldown_secs": 60 }] }"#;
+        let mut engine = RuleEngine::new(RuleSet::from_json(rules).unwrap());
+        let now = Instant::now();
+        let at = |secs| now + Duration::from_secs(secs);
+        let margin = |ratio| AccountEvent::MarginUpdate { symbol: "cmt_btcusdt".to_string(), margin_ratio: ratio };
+
+        assert_eq!(engine.on_account(&margin(0.9), at(0)).len(), 1);
+        assert!(engine.on_account(&margin(0.5), at(10)).is_empty());
+        // Re-entering within the cooldown is held back, not forgotten
+        assert!(engine.on_account(&margin(0.9), at(20)).is_empty());
+        assert!(engine.on_account(&margin(0.9), at(40)).is_empty());
+        assert_eq!(engine.on_account(&margin(0.95), at(61)).len(), 1);
+        assert!(engine.on_account(&margin(0.95), at(200)).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_spawned_rules_send_through_alerter() {
+        let sink = Arc::new(MemorySink::default());
+        let (alerts, alert_task) = Alerter::new().sink(sink.clone()).spawn(AlertPolicy::default());
+        let (handler, market_rx, account_rx) = EventHandler::new();
+        let rules = RuleEngine::new(RuleSet::from_json(RULES).unwrap()).spawn(market_rx, account_rx, alerts);
+
+        handler.emit_account(AccountEvent::OrderUpdate {
+            order_id: "42".to_string(),
+            status: "rejected".to_string(),
+            filled_size: 0.0,
+        });
+        handler.emit_market(MarketEvent::FundingRate { symbol: "BTCUSDT".to_string(), rate: 0.002 });
+        drop(handler);
+        rules.await.unwrap();
+        alert_task.await.unwrap();
+
+        let received = sink.alerts.lock().unwrap();
+        assert_eq!(received.len(), 2);
+        assert!(received.iter().any(|a| a.message.contains("order 42 rejected")));
+    }
+}
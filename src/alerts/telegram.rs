@@ -0,0 +1,187 @@
+use super::{check_status, Alert, AlertHandle, AlertPolicy, AlertSink, AlertType, Alerter};
+use crate::error::WeexError;
+use async_trait::async_trait;
+use reqwest::Client;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+/// Default Telegram Bot API endpoint
+pub const TELEGRAM_API_URL: &str = "https://api.telegram.org";
+
+/// Telegram bot configuration
+#[derive(Debug, Clone)]
+pub struct TelegramConfig {
+    pub bot_token: String,
+    pub chat_id: String,
+    pub enabled: bool,
+    /// Bot API base URL; override to point at a local stub or proxy
+    pub api_url: String,
+}
+
+impl TelegramConfig {
+    pub fn new(bot_token: &str, chat_id: &str) -> Self {
+        TelegramConfig {
+            bot_token: bot_token.to_string(),
+            chat_id: chat_id.to_string(),
+            enabled: true,
+            api_url: TELEGRAM_API_URL.to_string(),
+        }
+    }
+
+    pub fn disabled() -> Self {
+        TelegramConfig {
+            bot_token: String::new(),
+            chat_id: String::new(),
+            enabled: false,
+            api_url: TELEGRAM_API_URL.to_string(),
+        }
+    }
+
+    pub fn with_api_url(mut self, api_url: &str) -> Self {
+        self.api_url = api_url.trim_end_matches('/').to_string();
+        self
+    }
+}
+
+/// Telegram alerter
+///
+/// `send` and the `notify_*` methods await the Bot API request, so calling them
+/// from a trading loop stalls it for as long as Telegram takes to answer. Use
+/// [`TelegramAlerter::spawn`] (or an [`Alerter`](super::Alerter) with this as a
+/// sink) to get an [`AlertHandle`] whose methods only queue the alert.
+pub struct TelegramAlerter {
+    config: TelegramConfig,
+    client: Client,
+}
+
+impl TelegramAlerter {
+    pub fn new(config: TelegramConfig) -> Self {
+        TelegramAlerter {
+            config,
+            client: Client::new(),
+        }
+    }
+
+    /// Move the alerter onto a background queue; the returned handle never waits on the network
+    pub fn spawn(self, policy: AlertPolicy) -> (AlertHandle, JoinHandle<()>) {
+        Alerter::new().sink(Arc::new(self)).spawn(policy)
+    }
+
+    /// Send a message to Telegram, waiting for the request to complete
+    pub async fn send(&self, alert_type: AlertType, message: &str) -> Result<(), reqwest::Error> {
+        self.post(&Alert::new(alert_type, message).text()).await?;
+        Ok(())
+    }
+
+    async fn post(&self, text: &str) -> Result<Option<reqwest::Response>, reqwest::Error> {
+        if !self.config.enabled {
+            return Ok(None);
+        }
+
+        let url = format!("{}/bot{}/sendMessage", self.config.api_url, self.config.bot_token);
+
+        let params = [
+            ("chat_id", self.config.chat_id.as_str()),
+            ("text", text),
+            ("parse_mode", "HTML"),
+        ];
+
+        Ok(Some(self.client.post(&url).form(&params).send().await?))
+    }
+
+    /// Send trade notification, waiting for the request to complete
+    pub async fn notify_trade(
+        &self,
+        symbol: &str,
+        side: &str,
+        size: f64,
+        price: f64,
+    ) -> Result<(), reqwest::Error> {
+        self.send(AlertType::Trade, &trade_message(symbol, side, size, price)).await
+    }
+
+    /// Send position update, waiting for the request to complete
+    pub async fn notify_position(
+        &self,
+        symbol: &str,
+        size: f64,
+        pnl: f64,
+    ) -> Result<(), reqwest::Error> {
+        self.send(AlertType::Position, &position_message(symbol, size, pnl)).await
+    }
+
+    /// Send error alert, waiting for the request to complete
+    pub async fn notify_error(&self, error: &str) -> Result<(), reqwest::Error> {
+        self.send(AlertType::Error, error).await
+    }
+
+    /// Send info message, waiting for the request to complete
+    pub async fn notify_info(&self, message: &str) -> Result<(), reqwest::Error> {
+        self.send(AlertType::Info, message).await
+    }
+}
+
+#[async_trait]
+impl AlertSink for TelegramAlerter {
+    fn name(&self) -> &str {
+        "telegram"
+    }
+
+    async fn send(&self, alert: &Alert) -> Result<(), WeexError> {
+        match self.post(&alert.text()).await? {
+            Some(resp) => check_status(resp).await,
+            None => Ok(()),
+        }
+    }
+}
+
+pub(crate) fn trade_message(symbol: &str, side: &str, size: f64, price: f64) -> String {
+    format!(
+        "<b>{}</b> {}\nSize: {:.4}\nPrice: ${:.2}",
+        side.to_uppercase(),
+        symbol,
+        size,
+        price
+    )
+}
+
+pub(crate) fn position_message(symbol: &str, size: f64, pnl: f64) -> String {
+    let pnl_emoji = if pnl >= 0.0 { "🟢" } else { "🔴" };
+    format!(
+        "{} <b>{}</b>\nSize: {:.4}\nPnL: ${:.2}",
+        pnl_emoji, symbol, size, pnl
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::http_stub;
+
+    #[tokio::test]
+    async fn test_sends_to_configured_api_url() {
+        let (url, stub) = http_stub().await;
+        let alerter = TelegramAlerter::new(TelegramConfig::new("TOKEN", "42").with_api_url(&url));
+
+        AlertSink::send(&alerter, &Alert::new(AlertType::Info, "hello")).await.unwrap();
+
+        let (request_line, body) = stub.await.unwrap();
+        assert!(request_line.starts_with("POST /botTOKEN/sendMessage"));
+        assert!(body.contains("chat_id=42"));
+        assert!(body.contains("hello"));
+    }
+
+    #[tokio::test]
+    async fn test_spawned_handle_queues_instead_of_waiting() {
+        let (url, stub) = http_stub().await;
+        let alerter = TelegramAlerter::new(TelegramConfig::new("TOKEN", "42").with_api_url(&url));
+        let (handle, task) = alerter.spawn(AlertPolicy::default());
+
+        assert!(handle.notify_error("margin call"));
+        drop(handle);
+        task.await.unwrap();
+
+        let (_, body) = stub.await.unwrap();
+        assert!(body.contains("margin+call"));
+    }
+}
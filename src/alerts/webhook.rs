@@ -0,0 +1,83 @@
+use super::{check_status, Alert, AlertSink};
+use crate::error::WeexError;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// JSON payload shape for a webhook
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookFormat {
+    /// `{"text": ...}` (Slack incoming webhooks)
+    Slack,
+    /// `{"content": ...}` (Discord webhooks)
+    Discord,
+    /// The full [`Alert`] as JSON
+    #[default]
+    Generic,
+}
+
+/// Posts alerts as JSON to an HTTP endpoint
+pub struct WebhookSink {
+    url: String,
+    format: WebhookFormat,
+    client: Client,
+}
+
+impl WebhookSink {
+    pub fn new(url: &str, format: WebhookFormat) -> Self {
+        WebhookSink {
+            url: url.to_string(),
+            format,
+            client: Client::new(),
+        }
+    }
+
+    pub fn slack(url: &str) -> Self {
+        Self::new(url, WebhookFormat::Slack)
+    }
+
+    pub fn discord(url: &str) -> Self {
+        Self::new(url, WebhookFormat::Discord)
+    }
+
+    fn payload(&self, alert: &Alert) -> serde_json::Value {
+        match self.format {
+            WebhookFormat::Slack => json!({ "text": alert.plain_text() }),
+            WebhookFormat::Discord => json!({ "content": alert.plain_text() }),
+            WebhookFormat::Generic => json!(alert),
+        }
+    }
+}
+
+#[async_trait]
+impl AlertSink for WebhookSink {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    async fn send(&self, alert: &Alert) -> Result<(), WeexError> {
+        let resp = self.client.post(&self.url).json(&self.payload(alert)).send().await?;
+        check_status(resp).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::http_stub;
+    use crate::alerts::AlertType;
+
+    #[tokio::test]
+    async fn test_slack_payload_is_plain_text() {
+        let (url, stub) = http_stub().await;
+        let sink = WebhookSink::slack(&format!("{}/hook", url));
+        sink.send(&Alert::new(AlertType::Trade, "<b>BUY</b> cmt_btcusdt")).await.unwrap();
+
+        let (request_line, body) = stub.await.unwrap();
+        assert!(request_line.starts_with("POST /hook"));
+        let v: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(v["text"], "💰 BUY cmt_btcusdt");
+    }
+}
@@ -0,0 +1,820 @@
+/*!
+ * weex-cli - Command Line Interface for WEEX Rust SDK
+ *
+ * This binary wraps the weex_rust_sdk library to provide
+ * a CLI interface that can be called from other languages (TypeScript, Python).
+ * By default every command prints one JSON envelope: `{"success": true, "data": ...}`
+ * or `{"success": false, "error": "..."}`; `--output table|csv` renders the data for
+ * a terminal instead.
+ *
+ * Credentials come from environment variables or from a named profile in
+ * the TOML config file (`--profile live`).
+ */
+
+use weex_rust_sdk::{WeexClient, WeexError, Symbol, StateManager, ExportFilter, AiLogEntry, Config, Credentials};
+use weex_rust_sdk::types::{AILogStage, MarginMode, OrderType, PositionSide, Side, TimeInForce};
+use weex_rust_sdk::export::{check_page_covers, write_csv};
+use weex_rust_sdk::future::account::FundingPayment;
+use weex_rust_sdk::future::order::FuturesOrderRequest;
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use rust_decimal::Decimal;
+use std::env;
+use std::path::PathBuf;
+use serde_json::{json, Value};
+
+mod output;
+mod serve;
+mod watch;
+
+use output::{fail, OutputFormat, CANDLE_COLUMNS};
+use watch::WatchCommand;
+
+/// CLI for WEEX Rust SDK
+///
+/// Credentials are read from WEEX_API_KEY, WEEX_SECRET_KEY and WEEX_PASSPHRASE;
+/// WEEX_BASE_URL overrides the API host; without any keys, market commands and
+/// public streams still work. With `--profile`, or when WEEX_API_KEY
+/// is unset and the config file names a `default_profile`, they come from the
+/// config file instead (WEEX_CONFIG, else ~/.config/weex/config.toml).
+#[derive(Parser)]
+#[command(name = "weex-cli", version)]
+struct Cli {
+    /// Output format, given before the command (`weex-cli --output table positions`)
+    #[arg(long, short, value_enum, default_value_t = OutputFormat::Json)]
+    output: OutputFormat,
+
+    /// Config profile for endpoint, credentials, rate limit and risk defaults
+    #[arg(long, short)]
+    profile: Option<String>,
+
+    /// Config file to read profiles from
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    // ==================== MARKET ====================
+    /// Exchange server time
+    ServerTime,
+    /// Contract specifications, for one symbol or all
+    Contracts {
+        #[arg(long)]
+        symbol: Option<Symbol>,
+    },
+    /// Latest ticker
+    Ticker {
+        #[arg(long, default_value = "cmt_btcusdt")]
+        symbol: Symbol,
+    },
+    /// Tickers for all contracts
+    Tickers,
+    /// Order book depth
+    Depth {
+        #[arg(long, default_value = "cmt_btcusdt")]
+        symbol: Symbol,
+    },
+    /// Candlesticks
+    Candles {
+        #[arg(long, default_value = "cmt_btcusdt")]
+        symbol: Symbol,
+        /// Candle interval, e.g. 1m, 15m, 1H, 1D
+        #[arg(long, default_value = "1H")]
+        granularity: String,
+        #[arg(long, default_value_t = 50)]
+        limit: u32,
+    },
+    /// Recent public trades
+    Trades {
+        #[arg(long, default_value = "cmt_btcusdt")]
+        symbol: Symbol,
+        #[arg(long)]
+        limit: Option<u32>,
+    },
+    /// Index price
+    Index {
+        #[arg(long, default_value = "cmt_btcusdt")]
+        symbol: Symbol,
+    },
+    /// Open interest
+    OpenInterest {
+        #[arg(long, default_value = "cmt_btcusdt")]
+        symbol: Symbol,
+    },
+    /// Current funding rate
+    Funding {
+        #[arg(long, default_value = "cmt_btcusdt")]
+        symbol: Symbol,
+    },
+    /// Next funding settlement time
+    FundingTime {
+        #[arg(long, default_value = "cmt_btcusdt")]
+        symbol: Symbol,
+    },
+    /// Historical funding rates
+    FundingHistory {
+        #[arg(long, default_value = "cmt_btcusdt")]
+        symbol: Symbol,
+        #[arg(long)]
+        limit: Option<u32>,
+    },
+
+    // ==================== ACCOUNT ====================
+    /// Account balance
+    Balance,
+    /// Account assets
+    Assets,
+    /// All open positions
+    Positions,
+    /// Position for one symbol
+    Position {
+        #[arg(long)]
+        symbol: Symbol,
+    },
+    /// Leverage and margin settings for a symbol
+    Settings {
+        #[arg(long)]
+        symbol: Symbol,
+    },
+    /// Account bills (fees, funding, realized PnL)
+    Bills {
+        #[arg(long)]
+        symbol: Symbol,
+    },
+    /// Set leverage
+    Leverage {
+        #[arg(long)]
+        symbol: Symbol,
+        #[arg(long, value_parser = clap::value_parser!(i32).range(1..=125))]
+        leverage: i32,
+        #[arg(long, value_enum, default_value_t = SideArg::Buy)]
+        side: SideArg,
+    },
+    /// Set margin mode
+    MarginMode {
+        #[arg(long)]
+        symbol: Symbol,
+        #[arg(long, value_enum)]
+        mode: MarginModeArg,
+    },
+
+    // ==================== TRADING ====================
+    /// Place an order (asks for confirmation unless --yes is given)
+    #[command(group(ArgGroup::new("tif").args(["post_only", "ioc", "fok"])))]
+    Order {
+        #[arg(long)]
+        symbol: Symbol,
+        #[arg(long, value_enum)]
+        side: SideArg,
+        #[arg(long)]
+        size: Decimal,
+        /// Order type; defaults to limit when --price is given, market otherwise
+        #[arg(long = "type", value_enum)]
+        order_type: Option<OrderTypeArg>,
+        /// Limit price
+        #[arg(long)]
+        price: Option<Decimal>,
+        #[arg(long)]
+        client_oid: Option<String>,
+        /// Only reduce an existing position
+        #[arg(long)]
+        reduce_only: bool,
+        /// Maker only: cancel instead of taking liquidity
+        #[arg(long)]
+        post_only: bool,
+        /// Immediate or cancel
+        #[arg(long)]
+        ioc: bool,
+        /// Fill or kill
+        #[arg(long)]
+        fok: bool,
+        /// Preset take-profit price
+        #[arg(long)]
+        tp: Option<Decimal>,
+        /// Preset stop-loss price
+        #[arg(long)]
+        sl: Option<Decimal>,
+        /// Skip the confirmation prompt
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Cancel an order
+    Cancel {
+        #[arg(long)]
+        symbol: Symbol,
+        #[arg(long)]
+        order_id: String,
+    },
+    /// Cancel all open orders on a symbol
+    CancelAll {
+        #[arg(long)]
+        symbol: Symbol,
+    },
+    /// Close all positions on a symbol at market
+    CloseAll {
+        #[arg(long)]
+        symbol: Symbol,
+    },
+    /// Open orders
+    OpenOrders {
+        #[arg(long, default_value = "cmt_btcusdt")]
+        symbol: Symbol,
+    },
+    /// Order details
+    OrderDetail {
+        #[arg(long)]
+        symbol: Symbol,
+        #[arg(long)]
+        order_id: String,
+    },
+    /// Order history
+    OrderHistory {
+        #[arg(long, default_value = "cmt_btcusdt")]
+        symbol: Symbol,
+        #[arg(long)]
+        page_size: Option<u32>,
+    },
+    /// Executed fills
+    Fills {
+        #[arg(long, default_value = "cmt_btcusdt")]
+        symbol: Symbol,
+        #[arg(long)]
+        order_id: Option<String>,
+    },
+    /// Place (or with --modify, change) take-profit / stop-loss on a position
+    #[command(group(ArgGroup::new("prices").args(["tp", "sl"]).required(true).multiple(true)))]
+    Tpsl {
+        #[arg(long)]
+        symbol: Symbol,
+        #[arg(long, value_enum)]
+        side: HoldSide,
+        #[arg(long)]
+        tp: Option<Decimal>,
+        #[arg(long)]
+        sl: Option<Decimal>,
+        #[arg(long)]
+        modify: bool,
+    },
+    /// Place a trigger order
+    Trigger {
+        #[arg(long)]
+        symbol: Symbol,
+        #[arg(long, value_enum)]
+        side: SideArg,
+        #[arg(long)]
+        size: Decimal,
+        #[arg(long)]
+        trigger_price: Decimal,
+        /// Limit price once triggered; market if omitted
+        #[arg(long)]
+        execute_price: Option<Decimal>,
+    },
+    /// Cancel a trigger order
+    CancelTrigger {
+        #[arg(long)]
+        symbol: Symbol,
+        #[arg(long)]
+        order_id: String,
+    },
+    /// Current (or with --history, past) trigger orders
+    Plans {
+        #[arg(long, default_value = "cmt_btcusdt")]
+        symbol: Symbol,
+        #[arg(long)]
+        history: bool,
+    },
+
+    // ==================== AI LOG ====================
+    /// Upload an AI log entry
+    AiLog {
+        /// "Strategy Generation", "Decision Making", "Risk Assessment" or "Execution"
+        #[arg(long, value_parser = parse_stage)]
+        stage: AILogStage,
+        #[arg(long)]
+        model: String,
+        #[arg(long, value_parser = parse_json, default_value = "{}")]
+        input: Value,
+        #[arg(long, value_parser = parse_json, default_value = "{}")]
+        output: Value,
+        #[arg(long, default_value = "AI decision")]
+        explanation: String,
+        #[arg(long)]
+        order_id: Option<i64>,
+    },
+
+    // ==================== STREAMING ====================
+    /// Stream WebSocket updates until Ctrl-C (JSON lines, a refreshing table with --output table, or CSV)
+    Watch {
+        #[command(subcommand)]
+        stream: WatchCommand,
+    },
+    /// Serve newline-delimited JSON-RPC 2.0 over stdin/stdout, or a Unix socket with --socket
+    Serve {
+        /// Listen on this Unix socket instead of stdin/stdout
+        #[arg(long)]
+        socket: Option<std::path::PathBuf>,
+    },
+
+    // ==================== EXPORT ====================
+    /// Export records as CSV (to stdout unless --out is given)
+    Export {
+        #[arg(long, value_enum, default_value_t = ExportKind::Trades)]
+        kind: ExportKind,
+        /// Only records for this symbol
+        #[arg(long)]
+        symbol: Option<Symbol>,
+        /// Start date, YYYY-MM-DD or RFC 3339 (inclusive)
+        #[arg(long, value_parser = parse_from)]
+        from: Option<DateTime<Utc>>,
+        /// End date, YYYY-MM-DD (whole day) or RFC 3339 (inclusive)
+        #[arg(long, value_parser = parse_to)]
+        to: Option<DateTime<Utc>>,
+        #[arg(long)]
+        out: Option<String>,
+        /// State directory for trades and equity
+        #[arg(long, default_value = "./data")]
+        data_dir: String,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SideArg {
+    Buy,
+    Sell,
+}
+
+impl From<SideArg> for Side {
+    fn from(side: SideArg) -> Self {
+        match side {
+            SideArg::Buy => Side::Buy,
+            SideArg::Sell => Side::Sell,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OrderTypeArg {
+    Limit,
+    Market,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum HoldSide {
+    Long,
+    Short,
+}
+
+impl From<HoldSide> for PositionSide {
+    fn from(side: HoldSide) -> Self {
+        match side {
+            HoldSide::Long => PositionSide::Long,
+            HoldSide::Short => PositionSide::Short,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum MarginModeArg {
+    Crossed,
+    Isolated,
+}
+
+impl From<MarginModeArg> for MarginMode {
+    fn from(mode: MarginModeArg) -> Self {
+        match mode {
+            MarginModeArg::Crossed => MarginMode::Crossed,
+            MarginModeArg::Isolated => MarginMode::Isolated,
+        }
+    }
+}
+
+/// Largest page the order history endpoint returns
+const ORDER_HISTORY_PAGE_SIZE: u32 = 100;
+
+/// Bills returned per page by the account bills endpoint
+const BILLS_PAGE_SIZE: usize = 20;
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportKind {
+    Trades,
+    Fills,
+    Orders,
+    Funding,
+    Equity,
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        // --help and --version
+        Err(e) if !e.use_stderr() => e.exit(),
+        Err(e) => {
+            println!("{}", json!({"success": false, "error": e.render().to_string().trim()}));
+            std::process::exit(2);
+        }
+    };
+
+    let format = cli.output;
+    let columns: &[&str] = if matches!(cli.command, Command::Candles { .. }) { CANDLE_COLUMNS } else { &[] };
+    let client = build_client(&cli.profile, &cli.config, format);
+
+    let result = match cli.command {
+        Command::Watch { stream } => match watch::run(stream, client, format).await {
+            Ok(()) => return,
+            Err(e) => fail(format, &e, 1),
+        },
+
+        Command::Serve { socket } => match serve::run(client, socket).await {
+            Ok(()) => return,
+            Err(e) => fail(format, &e, 1),
+        },
+
+        Command::Export { kind, symbol, from, to, out, data_dir } => {
+            let filter = ExportFilter { symbol: symbol.clone(), from, to };
+            let symbol = symbol.unwrap_or_else(|| "cmt_btcusdt".parse().expect("valid default symbol"));
+
+            // Render in memory first so a failed fetch never leaves an empty or partial file behind
+            let mut csv = Vec::new();
+            let written = match kind {
+                ExportKind::Trades => {
+                    StateManager::new(&data_dir).export_trades_csv(&filter, &mut csv).map_err(|e| format!("{:?}", e))
+                }
+                ExportKind::Equity => StateManager::new(&data_dir)
+                    .load_equity_curve()
+                    .and_then(|c| {
+                        let c = c.between(filter.from, filter.to);
+                        c.write_csv(&mut csv).map(|_| c.snapshots().len())
+                    })
+                    .map_err(|e| format!("{:?}", e)),
+                ExportKind::Fills => match client.list_fills_between(&symbol, from, to).await {
+                    Ok(rows) => write_csv(&rows, &filter, &mut csv).map_err(|e| format!("{:?}", e)),
+                    Err(e) => Err(error_text(&e)),
+                },
+                ExportKind::Orders => match client.list_order_history(&symbol, Some(ORDER_HISTORY_PAGE_SIZE)).await {
+                    Ok(rows) => check_page_covers(&rows, rows.len() >= ORDER_HISTORY_PAGE_SIZE as usize, from)
+                        .map_err(|e| error_text(&e))
+                        .and_then(|_| write_csv(&rows, &filter, &mut csv).map_err(|e| format!("{:?}", e))),
+                    Err(e) => Err(error_text(&e)),
+                },
+                ExportKind::Funding => match client
+                    .get_bills(&symbol)
+                    .await
+                    .and_then(|raw| FundingPayment::parse_bills_page(&raw))
+                {
+                    Ok((rows, bills)) => check_page_covers(&rows, bills >= BILLS_PAGE_SIZE, from)
+                        .map_err(|e| error_text(&e))
+                        .and_then(|_| write_csv::<FundingPayment, _>(&rows, &filter, &mut csv).map_err(|e| format!("{:?}", e))),
+                    Err(e) => Err(error_text(&e)),
+                },
+            };
+
+            let written = written.and_then(|rows| match &out {
+                Some(path) => std::fs::write(path, &csv).map(|_| rows).map_err(|e| format!("Cannot write {}: {}", path, e)),
+                None => std::io::Write::write_all(&mut std::io::stdout(), &csv).map(|_| rows).map_err(|e| e.to_string()),
+            });
+            match (written, out) {
+                // CSV already went to stdout
+                (Ok(_), None) => return,
+                (Ok(rows), Some(path)) => json!({"success": true, "data": {"rows": rows, "path": path}}),
+                (Err(e), _) => json!({"success": false, "error": e}),
+            }
+        }
+
+        command => match execute(command, &client, true).await {
+            Ok(result) => result,
+            Err(f) => fail(format, &f.error, f.code),
+        },
+    };
+
+    output::print(format, &result, columns);
+}
+
+/// Command that stopped before reaching the API
+struct Failure {
+    error: String,
+    /// Process exit status: 2 for invalid input, 1 otherwise
+    code: i32,
+}
+
+impl Failure {
+    fn invalid(error: String) -> Self {
+        Failure { error, code: 2 }
+    }
+}
+
+/// Run a request/response command and return its result envelope
+///
+/// `interactive` allows the order confirmation prompt; without it orders need `--yes`.
+/// Streaming (`watch`, `serve`) and `export` commands are handled by the caller.
+async fn execute(command: Command, client: &WeexClient, interactive: bool) -> Result<Value, Failure> {
+    Ok(match command {
+        Command::ServerTime => respond(client.get_server_time().await),
+        Command::Contracts { symbol } => respond(client.get_contracts(symbol.as_deref()).await),
+        Command::Ticker { symbol } => respond(client.get_ticker_raw(&symbol).await),
+        Command::Tickers => respond(client.get_all_tickers().await),
+        Command::Depth { symbol } => respond(client.get_depth(&symbol, None).await),
+        Command::Candles { symbol, granularity, limit } => {
+            respond(client.get_klines(&symbol, &granularity, limit).await)
+        }
+        Command::Trades { symbol, limit } => respond(client.get_trades(&symbol, limit).await),
+        Command::Index { symbol } => respond(client.get_index(&symbol).await),
+        Command::OpenInterest { symbol } => respond(client.get_open_interest(&symbol).await),
+        Command::Funding { symbol } => respond(client.get_funding_rate(&symbol).await),
+        Command::FundingTime { symbol } => respond(client.get_funding_time(&symbol).await),
+        Command::FundingHistory { symbol, limit } => {
+            respond(client.get_history_funding_rate(&symbol, limit).await)
+        }
+
+        Command::Balance => respond(client.get_balance().await),
+        Command::Assets => respond(client.get_assets().await),
+        Command::Positions => respond(client.get_all_positions().await),
+        Command::Position { symbol } => respond(client.get_position(&symbol).await),
+        Command::Settings { symbol } => respond(client.get_settings(&symbol).await),
+        Command::Bills { symbol } => respond(client.get_bills(&symbol).await),
+        Command::Leverage { symbol, leverage, side } => {
+            respond(client.set_leverage(&symbol, leverage, side.into()).await)
+        }
+        Command::MarginMode { symbol, mode } => {
+            respond(client.set_margin_mode(&symbol, &MarginMode::from(mode).to_string()).await)
+        }
+
+        Command::Order {
+            symbol, side, size, order_type, price, client_oid, reduce_only, post_only, ioc, fok, tp, sl, yes,
+        } => {
+            let order_type = match (order_type, price) {
+                (Some(OrderTypeArg::Limit), _) | (None, Some(_)) => OrderType::Limit,
+                (Some(OrderTypeArg::Market), _) | (None, None) => OrderType::Market,
+            };
+            let mut req = FuturesOrderRequest {
+                order_type,
+                price,
+                ..FuturesOrderRequest::market(&symbol, side.into(), size)
+            };
+            req = req.reduce_only(reduce_only);
+            if let Some(oid) = &client_oid {
+                req = req.client_oid(oid);
+            }
+            if post_only {
+                req = req.time_in_force(TimeInForce::PostOnly);
+            } else if ioc {
+                req = req.time_in_force(TimeInForce::Ioc);
+            } else if fok {
+                req = req.time_in_force(TimeInForce::Fok);
+            }
+            if let Some(tp) = tp {
+                req = req.take_profit(tp);
+            }
+            if let Some(sl) = sl {
+                req = req.stop_loss(sl);
+            }
+            if let Err(e) = req.validate() {
+                return Err(Failure::invalid(e.to_string()));
+            }
+            if !yes {
+                if !interactive {
+                    return Err(Failure::invalid("Orders without a terminal need `yes`".to_string()));
+                }
+                confirm(&describe_order(&req)).map_err(|e| Failure { error: e, code: 1 })?;
+            }
+
+            // A profile's risk gate prices market orders off the mark, which a one-shot CLI has not seen yet
+            if let Some(gate) = &client.risk_gate {
+                if gate.mark_price(&req.symbol).is_none() {
+                    if let Ok(ticker) = client.get_ticker(&req.symbol).await {
+                        gate.update_mark_price(&req.symbol, ticker.last);
+                    }
+                }
+            }
+            match client.place_futures_order_request(&req).await {
+                Ok(order_id) => json!({
+                    "success": true,
+                    "data": {
+                        "order_id": order_id
+                    }
+                }),
+                Err(e) => json!({"success": false, "error": error_text(&e)})
+            }
+        }
+        Command::Cancel { symbol, order_id } => respond(client.cancel_futures_order(&symbol, &order_id).await),
+        Command::CancelAll { symbol } => respond(client.cancel_all_orders(&symbol).await),
+        Command::CloseAll { symbol } => respond(client.close_all_positions(&symbol).await),
+        Command::OpenOrders { symbol } => respond(client.get_current_orders(&symbol).await),
+        Command::OrderDetail { symbol, order_id } => respond(client.get_order_detail(&symbol, &order_id).await),
+        Command::OrderHistory { symbol, page_size } => respond(client.get_order_history(&symbol, page_size).await),
+        Command::Fills { symbol, order_id } => respond(client.get_fills(&symbol, order_id.as_deref()).await),
+        Command::Tpsl { symbol, side, tp, sl, modify } => {
+            let side = PositionSide::from(side).to_string();
+            let tp = tp.map(|p| p.to_string());
+            let sl = sl.map(|p| p.to_string());
+            if modify {
+                respond(client.modify_tpsl(&symbol, &side, tp.as_deref(), sl.as_deref()).await)
+            } else {
+                respond(client.place_tpsl(&symbol, &side, tp.as_deref(), sl.as_deref()).await)
+            }
+        }
+        Command::Trigger { symbol, side, size, trigger_price, execute_price } => {
+            let execute_price = execute_price.map(|p| p.to_string());
+            respond(
+                client
+                    .place_trigger_order(
+                        &symbol,
+                        &size.to_string(),
+                        &Side::from(side).to_string(),
+                        &trigger_price.to_string(),
+                        execute_price.as_deref(),
+                    )
+                    .await,
+            )
+        }
+        Command::CancelTrigger { symbol, order_id } => respond(client.cancel_trigger_order(&symbol, &order_id).await),
+        Command::Plans { symbol, history } => {
+            if history {
+                respond(client.get_history_plan(&symbol).await)
+            } else {
+                respond(client.get_current_plan(&symbol).await)
+            }
+        }
+
+        Command::AiLog { stage, model, input, output, explanation, order_id } => {
+            let mut builder = AiLogEntry::builder(stage)
+                .model(&model)
+                .input(input)
+                .output(output)
+                .explanation(&explanation);
+            if let Some(oid) = order_id {
+                builder = builder.order_id(oid);
+            }
+            match builder.build() {
+                Ok(entry) => respond(client.upload_ai_log_entry(&entry).await),
+                Err(e) => json!({"success": false, "error": e.to_string()}),
+            }
+        }
+        Command::Watch { .. } | Command::Serve { .. } | Command::Export { .. } => {
+            return Err(Failure::invalid("Command cannot be executed here".to_string()));
+        }
+    })
+}
+
+/// Client from a config profile when one is named (or defaulted), else from the environment
+fn build_client(profile: &Option<String>, config: &Option<PathBuf>, format: OutputFormat) -> WeexClient {
+    let from_config = profile.is_some() || config.is_some() || env::var("WEEX_API_KEY").is_err();
+    let loaded = match config {
+        Some(path) => Config::load(path).map(Some),
+        None if from_config => match Config::default_path() {
+            Some(path) if path.exists() => Config::load(path).map(Some),
+            _ => Ok(None),
+        },
+        None => Ok(None),
+    };
+    let loaded = match loaded {
+        Ok(c) => c,
+        Err(e) => fail(format, &e.to_string(), 1),
+    };
+
+    match loaded {
+        Some(c) if profile.is_some() || c.default_profile.is_some() => {
+            match c.builder(profile.as_deref()).and_then(|b| b.build()) {
+                Ok(client) => client,
+                Err(e) => fail(format, &format!("Failed to build client: {}", e), 1),
+            }
+        }
+        None if profile.is_some() => fail(format, "--profile given but no config file found", 1),
+        _ => client_from_env(format),
+    }
+}
+
+fn client_from_env(format: OutputFormat) -> WeexClient {
+    let base_url = env::var("WEEX_BASE_URL").unwrap_or_else(|_| "https://api-contract.weex.com".to_string());
+    let mut builder = WeexClient::builder().base_url(&base_url);
+
+    // Without any keys the client still serves market data and public streams
+    let keyless = ["WEEX_API_KEY", "WEEX_SECRET_KEY", "WEEX_PASSPHRASE"].iter().all(|v| env::var_os(v).is_none());
+    if !keyless {
+        match Credentials::from_env() {
+            Ok(c) => builder = builder.credentials(c),
+            Err(e) => fail(format, &e.to_string(), 1),
+        }
+    }
+
+    match builder.build() {
+        Ok(c) => c,
+        Err(e) => fail(format, &format!("Failed to build client: {}", e), 1),
+    }
+}
+
+/// One-line summary of an order for the confirmation prompt
+fn describe_order(req: &FuturesOrderRequest) -> String {
+    let mut text = format!(
+        "{} {} {} {}",
+        req.order_type.to_string().to_uppercase(),
+        req.side.to_string().to_uppercase(),
+        req.size,
+        req.symbol
+    );
+    if let Some(p) = req.price {
+        text.push_str(&format!(" @ {}", p));
+    }
+    let mut options = Vec::new();
+    if let Some(tif) = req.time_in_force {
+        options.push(tif.to_string());
+    }
+    if req.reduce_only {
+        options.push("reduce-only".to_string());
+    }
+    if let Some(tp) = req.take_profit {
+        options.push(format!("TP {}", tp));
+    }
+    if let Some(sl) = req.stop_loss {
+        options.push(format!("SL {}", sl));
+    }
+    if let Some(oid) = &req.client_oid {
+        options.push(format!("client oid {}", oid));
+    }
+    if !options.is_empty() {
+        text.push_str(&format!(" ({})", options.join(", ")));
+    }
+    text
+}
+
+/// Ask on stderr before sending a live order; stdout stays reserved for the JSON envelope
+fn confirm(summary: &str) -> Result<(), String> {
+    use std::io::{BufRead, IsTerminal, Write};
+
+    if !std::io::stdin().is_terminal() {
+        return Err("Refusing to place a live order without confirmation; pass --yes".to_string());
+    }
+    eprint!("Place live order: {}? [y/N] ", summary);
+    let _ = std::io::stderr().flush();
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer).map_err(|e| e.to_string())?;
+    match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => Ok(()),
+        _ => Err("Order not confirmed".to_string()),
+    }
+}
+
+/// Wrap a raw API response in the success envelope, embedding it as JSON when it parses
+fn respond(result: Result<String, WeexError>) -> Value {
+    match result {
+        Ok(raw) => match serde_json::from_str::<Value>(&raw) {
+            Ok(v) => json!({"success": true, "data": v}),
+            Err(_) => json!({"success": true, "data": raw}),
+        },
+        Err(e) => json!({"success": false, "error": error_text(&e)}),
+    }
+}
+
+fn error_text(e: &WeexError) -> String {
+    match e {
+        WeexError::MissingCredentials(_) => {
+            format!("{} (set WEEX_API_KEY, WEEX_SECRET_KEY and WEEX_PASSPHRASE, or use --profile)", e)
+        }
+        other => format!("{:?}", other),
+    }
+}
+
+/// Accept the API spelling ("Decision Making") or a kebab-case form ("decision-making")
+fn parse_stage(raw: &str) -> Result<AILogStage, String> {
+    let normalized = raw.replace(['-', '_'], " ").to_lowercase();
+    [
+        AILogStage::StrategyGeneration,
+        AILogStage::DecisionMaking,
+        AILogStage::RiskAssessment,
+        AILogStage::Execution,
+    ]
+    .into_iter()
+    .find(|stage| stage.to_string().to_lowercase() == normalized)
+    .ok_or_else(|| format!("unknown stage '{}'", raw))
+}
+
+fn parse_json(raw: &str) -> Result<Value, String> {
+    serde_json::from_str(raw).map_err(|e| format!("invalid JSON: {}", e))
+}
+
+fn parse_from(raw: &str) -> Result<DateTime<Utc>, String> {
+    parse_date(raw, false)
+}
+
+fn parse_to(raw: &str) -> Result<DateTime<Utc>, String> {
+    parse_date(raw, true)
+}
+
+/// Parse an RFC 3339 timestamp or a `YYYY-MM-DD` date (start of day, or end of day for `--to`)
+fn parse_date(raw: &str, end_of_day: bool) -> Result<DateTime<Utc>, String> {
+    if let Ok(ts) = DateTime::parse_from_rfc3339(raw) {
+        return Ok(ts.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+        .map_err(|_| format!("invalid date '{}' (use YYYY-MM-DD or RFC 3339)", raw))?;
+    let time = if end_of_day {
+        date.and_hms_milli_opt(23, 59, 59, 999)
+    } else {
+        date.and_hms_opt(0, 0, 0)
+    };
+    Ok(time.expect("valid time of day").and_utc())
+}
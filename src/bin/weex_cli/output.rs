@@ -0,0 +1,216 @@
+//! Rendering of command results as JSON envelopes, aligned tables or CSV
+
+use clap::ValueEnum;
+use serde_json::{json, Map, Value};
+
+/// Column names for candle rows, which the API returns as bare arrays
+pub const CANDLE_COLUMNS: &[&str] = &["timestamp", "open", "high", "low", "close", "volume", "quote_volume"];
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// One-line `{"success": ..., "data": ...}` envelope
+    #[default]
+    Json,
+    /// Aligned columns for a terminal
+    Table,
+    /// CSV with a header row
+    Csv,
+}
+
+/// Print a result envelope in `format`
+///
+/// Table and CSV print only the payload; a failed envelope goes to stderr and
+/// exits with status 1. `array_columns` names the fields of rows that arrive as
+/// bare arrays.
+pub fn print(format: OutputFormat, envelope: &Value, array_columns: &[&str]) {
+    if format == OutputFormat::Json {
+        println!("{}", serde_json::to_string(envelope).unwrap());
+        return;
+    }
+    if envelope["success"] != Value::Bool(true) {
+        fail(format, &cell(&envelope["error"]), 1);
+    }
+
+    let table = Tabular::from_value(&envelope["data"], array_columns);
+    match format {
+        OutputFormat::Table => print!("{}", table.to_table()),
+        _ => print!("{}", table.to_csv()),
+    }
+}
+
+/// Report an error in `format` and exit with `code`
+pub fn fail(format: OutputFormat, error: &str, code: i32) -> ! {
+    match format {
+        OutputFormat::Json => println!("{}", json!({"success": false, "error": error})),
+        _ => eprintln!("error: {}", error),
+    }
+    std::process::exit(code)
+}
+
+/// API payload flattened into a header row and string cells
+#[derive(Debug, PartialEq)]
+pub struct Tabular {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Tabular {
+    /// Flatten a payload: a list becomes one row per item, an object one row
+    ///
+    /// `{"data": ...}` and `{"list": [...]}` wrappers are unwrapped. Object rows
+    /// take their columns from the union of keys; nested values are kept as JSON.
+    pub fn from_value(data: &Value, array_columns: &[&str]) -> Tabular {
+        let items = rows_of(data);
+
+        let mut headers: Vec<String> = Vec::new();
+        for item in &items {
+            match item {
+                Value::Object(map) => {
+                    for key in map.keys() {
+                        if !headers.contains(key) {
+                            headers.push(key.clone());
+                        }
+                    }
+                }
+                Value::Array(fields) => {
+                    for i in headers.len()..fields.len() {
+                        headers.push(array_columns.get(i).map(|c| c.to_string()).unwrap_or_else(|| i.to_string()));
+                    }
+                }
+                _ => {
+                    if headers.is_empty() {
+                        headers.push("value".to_string());
+                    }
+                }
+            }
+        }
+        Self::with_headers(data, headers)
+    }
+
+    /// Flatten a payload onto fixed columns, e.g. later messages of a stream
+    pub fn with_headers(data: &Value, headers: Vec<String>) -> Tabular {
+        let rows = rows_of(data)
+            .iter()
+            .map(|item| match item {
+                Value::Object(map) => headers.iter().map(|h| map.get(h).map(cell).unwrap_or_default()).collect(),
+                Value::Array(fields) => (0..headers.len()).map(|i| fields.get(i).map(cell).unwrap_or_default()).collect(),
+                other => vec![cell(other)],
+            })
+            .collect();
+        Tabular { headers, rows }
+    }
+
+    /// Left-aligned text, right-aligned numbers, with a dashed rule under the header
+    pub fn to_table(&self) -> String {
+        let mut widths: Vec<usize> = self.headers.iter().map(|h| h.chars().count()).collect();
+        for row in &self.rows {
+            for (w, c) in widths.iter_mut().zip(row) {
+                *w = (*w).max(c.chars().count());
+            }
+        }
+
+        let line = |cells: &[String], numeric: bool| -> String {
+            let padded: Vec<String> = cells
+                .iter()
+                .zip(&widths)
+                .map(|(c, w)| {
+                    if numeric && c.parse::<f64>().is_ok() {
+                        format!("{:>w$}", c, w = w)
+                    } else {
+                        format!("{:<w$}", c, w = w)
+                    }
+                })
+                .collect();
+            format!("{}\n", padded.join("  ").trim_end())
+        };
+
+        let mut out = line(&self.headers, false);
+        let rule: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+        out.push_str(&line(&rule, false));
+        for row in &self.rows {
+            out.push_str(&line(row, true));
+        }
+        out
+    }
+
+    pub fn to_csv(&self) -> String {
+        self.write_csv(true)
+    }
+
+    /// CSV rows without the header, for appending to a stream
+    pub fn csv_rows(&self) -> String {
+        self.write_csv(false)
+    }
+
+    fn write_csv(&self, header: bool) -> String {
+        let mut csv = csv::Writer::from_writer(Vec::new());
+        if header {
+            let _ = csv.write_record(&self.headers);
+        }
+        for row in &self.rows {
+            let _ = csv.write_record(row);
+        }
+        String::from_utf8(csv.into_inner().unwrap_or_default()).unwrap_or_default()
+    }
+}
+
+/// Items to render as rows, after unwrapping response envelopes
+pub fn rows_of(data: &Value) -> Vec<&Value> {
+    match data {
+        Value::Null => Vec::new(),
+        Value::Array(items) => items.iter().collect(),
+        Value::Object(map) => match list_field(map) {
+            Some(inner) => rows_of(inner),
+            None => vec![data],
+        },
+        scalar => vec![scalar],
+    }
+}
+
+fn list_field(map: &Map<String, Value>) -> Option<&Value> {
+    if let Some(inner) = map.get("data").filter(|d| !d.is_null()) {
+        return Some(inner);
+    }
+    ["list", "orderList", "resultList"]
+        .iter()
+        .find_map(|k| map.get(*k).filter(|v| v.is_array()))
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Number(_) | Value::Bool(_) => value.to_string(),
+        nested => nested.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_objects_and_candles() {
+        let positions = json!({"code": "00000", "data": [
+            {"symbol": "cmt_btcusdt", "size": "0.5", "side": "long"},
+            {"symbol": "cmt_ethusdt", "size": "12", "leverage": 10},
+        ]});
+        let table = Tabular::from_value(&positions, &[]);
+        assert_eq!(table.headers, ["side", "size", "symbol", "leverage"]);
+        assert_eq!(table.rows[1], ["", "12", "cmt_ethusdt", "10"]);
+        assert_eq!(
+            table.to_table(),
+            [
+                "side  size  symbol       leverage\n",
+                "----  ----  -----------  --------\n",
+                "long   0.5  cmt_btcusdt\n",
+                "        12  cmt_ethusdt        10\n",
+            ]
+            .concat()
+        );
+
+        let candles = json!([["1700000000000", "100", "110", "90", "105", "3"]]);
+        let table = Tabular::from_value(&candles, CANDLE_COLUMNS);
+        assert_eq!(table.to_csv(), "timestamp,open,high,low,close,volume\n1700000000000,100,110,90,105,3\n");
+    }
+}
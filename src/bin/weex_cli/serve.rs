@@ -0,0 +1,391 @@
+//! `serve`: newline-delimited JSON-RPC 2.0 over stdin/stdout or a Unix socket
+//!
+//! Methods are the CLI commands, with params named after their flags:
+//! `{"jsonrpc": "2.0", "id": 1, "method": "ticker", "params": {"symbol": "BTCUSDT"}}`.
+//! `subscribe` (`{"channel": "ticker", "symbol": ...}`) returns a subscription id and
+//! pushes `subscription` notifications until `unsubscribe` (`{"subscription": id}`).
+//! A client that falls behind has the subscription closed with a `subscription_closed`
+//! notification rather than buffering updates without bound.
+//! Orders are never confirmed interactively, so `order` needs `"yes": true`.
+
+use crate::watch::{self, WatchCommand};
+use crate::{execute, Cli};
+use clap::error::ErrorKind;
+use clap::Parser;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use weex_rust_sdk::ws::client::WsMessage;
+use weex_rust_sdk::WeexClient;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// Command reached the API (or the order prompt) and failed
+const COMMAND_FAILED: i64 = -32000;
+
+/// Replies and notifications waiting to be written to one connection
+const OUTBOX_CAPACITY: usize = 1024;
+
+/// Subscriptions that ended on their own, waiting to leave the session's map
+const CLOSED_CAPACITY: usize = 16;
+
+/// Subscription ids, unique across all connections
+static NEXT_SUBSCRIPTION: AtomicU64 = AtomicU64::new(1);
+
+/// Parses `subscribe` params with the same definitions as `weex-cli watch`
+#[derive(Parser)]
+#[command(name = "subscribe")]
+struct Subscribe {
+    #[command(subcommand)]
+    stream: WatchCommand,
+}
+
+/// Tasks behind one `subscribe`
+struct Subscription {
+    stream: JoinHandle<Result<(), String>>,
+    forward: JoinHandle<()>,
+}
+
+impl Subscription {
+    fn cancel(&self) {
+        self.stream.abort();
+        self.forward.abort();
+    }
+}
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        RpcError { code, message: message.into() }
+    }
+}
+
+/// Serve until stdin closes, or forever on a socket
+pub async fn run(client: WeexClient, socket: Option<PathBuf>) -> Result<(), String> {
+    match socket {
+        None => {
+            session(client, tokio::io::stdin(), tokio::io::stdout()).await;
+            Ok(())
+        }
+        Some(path) => listen(client, path).await,
+    }
+}
+
+#[cfg(unix)]
+async fn listen(client: WeexClient, path: PathBuf) -> Result<(), String> {
+    use std::os::unix::fs::FileTypeExt;
+
+    // Replace a socket left by a previous run, but never any other kind of file
+    if let Ok(meta) = std::fs::symlink_metadata(&path) {
+        if !meta.file_type().is_socket() {
+            return Err(format!("{} exists and is not a socket", path.display()));
+        }
+        std::fs::remove_file(&path).map_err(|e| format!("Cannot remove {}: {}", path.display(), e))?;
+    }
+    let listener = tokio::net::UnixListener::bind(&path).map_err(|e| format!("Cannot bind {}: {}", path.display(), e))?;
+    tracing::info!("Serving JSON-RPC on {}", path.display());
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    let (read, write) = stream.into_split();
+                    tokio::spawn(session(client.clone(), read, write));
+                }
+                Err(e) => tracing::warn!("Accept failed: {}", e),
+            },
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+    let _ = std::fs::remove_file(&path);
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn listen(_client: WeexClient, _path: PathBuf) -> Result<(), String> {
+    Err("--socket needs a Unix platform; use stdin/stdout instead".to_string())
+}
+
+/// Handle one connection: requests run concurrently, replies and notifications are
+/// written one line at a time in completion order
+async fn session<R, W>(client: WeexClient, reader: R, mut writer: W)
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    let (out_tx, mut out_rx) = mpsc::channel::<Value>(OUTBOX_CAPACITY);
+    let writer_task = tokio::spawn(async move {
+        while let Some(msg) = out_rx.recv().await {
+            let line = format!("{}\n", msg);
+            if writer.write_all(line.as_bytes()).await.is_err() || writer.flush().await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let (closed_tx, mut closed_rx) = mpsc::channel::<u64>(CLOSED_CAPACITY);
+    let mut subscriptions: HashMap<u64, Subscription> = HashMap::new();
+    let mut lines = BufReader::new(reader).lines();
+    loop {
+        let line = tokio::select! {
+            line = lines.next_line() => match line {
+                Ok(Some(line)) => line,
+                _ => break,
+            },
+            Some(sub_id) = closed_rx.recv() => {
+                subscriptions.remove(&sub_id);
+                continue;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let request: Value = match serde_json::from_str(&line) {
+            Ok(v) => v,
+            Err(e) => {
+                let _ = out_tx.send(reply(Value::Null, Err(RpcError::new(PARSE_ERROR, e.to_string())))).await;
+                continue;
+            }
+        };
+        // Requests without an id are notifications and get no reply
+        let id = request.get("id").cloned();
+        let Some(method) = request.get("method").and_then(|m| m.as_str()).map(str::to_string) else {
+            let error = RpcError::new(INVALID_REQUEST, "missing method");
+            let _ = out_tx.send(reply(id.unwrap_or(Value::Null), Err(error))).await;
+            continue;
+        };
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+        match method.as_str() {
+            "subscribe" => {
+                let result = subscribe(&client, &params, &out_tx, &closed_tx).map(|(sub_id, subscription)| {
+                    subscriptions.insert(sub_id, subscription);
+                    json!(sub_id)
+                });
+                if let Some(id) = id {
+                    let _ = out_tx.send(reply(id, result)).await;
+                }
+            }
+            "unsubscribe" => {
+                let removed = params
+                    .get("subscription")
+                    .and_then(|s| s.as_u64())
+                    .and_then(|sub_id| subscriptions.remove(&sub_id));
+                if let Some(subscription) = &removed {
+                    subscription.cancel();
+                }
+                if let Some(id) = id {
+                    let _ = out_tx.send(reply(id, Ok(json!(removed.is_some())))).await;
+                }
+            }
+            _ => {
+                let client = client.clone();
+                let out_tx = out_tx.clone();
+                tokio::spawn(async move {
+                    let result = call(&client, &method, &params).await;
+                    if let Some(id) = id {
+                        let _ = out_tx.send(reply(id, result)).await;
+                    }
+                });
+            }
+        }
+    }
+
+    for subscription in subscriptions.values() {
+        subscription.cancel();
+    }
+    // Let in-flight requests finish replying before the writer stops
+    drop(out_tx);
+    let _ = writer_task.await;
+}
+
+/// Run one CLI command given as a method name and flag-named params
+async fn call(client: &WeexClient, method: &str, params: &Value) -> Result<Value, RpcError> {
+    if matches!(method, "watch" | "serve" | "export" | "help") {
+        return Err(RpcError::new(METHOD_NOT_FOUND, format!("{} is not available over JSON-RPC", method)));
+    }
+    let cli = Cli::try_parse_from(to_args("weex-cli", Some(method), params)?).map_err(clap_error)?;
+
+    let envelope = execute(cli.command, client, false).await.map_err(|f| {
+        let code = if f.code == 2 { INVALID_PARAMS } else { COMMAND_FAILED };
+        RpcError::new(code, f.error)
+    })?;
+    if envelope["success"] == Value::Bool(true) {
+        Ok(envelope["data"].clone())
+    } else {
+        let message = envelope["error"].as_str().map(str::to_string).unwrap_or_else(|| envelope["error"].to_string());
+        Err(RpcError::new(COMMAND_FAILED, message))
+    }
+}
+
+/// Start streaming `params.channel` to the caller as `subscription` notifications
+fn subscribe(
+    client: &WeexClient,
+    params: &Value,
+    out_tx: &mpsc::Sender<Value>,
+    closed_tx: &mpsc::Sender<u64>,
+) -> Result<(u64, Subscription), RpcError> {
+    let channel = params
+        .get("channel")
+        .and_then(|c| c.as_str())
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, "missing channel"))?;
+    let mut rest = params.clone();
+    if let Some(map) = rest.as_object_mut() {
+        map.remove("channel");
+    }
+    let stream = Subscribe::try_parse_from(to_args("subscribe", Some(channel), &rest)?)
+        .map_err(clap_error)?
+        .stream;
+
+    let sub_id = NEXT_SUBSCRIPTION.fetch_add(1, Ordering::Relaxed);
+    let (ws_tx, ws_rx) = mpsc::channel::<WsMessage>(1000);
+    let stream_task = watch::spawn(stream, client.clone(), ws_tx);
+
+    let stream_abort = stream_task.abort_handle();
+    let (out_tx, closed_tx, channel) = (out_tx.clone(), closed_tx.clone(), channel.to_string());
+    let forward_task = tokio::spawn(async move {
+        forward(sub_id, &channel, ws_rx, &out_tx).await;
+        stream_abort.abort();
+        // Only reached when the subscription ends on its own; `unsubscribe` aborts this task
+        let _ = closed_tx.send(sub_id).await;
+    });
+    Ok((sub_id, Subscription { stream: stream_task, forward: forward_task }))
+}
+
+/// Send stream updates as notifications until the stream ends or the client falls behind
+async fn forward(sub_id: u64, channel: &str, mut ws_rx: mpsc::Receiver<WsMessage>, out_tx: &mpsc::Sender<Value>) {
+    while let Some(msg) = ws_rx.recv().await {
+        let Some(data) = msg.data.filter(|_| msg.event.is_none()) else { continue };
+        let notification = json!({
+            "jsonrpc": "2.0",
+            "method": "subscription",
+            "params": {"subscription": sub_id, "channel": channel, "action": msg.action, "data": data},
+        });
+        match out_tx.try_send(notification) {
+            Ok(()) => {}
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                tracing::warn!("Subscription {} closed: client is not reading updates", sub_id);
+                let closed = json!({
+                    "jsonrpc": "2.0",
+                    "method": "subscription_closed",
+                    "params": {"subscription": sub_id, "channel": channel, "reason": "client too slow"},
+                });
+                let _ = out_tx.send(closed).await;
+                return;
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => return,
+        }
+    }
+}
+
+/// Turn `{"order_id": "1", "reduce_only": true}` into `--order-id 1 --reduce-only`
+fn to_args(bin: &str, command: Option<&str>, params: &Value) -> Result<Vec<String>, RpcError> {
+    let mut args: Vec<String> = std::iter::once(bin).chain(command).map(str::to_string).collect();
+    let map = match params {
+        Value::Null => return Ok(args),
+        Value::Object(map) => map,
+        _ => return Err(RpcError::new(INVALID_PARAMS, "params must be an object")),
+    };
+    for (key, value) in map {
+        let flag = format!("--{}", key.replace('_', "-"));
+        match value {
+            Value::Null | Value::Bool(false) => {}
+            Value::Bool(true) => args.push(flag),
+            Value::String(s) => args.extend([flag, s.clone()]),
+            // Numbers as written; objects and arrays (e.g. AI log input) as JSON text
+            other => args.extend([flag, other.to_string()]),
+        }
+    }
+    Ok(args)
+}
+
+fn clap_error(e: clap::Error) -> RpcError {
+    let code = match e.kind() {
+        ErrorKind::InvalidSubcommand | ErrorKind::MissingSubcommand => METHOD_NOT_FOUND,
+        _ => INVALID_PARAMS,
+    };
+    RpcError::new(code, e.render().to_string().trim())
+}
+
+fn reply(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+        Err(e) => json!({"jsonrpc": "2.0", "id": id, "error": {"code": e.code, "message": e.message}}),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_params_to_args() {
+        let params = json!({"symbol": "BTCUSDT", "size": 0.01, "reduce_only": true, "post_only": false, "input": {"a": 1}});
+        let args = to_args("weex-cli", Some("order"), &params).ok().unwrap();
+        assert_eq!(
+            args,
+            ["weex-cli", "order", "--input", r#"{"a":1}"#, "--reduce-only", "--size", "0.01", "--symbol", "BTCUSDT"]
+        );
+        assert!(to_args("weex-cli", None, &json!([1])).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_slow_client_closes_subscription() {
+        let (ws_tx, ws_rx) = mpsc::channel(8);
+        for n in 0..3 {
+            let msg = WsMessage { event: None, arg: None, data: Some(json!({"n": n})), action: None };
+            ws_tx.send(msg).await.unwrap();
+        }
+        let (out_tx, mut out_rx) = mpsc::channel(1);
+        let task = tokio::spawn(async move { forward(7, "ticker", ws_rx, &out_tx).await });
+
+        assert_eq!(out_rx.recv().await.unwrap()["method"], "subscription");
+        let closed = out_rx.recv().await.unwrap();
+        assert_eq!(closed["method"], "subscription_closed");
+        assert_eq!(closed["params"]["subscription"], 7);
+        // Stops reading the stream and sends nothing more
+        task.await.unwrap();
+        assert!(ws_tx.is_closed());
+        assert!(out_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_session_replies_per_request() {
+        let client = WeexClient::builder()
+            .base_url("http://127.0.0.1:9")
+            .api_key("k")
+            .secret_key("s")
+            .passphrase("p")
+            .build()
+            .unwrap();
+        let input = concat!(
+            "not json\n",
+            r#"{"jsonrpc":"2.0","id":1,"method":"nope"}"#, "\n",
+            r#"{"jsonrpc":"2.0","id":2,"method":"order","params":{"symbol":"BTCUSDT","side":"buy","size":"1"}}"#, "\n",
+            r#"{"jsonrpc":"2.0","id":3,"method":"unsubscribe","params":{"subscription":99}}"#, "\n",
+        );
+        let (mut ours, theirs) = tokio::io::duplex(64 * 1024);
+        session(client, input.as_bytes(), theirs).await;
+
+        let mut out = String::new();
+        tokio::io::AsyncReadExt::read_to_string(&mut ours, &mut out).await.unwrap();
+        let replies: Vec<Value> = out.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        let by_id = |id: i64| replies.iter().find(|r| r["id"] == id).unwrap().clone();
+
+        assert_eq!(replies.len(), 4);
+        assert!(replies.iter().any(|r| r["id"].is_null() && r["error"]["code"] == PARSE_ERROR));
+        assert_eq!(by_id(1)["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(by_id(2)["error"]["code"], INVALID_PARAMS);
+        assert_eq!(by_id(3)["result"], false);
+    }
+}
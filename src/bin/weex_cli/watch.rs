@@ -0,0 +1,222 @@
+//! `watch` subcommands: stream WebSocket updates until interrupted
+
+use crate::output::{rows_of, OutputFormat, Tabular};
+use chrono::Utc;
+use clap::Subcommand;
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use weex_rust_sdk::ws::client::{run_subscription_loop, WsMessage};
+use weex_rust_sdk::ws::private::UserStream;
+use weex_rust_sdk::{Symbol, WeexClient};
+
+/// Trades kept on screen by `watch trades --output table`
+const TRADE_ROWS: usize = 20;
+
+/// Column names for flattened order book levels
+const DEPTH_COLUMNS: &[&str] = &["side", "price", "size"];
+
+#[derive(Subcommand)]
+pub enum WatchCommand {
+    /// Ticker updates
+    Ticker {
+        #[arg(long, default_value = "cmt_btcusdt")]
+        symbol: Symbol,
+    },
+    /// Order book updates
+    Depth {
+        #[arg(long, default_value = "cmt_btcusdt")]
+        symbol: Symbol,
+    },
+    /// Public trades
+    Trades {
+        #[arg(long, default_value = "cmt_btcusdt")]
+        symbol: Symbol,
+    },
+    /// Your order updates (private stream)
+    Orders,
+    /// Your position updates (private stream)
+    Positions,
+}
+
+/// How a table view folds each update into what is on screen
+enum View {
+    /// Order book: latest update only, flattened into one row per level
+    Depth,
+    /// Keep the last N rows
+    Tail(usize),
+    /// Upsert rows by the first present key field
+    Keyed(&'static [&'static str]),
+}
+
+/// Stream updates for `cmd` until Ctrl-C or the stream gives up reconnecting
+pub async fn run(cmd: WatchCommand, client: WeexClient, format: OutputFormat) -> Result<(), String> {
+    let (tx, mut rx) = mpsc::channel::<WsMessage>(1000);
+
+    let (title, view) = match &cmd {
+        WatchCommand::Ticker { symbol } => (format!("ticker {}", symbol), View::Keyed(&["instId"])),
+        WatchCommand::Depth { symbol } => (format!("depth {}", symbol), View::Depth),
+        WatchCommand::Trades { symbol } => (format!("trades {}", symbol), View::Tail(TRADE_ROWS)),
+        WatchCommand::Orders => ("orders".to_string(), View::Keyed(&["ordId", "orderId"])),
+        WatchCommand::Positions => ("positions".to_string(), View::Keyed(&["posId", "instId"])),
+    };
+
+    let stream = spawn(cmd, client, tx);
+
+    let mut printer = Printer { format, title, view, rows: Vec::new(), csv_headers: None };
+    loop {
+        tokio::select! {
+            msg = rx.recv() => match msg {
+                Some(msg) => printer.show(&msg),
+                None => break,
+            },
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+    }
+    match stream.await {
+        Ok(result) => result,
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Open the WebSocket stream for `cmd`, forwarding messages to `tx` until aborted
+pub fn spawn(cmd: WatchCommand, client: WeexClient, tx: mpsc::Sender<WsMessage>) -> JoinHandle<Result<(), String>> {
+    tokio::spawn(async move {
+        let public = |channel: &'static str, symbol: Symbol, tx| async move {
+            run_subscription_loop(channel, &symbol, tx).await.map_err(|e| e.to_string())
+        };
+        match cmd {
+            WatchCommand::Ticker { symbol } => public("ticker", symbol, tx).await,
+            WatchCommand::Depth { symbol } => public("books", symbol, tx).await,
+            WatchCommand::Trades { symbol } => public("trade", symbol, tx).await,
+            WatchCommand::Orders => {
+                UserStream::new(client).channel("mc", "orders").start(tx).await.map_err(|e| e.to_string())
+            }
+            WatchCommand::Positions => {
+                UserStream::new(client).channel("mc", "positions").start(tx).await.map_err(|e| e.to_string())
+            }
+        }
+    })
+}
+
+struct Printer {
+    format: OutputFormat,
+    title: String,
+    view: View,
+    /// Rows currently on screen (table view)
+    rows: Vec<Value>,
+    /// Columns fixed by the first update (CSV stream)
+    csv_headers: Option<Vec<String>>,
+}
+
+impl Printer {
+    fn show(&mut self, msg: &WsMessage) {
+        // Subscribe and login acknowledgements carry `event`, not data
+        let Some(data) = msg.data.as_ref().filter(|_| msg.event.is_none()) else { return };
+        let items = match self.view {
+            View::Depth => depth_levels(data),
+            _ => rows_of(data).into_iter().cloned().collect(),
+        };
+        let columns: &[&str] = match self.view {
+            View::Depth => DEPTH_COLUMNS,
+            _ => &[],
+        };
+
+        match self.format {
+            OutputFormat::Json => {
+                let channel = msg.arg.as_ref().and_then(|a| a.get("channel")).cloned().unwrap_or(Value::Null);
+                println!("{}", json!({"success": true, "channel": channel, "action": msg.action, "data": data}));
+            }
+            OutputFormat::Csv => {
+                let batch = Value::Array(items);
+                match &self.csv_headers {
+                    Some(headers) => print!("{}", Tabular::with_headers(&batch, headers.clone()).csv_rows()),
+                    None => {
+                        let table = Tabular::from_value(&batch, columns);
+                        print!("{}", table.to_csv());
+                        self.csv_headers = Some(table.headers);
+                    }
+                }
+            }
+            OutputFormat::Table => {
+                self.fold(items);
+                let table = Tabular::from_value(&Value::Array(self.rows.clone()), columns);
+                // Clear the screen and redraw from the top-left corner
+                print!("\x1b[2J\x1b[H");
+                println!("watch {} - {} (Ctrl-C to quit)\n", self.title, Utc::now().format("%H:%M:%S"));
+                print!("{}", table.to_table());
+            }
+        }
+    }
+
+    fn fold(&mut self, items: Vec<Value>) {
+        match self.view {
+            View::Depth => self.rows = items,
+            View::Tail(n) => {
+                self.rows.extend(items);
+                let excess = self.rows.len().saturating_sub(n);
+                self.rows.drain(..excess);
+            }
+            View::Keyed(keys) => {
+                let key_of = |v: &Value| keys.iter().find_map(|k| v.get(*k).cloned());
+                for item in items {
+                    let key = key_of(&item);
+                    match self.rows.iter_mut().find(|row| key.is_some() && key_of(row) == key) {
+                        Some(row) => *row = item,
+                        None => self.rows.push(item),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Flatten `[{"asks": [[price, size], ...], "bids": [...]}]` into `[side, price, size]` rows,
+/// asks from highest to lowest above the bids
+fn depth_levels(data: &Value) -> Vec<Value> {
+    let Some(book) = rows_of(data).into_iter().next() else { return Vec::new() };
+    let levels = |side: &str| -> Vec<Value> {
+        book.get(format!("{}s", side))
+            .and_then(|l| l.as_array())
+            .map(|levels| {
+                levels
+                    .iter()
+                    .filter_map(|l| Some(json!([side, l.get(0)?, l.get(1)?])))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let mut rows: Vec<Value> = levels("ask").into_iter().rev().collect();
+    rows.extend(levels("bid"));
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn printer(view: View) -> Printer {
+        Printer { format: OutputFormat::Table, title: String::new(), view, rows: Vec::new(), csv_headers: None }
+    }
+
+    #[test]
+    fn test_keyed_and_tail_views() {
+        let mut orders = printer(View::Keyed(&["ordId"]));
+        orders.fold(vec![json!({"ordId": "1", "status": "new"}), json!({"ordId": "2", "status": "new"})]);
+        orders.fold(vec![json!({"ordId": "1", "status": "filled"})]);
+        assert_eq!(orders.rows, [json!({"ordId": "1", "status": "filled"}), json!({"ordId": "2", "status": "new"})]);
+
+        let mut trades = printer(View::Tail(2));
+        trades.fold(vec![json!(1), json!(2), json!(3)]);
+        assert_eq!(trades.rows, [json!(2), json!(3)]);
+    }
+
+    #[test]
+    fn test_depth_levels() {
+        let data = json!([{"asks": [["101", "1"], ["102", "2"]], "bids": [["100", "3"]], "ts": "1"}]);
+        assert_eq!(
+            depth_levels(&data),
+            [json!(["ask", "102", "2"]), json!(["ask", "101", "1"]), json!(["bid", "100", "3"])]
+        );
+    }
+}
@@ -1,15 +1,26 @@
 use crate::WeexClient;
+use crate::capability::MarketClient;
 use crate::error::WeexError;
+use crate::config::Config;
+use crate::credentials::Credentials;
+use crate::rate_limiter::RateLimiter;
+use crate::risk_gate::RiskGate;
 use reqwest::Client;
+use std::sync::Arc;
 use std::time::Duration;
+use zeroize::Zeroizing;
 
 #[derive(Default)]
 pub struct WeexClientBuilder {
-    base_url: Option<String>,
-    api_key: Option<String>,
-    secret_key: Option<String>,
-    passphrase: Option<String>,
+    pub(crate) base_url: Option<String>,
+    credentials: Option<Credentials>,
+    api_key: Option<Zeroizing<String>>,
+    secret_key: Option<Zeroizing<String>>,
+    passphrase: Option<Zeroizing<String>>,
     timeout: Option<Duration>,
+    risk_gate: Option<Arc<RiskGate>>,
+    pub(crate) rate_limiter: Option<Arc<RateLimiter>>,
+    http_client: Option<Client>,
 }
 
 impl WeexClientBuilder {
@@ -17,23 +28,35 @@ impl WeexClientBuilder {
         WeexClientBuilder::default()
     }
 
+    /// Builder for a named profile in the default config file (see `Config::load_default`)
+    pub fn from_profile(name: &str) -> Result<Self, WeexError> {
+        Config::load_default()?.builder(Some(name))
+    }
+
     pub fn base_url(mut self, url: &str) -> Self {
         self.base_url = Some(url.to_string());
         self
     }
 
+    /// Credentials loaded with `Credentials::from_env` or `from_file`; `api_key`,
+    /// `secret_key` and `passphrase` override single fields
+    pub fn credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
     pub fn api_key(mut self, key: &str) -> Self {
-        self.api_key = Some(key.to_string());
+        self.api_key = Some(Zeroizing::new(key.to_string()));
         self
     }
 
     pub fn secret_key(mut self, secret: &str) -> Self {
-        self.secret_key = Some(secret.to_string());
+        self.secret_key = Some(Zeroizing::new(secret.to_string()));
         self
     }
 
     pub fn passphrase(mut self, phrase: &str) -> Self {
-        self.passphrase = Some(phrase.to_string());
+        self.passphrase = Some(Zeroizing::new(phrase.to_string()));
         self
     }
 
@@ -42,26 +65,88 @@ impl WeexClientBuilder {
         self
     }
 
+    /// Reuse an existing HTTP connection pool; `timeout` is then ignored
+    pub fn http_client(mut self, client: Client) -> Self {
+        self.http_client = Some(client);
+        self
+    }
+
+    /// Check every order against a pre-trade risk gate before it is sent
+    ///
+    /// The client only records leverage changes it makes; mark prices and positions
+    /// are not fed from responses, so keep the gate current from market and account
+    /// events (the `Engine` does this) or market orders are rejected for lack of a mark.
+    /// Accepted single orders are reserved in the gate until `RiskGate::order_update`
+    /// reports them filled or cancelled; batch orders are checked but not reserved.
+    pub fn risk_gate(mut self, gate: Arc<RiskGate>) -> Self {
+        self.risk_gate = Some(gate);
+        self
+    }
+
+    /// Wait for a token from `limiter` before every request; share one limiter to pace several clients together
+    pub fn rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Build the client; with no credentials at all it is a public client for
+    /// market data only, while partial credentials are an error
     pub fn build(self) -> Result<WeexClient, WeexError> {
+        let keyless = self.credentials.is_none()
+            && self.api_key.is_none()
+            && self.secret_key.is_none()
+            && self.passphrase.is_none();
+        let credentials = if keyless { None } else { Some(self.resolve_credentials()?) };
+        self.finish(credentials)
+    }
+
+    /// Public market-data client that sends no credentials; keys set on the builder are ignored
+    pub fn build_market(self) -> Result<MarketClient, WeexError> {
+        Ok(MarketClient::new(self.finish(None)?))
+    }
+
+    fn resolve_credentials(&self) -> Result<Arc<Credentials>, WeexError> {
+        let base = self.credentials.as_ref();
+        let api_key = pick(&self.api_key, base, Credentials::api_key)
+            .ok_or_else(|| WeexError::Config("partial credentials: api key missing".into()))?;
+        let secret_key = pick(&self.secret_key, base, Credentials::secret_key)
+            .ok_or_else(|| WeexError::Config("partial credentials: secret key missing".into()))?;
+        let passphrase = pick(&self.passphrase, base, Credentials::passphrase)
+            .ok_or_else(|| WeexError::Config("partial credentials: passphrase missing".into()))?;
+        Ok(Arc::new(Credentials::new(api_key, secret_key, passphrase)))
+    }
+
+    fn finish(self, credentials: Option<Arc<Credentials>>) -> Result<WeexClient, WeexError> {
         let base_url = self.base_url.unwrap_or_else(|| "https://api.weex.com".to_string());
-        let api_key = self.api_key.ok_or_else(|| WeexError::Unknown("API Key required".into()))?;
-        let secret_key = self.secret_key.ok_or_else(|| WeexError::Unknown("Secret Key required".into()))?;
-        let passphrase = self.passphrase.ok_or_else(|| WeexError::Unknown("Passphrase required".into()))?;
-
-        let timeout = self.timeout.unwrap_or(Duration::from_secs(10));
-        
-        let client = Client::builder()
-            .timeout(timeout)
-            .user_agent("WeexRustSDK/0.4.0 (Professional)")
-            .build()
-            .map_err(WeexError::Http)?;
+        let client = match self.http_client {
+            Some(client) => client,
+            None => http_client(self.timeout.unwrap_or(Duration::from_secs(10)))?,
+        };
 
         Ok(WeexClient {
             base_url,
-            api_key,
-            secret_key,
-            passphrase,
+            credentials,
             client,
+            risk_gate: self.risk_gate,
+            rate_limiter: self.rate_limiter,
         })
     }
 }
+
+/// A field set on the builder, else the same field of the base credentials
+fn pick<'a>(
+    own: &'a Option<Zeroizing<String>>,
+    base: Option<&'a Credentials>,
+    stored: fn(&Credentials) -> &str,
+) -> Option<&'a str> {
+    own.as_deref().map(String::as_str).or(base.map(stored))
+}
+
+/// HTTP client with the SDK's user agent
+pub(crate) fn http_client(timeout: Duration) -> Result<Client, WeexError> {
+    Client::builder()
+        .timeout(timeout)
+        .user_agent("WeexRustSDK/0.4.0 (Professional)")
+        .build()
+        .map_err(WeexError::Http)
+}
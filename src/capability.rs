@@ -0,0 +1,259 @@
+//! Clients restricted to what a service needs
+//!
+//! `MarketClient` only reaches public market data, `AccountClient` adds
+//! account, position and order reads, and `TradingClient` adds everything that
+//! changes account state. Each dereferences to the one below it, so a trading
+//! client can be handed to code expecting read access, but not the other way
+//! round:
+//!
+//! ```compile_fail
+//! async fn dashboard(client: weex_rust_sdk::AccountClient) {
+//!     client.close_all_positions("cmt_btcusdt").await.unwrap();
+//! }
+//! ```
+
+use crate::client::WeexClient;
+use crate::error::WeexError;
+use crate::spot::market::Ticker;
+use crate::traits::Exchange;
+use crate::types::{OrderType, Side};
+use crate::ws::private::UserStream;
+use async_trait::async_trait;
+use std::ops::Deref;
+
+/// Forward async methods to the same-named `WeexClient` method
+macro_rules! delegate {
+    ($($name:ident($($arg:ident: $ty:ty),*) -> $ret:ty;)*) => {
+        $(
+            #[doc = concat!("See [`WeexClient::", stringify!($name), "`].")]
+            pub async fn $name(&self, $($arg: $ty),*) -> Result<$ret, WeexError> {
+                self.client().$name($($arg),*).await
+            }
+        )*
+    };
+}
+
+/// Public market data only; needs no credentials (`WeexClientBuilder::build_market`)
+#[derive(Clone, Debug)]
+pub struct MarketClient {
+    inner: WeexClient,
+}
+
+/// Market data plus read-only account, position and order queries
+#[derive(Clone, Debug)]
+pub struct AccountClient {
+    market: MarketClient,
+}
+
+/// Full access: reads plus orders, cancels, leverage, margin and AI log uploads
+#[derive(Clone, Debug)]
+pub struct TradingClient {
+    account: AccountClient,
+}
+
+impl MarketClient {
+    pub fn new(client: WeexClient) -> Self {
+        MarketClient { inner: client }
+    }
+
+    fn client(&self) -> &WeexClient {
+        &self.inner
+    }
+
+    delegate! {
+        get_ticker(symbol: &str) -> Ticker;
+        get_ticker_raw(symbol: &str) -> String;
+        get_all_tickers() -> String;
+        get_klines(symbol: &str, interval: &str, limit: u32) -> String;
+        get_candles(symbol: &str, interval: &str, limit: u32) -> Vec<crate::future::market::Kline>;
+        get_depth(symbol: &str, depth_type: Option<&str>) -> String;
+        get_trades(symbol: &str, limit: Option<u32>) -> String;
+        get_server_time() -> String;
+        get_contracts(symbol: Option<&str>) -> String;
+        get_index(symbol: &str) -> String;
+        get_open_interest(symbol: &str) -> String;
+        get_funding_rate(symbol: &str) -> String;
+        get_funding_time(symbol: &str) -> String;
+        get_history_funding_rate(symbol: &str, page_size: Option<u32>) -> String;
+    }
+}
+
+impl AccountClient {
+    pub fn new(client: WeexClient) -> Self {
+        AccountClient { market: MarketClient::new(client) }
+    }
+
+    fn client(&self) -> &WeexClient {
+        &self.market.inner
+    }
+
+    /// Market-data view of this client
+    pub fn market(&self) -> MarketClient {
+        self.market.clone()
+    }
+
+    /// Private WebSocket stream of order, position and account updates
+    pub fn user_stream(&self) -> UserStream {
+        UserStream::new(self.client().clone())
+    }
+
+    delegate! {
+        get_balance() -> String;
+        get_assets() -> String;
+        get_bills(symbol: &str) -> String;
+        get_funding_payments(symbol: &str) -> Vec<crate::future::account::FundingPayment>;
+        get_settings(symbol: &str) -> String;
+        get_position(symbol: &str) -> String;
+        get_all_positions() -> String;
+        get_open_orders(symbol: &str) -> String;
+        get_current_orders(symbol: &str) -> String;
+        get_order_detail(symbol: &str, order_id: &str) -> String;
+        get_order_history(symbol: &str, page_size: Option<u32>) -> String;
+        list_order_history(symbol: &str, page_size: Option<u32>) -> Vec<crate::future::order::OpenOrder>;
+        get_fills(symbol: &str, order_id: Option<&str>) -> String;
+        list_fills(symbol: &str, order_id: Option<&str>) -> Vec<crate::future::order::Fill>;
+        get_fills_page(symbol: &str, start_time: Option<i64>, end_time: Option<i64>, limit: u32) -> String;
+        list_fills_between(symbol: &str, from: Option<chrono::DateTime<chrono::Utc>>, to: Option<chrono::DateTime<chrono::Utc>>) -> Vec<crate::future::order::Fill>;
+        get_current_plan(symbol: &str) -> String;
+        get_history_plan(symbol: &str) -> String;
+    }
+}
+
+impl TradingClient {
+    pub fn new(client: WeexClient) -> Self {
+        TradingClient { account: AccountClient::new(client) }
+    }
+
+    fn client(&self) -> &WeexClient {
+        self.account.client()
+    }
+
+    /// Read-only view of this client
+    pub fn read_only(&self) -> AccountClient {
+        self.account.clone()
+    }
+
+    delegate! {
+        place_order(req: &crate::spot::order::PlaceOrderRequest) -> crate::spot::order::OrderResponse;
+        post_batch_orders(req: crate::spot::batch::BatchOrderRequest) -> String;
+        place_futures_order(
+            symbol: &str,
+            size: &str,
+            side: Side,
+            order_type: OrderType,
+            price: Option<&str>,
+            client_oid: Option<&str>
+        ) -> String;
+        place_futures_order_request(req: &crate::future::order::FuturesOrderRequest) -> String;
+        cancel_order(symbol: &str, order_id: &str) -> String;
+        cancel_futures_order(symbol: &str, order_id: &str) -> String;
+        cancel_all_orders(symbol: &str) -> String;
+        close_all_positions(symbol: &str) -> String;
+        place_trigger_order(
+            symbol: &str,
+            size: &str,
+            side: &str,
+            trigger_price: &str,
+            execute_price: Option<&str>
+        ) -> String;
+        cancel_trigger_order(symbol: &str, order_id: &str) -> String;
+        place_tpsl(symbol: &str, side: &str, tp_price: Option<&str>, sl_price: Option<&str>) -> String;
+        modify_tpsl(symbol: &str, side: &str, tp_price: Option<&str>, sl_price: Option<&str>) -> String;
+        set_leverage(symbol: &str, leverage: i32, side: Side) -> String;
+        set_margin_mode(symbol: &str, margin_mode: &str) -> String;
+        adjust_margin(symbol: &str, amount: &str, side: &str) -> String;
+        set_auto_margin(symbol: &str, side: &str, auto_margin: bool) -> String;
+        modify_pos_mode(symbol: &str, pos_mode: &str) -> String;
+        upload_ai_log_entry(entry: &crate::ai_log::AiLogEntry) -> String;
+        upload_ai_log(
+            order_id: Option<i64>,
+            stage: &str,
+            model: &str,
+            input: serde_json::Value,
+            output: serde_json::Value,
+            explanation: &str
+        ) -> String;
+    }
+}
+
+impl Deref for AccountClient {
+    type Target = MarketClient;
+
+    fn deref(&self) -> &MarketClient {
+        &self.market
+    }
+}
+
+impl Deref for TradingClient {
+    type Target = AccountClient;
+
+    fn deref(&self) -> &AccountClient {
+        &self.account
+    }
+}
+
+impl From<WeexClient> for MarketClient {
+    fn from(client: WeexClient) -> Self {
+        MarketClient::new(client)
+    }
+}
+
+impl From<WeexClient> for AccountClient {
+    fn from(client: WeexClient) -> Self {
+        AccountClient::new(client)
+    }
+}
+
+impl From<WeexClient> for TradingClient {
+    fn from(client: WeexClient) -> Self {
+        TradingClient::new(client)
+    }
+}
+
+#[async_trait]
+impl Exchange for TradingClient {
+    async fn get_ticker(&self, symbol: &str) -> Result<Ticker, WeexError> {
+        self.client().get_ticker(symbol).await
+    }
+    async fn get_balance(&self) -> Result<String, WeexError> {
+        self.client().get_balance().await
+    }
+    async fn place_order(
+        &self,
+        symbol: &str,
+        size: &str,
+        side: Side,
+        order_type: OrderType,
+        price: Option<&str>,
+    ) -> Result<String, WeexError> {
+        Exchange::place_order(self.client(), symbol, size, side, order_type, price).await
+    }
+    async fn cancel_all_orders(&self, symbol: &str) -> Result<String, WeexError> {
+        self.client().cancel_all_orders(symbol).await
+    }
+    async fn close_all_positions(&self, symbol: &str) -> Result<String, WeexError> {
+        self.client().close_all_positions(symbol).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::http_stub;
+
+    #[tokio::test]
+    async fn test_market_client_without_credentials() {
+        let (url, stub) = http_stub().await;
+        let market = WeexClient::builder().base_url(&url).build_market().unwrap();
+        market.get_server_time().await.unwrap();
+        let (request_line, _) = stub.await.unwrap();
+        assert!(request_line.starts_with("GET /capi/v2/market/time"));
+        assert!(market.inner.credentials.is_none());
+
+        // Views share the underlying client
+        let trading = TradingClient::from(
+            WeexClient::builder().base_url(&url).api_key("k").secret_key("s").passphrase("p").build().unwrap(),
+        );
+        assert_eq!(trading.read_only().market().inner.base_url, url);
+    }
+}
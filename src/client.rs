@@ -1,13 +1,20 @@
 use crate::spot::market::Ticker;
 use crate::spot::batch::BatchOrderRequest;
-use crate::util::generate_signature;
+use crate::credentials::Credentials;
 use crate::error::WeexError;
 use crate::builder::WeexClientBuilder;
+use crate::rate_limiter::RateLimiter;
+use crate::risk_gate::{OrderIntent, RiskGate, RiskRejection};
+use crate::types::{OrderType, Side};
+use std::sync::Arc;
 use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
 use reqwest::Client;
 use std::time::{SystemTime, UNIX_EPOCH};
 use serde::Deserialize;
 
+/// Page size requested by `list_fills_between`, the fills endpoint maximum
+const FILLS_PAGE_LIMIT: u32 = 100;
+
 // Helper for parsing response logic
 #[derive(Debug, Deserialize)]
 struct ApiResponse<T> {
@@ -21,10 +28,22 @@ struct ApiResponse<T> {
 #[derive(Clone)]
 pub struct WeexClient {
     pub base_url: String,
-    pub api_key: String,
-    pub secret_key: String,
-    pub passphrase: String,
+    /// `None` for a public client: market data works, private endpoints fail with `MissingCredentials`
+    pub(crate) credentials: Option<Arc<Credentials>>,
     pub client: Client,
+    pub risk_gate: Option<Arc<RiskGate>>,
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+}
+
+impl std::fmt::Debug for WeexClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WeexClient")
+            .field("base_url", &self.base_url)
+            .field("credentials", &self.credentials)
+            .field("risk_gate", &self.risk_gate.is_some())
+            .field("rate_limiter", &self.rate_limiter.is_some())
+            .finish()
+    }
 }
 
 impl WeexClient {
@@ -32,6 +51,11 @@ impl WeexClient {
         WeexClientBuilder::new()
     }
 
+    /// Whether this client can call private (signed) endpoints
+    pub fn has_credentials(&self) -> bool {
+        self.credentials.is_some()
+    }
+
     pub fn get_timestamp(&self) -> String {
         let start = SystemTime::now();
         let since_the_epoch = start
@@ -41,30 +65,64 @@ impl WeexClient {
     }
 
     pub fn build_headers(&self, method: &str, request_path: &str, query_string: &str, body: &str, timestamp: &str) -> Result<HeaderMap, WeexError> {
+        let credentials = self.credentials.as_ref()
+            .ok_or_else(|| WeexError::MissingCredentials(request_path.to_string()))?;
         let mut headers = HeaderMap::new();
         // Safe header creation
-        headers.insert("ACCESS-KEY", HeaderValue::from_str(&self.api_key).map_err(|_| WeexError::Unknown("Invalid Header Value".into()))?);
-        headers.insert("ACCESS-PASSPHRASE", HeaderValue::from_str(&self.passphrase).map_err(|_| WeexError::Unknown("Invalid Header Value".into()))?);
+        headers.insert("ACCESS-KEY", HeaderValue::from_str(credentials.api_key()).map_err(|_| WeexError::Unknown("Invalid Header Value".into()))?);
+        headers.insert("ACCESS-PASSPHRASE", HeaderValue::from_str(credentials.passphrase()).map_err(|_| WeexError::Unknown("Invalid Header Value".into()))?);
         headers.insert("ACCESS-TIMESTAMP", HeaderValue::from_str(timestamp).map_err(|_| WeexError::Unknown("Invalid Header Value".into()))?);
         headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
 
         let message = format!("{}{}{}{}{}", timestamp, method.to_uppercase(), request_path, query_string, body);
-        let signature = generate_signature(&self.secret_key, &message);
+        let signature = credentials.sign(&message);
         headers.insert("ACCESS-SIGN", HeaderValue::from_str(&signature).map_err(|_| WeexError::Signing("Invalid Signature Header".into()))?);
 
         Ok(headers)
     }
 
+    /// Wait for the attached rate limiter, if any
+    pub(crate) async fn throttle(&self) {
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire().await;
+        }
+    }
+
+    /// Run an order through the attached risk gate, if any
+    fn check_risk(&self, intent: &OrderIntent) -> Result<(), WeexError> {
+        if let Some(gate) = &self.risk_gate {
+            gate.check_order(intent)?;
+        }
+        Ok(())
+    }
+
+    /// Reserve an order the exchange accepted in the attached risk gate, if any
+    fn reserve_risk(&self, intent: &OrderIntent, order_id: Option<String>) {
+        if let Some(gate) = &self.risk_gate {
+            match order_id {
+                Some(order_id) => gate.reserve(&order_id, intent),
+                None => tracing::warn!("No order ID for accepted {} order, risk gate cannot reserve it", intent.symbol),
+            }
+        }
+    }
+
+    /// Reserve a futures order from its place-order response once acknowledged
+    fn reserve_placed(&self, intent: Option<&OrderIntent>, text: &str) {
+        if let Some(intent) = intent {
+            if crate::util::check_ack(text).is_ok() {
+                self.reserve_risk(intent, crate::util::order_id_from_response(text).map(|id| id.to_string()));
+            }
+        }
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn get_ticker(&self, symbol: &str) -> Result<Ticker, WeexError> {
         let path = "/capi/v2/market/ticker";
         let qs = format!("?symbol={}", symbol);
         let url = format!("{}{}{}", self.base_url, path, qs);
-        
-        let timestamp = self.get_timestamp();
-        let headers = self.build_headers("GET", path, &qs, "", &timestamp)?;
 
-        let resp = self.client.get(&url).headers(headers).send().await?;
+        self.throttle().await;
+        let resp = self.client.get(&url).send().await?;
         let text = resp.text().await?;
 
         // Logic to try flat vs wrapped parsing
@@ -83,17 +141,16 @@ impl WeexClient {
         let path = "/capi/v2/market/ticker";
         let qs = format!("?symbol={}", symbol);
         let url = format!("{}{}{}", self.base_url, path, qs);
-        
-        let timestamp = self.get_timestamp();
-        let headers = self.build_headers("GET", path, &qs, "", &timestamp)?;
 
-        let resp = self.client.get(&url).headers(headers).send().await?;
+        self.throttle().await;
+        let resp = self.client.get(&url).send().await?;
         Ok(resp.text().await?)
     }
     
     pub async fn get_balance(&self) -> Result<String, WeexError> {
          let path = "/capi/v2/account/balance";
          let url = format!("{}{}", self.base_url, path);
+         self.throttle().await;
          let timestamp = self.get_timestamp();
          let headers = self.build_headers("GET", path, "", "", &timestamp)?;
          
@@ -102,6 +159,9 @@ impl WeexClient {
     }
 
     pub async fn set_leverage(&self, symbol: &str, leverage: i32, side: crate::types::Side) -> Result<String, WeexError> {
+        if let Some(gate) = &self.risk_gate {
+            gate.check_leverage(leverage.max(0) as u32)?;
+        }
         let path = "/capi/v2/account/leverage";
         let body = serde_json::json!({
             "symbol": symbol,
@@ -109,11 +169,18 @@ impl WeexClient {
             "side": side
         }).to_string();
         let url = format!("{}{}", self.base_url, path);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("POST", path, "", &body, &timestamp)?;
 
         let resp = self.client.post(&url).headers(headers).body(body).send().await?;
-        Ok(resp.text().await?)
+        let text = resp.text().await?;
+        if let Some(gate) = &self.risk_gate {
+            if crate::util::check_ack(&text).is_ok() {
+                gate.update_leverage(symbol, leverage.max(0) as u32);
+            }
+        }
+        Ok(text)
     }
 
     pub async fn set_margin_mode(&self, symbol: &str, margin_mode: &str) -> Result<String, WeexError> {
@@ -123,6 +190,7 @@ impl WeexClient {
             "marginMode": margin_mode
         }).to_string();
         let url = format!("{}{}", self.base_url, path);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("POST", path, "", &body, &timestamp)?;
 
@@ -131,9 +199,13 @@ impl WeexClient {
     }
 
     pub async fn post_batch_orders(&self, req: BatchOrderRequest) -> Result<String, WeexError> {
+        for order in &req.orders {
+            self.check_risk(&spot_intent(&req.symbol, order))?;
+        }
         let path = "/api/v2/trade/batch-orders"; 
         let body = serde_json::to_string(&req)?;
         let url = format!("{}{}", self.base_url, path);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("POST", path, "", &body, &timestamp)?;
 
@@ -146,9 +218,12 @@ impl WeexClient {
     /// Place a single order (Spot)
     #[tracing::instrument(skip(self))]
     pub async fn place_order(&self, req: &crate::spot::order::PlaceOrderRequest) -> Result<crate::spot::order::OrderResponse, WeexError> {
+        let intent = spot_intent(&req.symbol, req);
+        self.check_risk(&intent)?;
         let path = "/api/v2/trade/orders";
         let body = serde_json::to_string(req)?;
         let url = format!("{}{}", self.base_url, path);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("POST", path, "", &body, &timestamp)?;
 
@@ -156,10 +231,12 @@ impl WeexClient {
         let text = resp.text().await?;
         
         let wrapper: ApiResponse<crate::spot::order::OrderResponse> = serde_json::from_str(&text)?;
-        wrapper.data.ok_or_else(|| WeexError::Api {
+        let order = wrapper.data.ok_or_else(|| WeexError::Api {
             code: wrapper.code.unwrap_or_default(),
-            msg: wrapper.msg.unwrap_or_else(|| text),
-        })
+            msg: wrapper.msg.unwrap_or(text),
+        })?;
+        self.reserve_risk(&intent, Some(order.order_id.clone()));
+        Ok(order)
     }
 
     /// Place a futures/contract order
@@ -173,6 +250,13 @@ impl WeexClient {
         price: Option<&str>,
         client_oid: Option<&str>,
     ) -> Result<String, WeexError> {
+        let intent = match self.risk_gate {
+            Some(_) => Some(OrderIntent::from_strs(symbol, side, order_type, size, price)?),
+            None => None,
+        };
+        if let Some(intent) = &intent {
+            self.check_risk(intent)?;
+        }
         let path = "/capi/v2/order/placeOrder";
         let mut body_map = serde_json::json!({
             "symbol": symbol,
@@ -191,11 +275,39 @@ impl WeexClient {
         
         let body = body_map.to_string();
         let url = format!("{}{}", self.base_url, path);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("POST", path, "", &body, &timestamp)?;
 
         let resp = self.client.post(&url).headers(headers).body(body).send().await?;
-        Ok(resp.text().await?)
+        let text = resp.text().await?;
+        self.reserve_placed(intent.as_ref(), &text);
+        Ok(text)
+    }
+
+    /// Place a validated futures order with limit/market, time-in-force, reduce-only and TP/SL options
+    #[tracing::instrument(skip(self, req), fields(symbol = %req.symbol, side = %req.side))]
+    pub async fn place_futures_order_request(&self, req: &crate::future::order::FuturesOrderRequest) -> Result<String, WeexError> {
+        req.validate()?;
+        let intent = OrderIntent {
+            symbol: req.symbol.clone(),
+            side: req.side,
+            order_type: req.order_type,
+            size: req.size,
+            price: req.price,
+        };
+        self.check_risk(&intent)?;
+        let path = "/capi/v2/order/placeOrder";
+        let body = req.body().to_string();
+        let url = format!("{}{}", self.base_url, path);
+        self.throttle().await;
+        let timestamp = self.get_timestamp();
+        let headers = self.build_headers("POST", path, "", &body, &timestamp)?;
+
+        let resp = self.client.post(&url).headers(headers).body(body).send().await?;
+        let text = resp.text().await?;
+        self.reserve_placed(Some(&intent), &text);
+        Ok(text)
     }
 
     /// Cancel a single order
@@ -207,6 +319,7 @@ impl WeexClient {
             "orderId": order_id
         }).to_string();
         let url = format!("{}{}", self.base_url, path);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("POST", path, "", &body, &timestamp)?;
 
@@ -220,6 +333,7 @@ impl WeexClient {
         let path = "/api/v2/trade/open-orders";
         let qs = format!("?symbol={}", symbol);
         let url = format!("{}{}{}", self.base_url, path, qs);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("GET", path, &qs, "", &timestamp)?;
 
@@ -233,6 +347,7 @@ impl WeexClient {
         let path = "/capi/v2/account/position/singlePosition";
         let qs = format!("?symbol={}", symbol);
         let url = format!("{}{}{}", self.base_url, path, qs);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("GET", path, &qs, "", &timestamp)?;
 
@@ -248,13 +363,18 @@ impl WeexClient {
         let path = "/capi/v2/market/candles";
         let qs = format!("?symbol={}&granularity={}&limit={}", symbol, interval, limit);
         let url = format!("{}{}{}", self.base_url, path, qs);
-        let timestamp = self.get_timestamp();
-        let headers = self.build_headers("GET", path, &qs, "", &timestamp)?;
 
-        let resp = self.client.get(&url).headers(headers).send().await?;
+        self.throttle().await;
+        let resp = self.client.get(&url).send().await?;
         Ok(resp.text().await?)
     }
 
+    /// Get Kline/Candlestick data parsed into typed candles, oldest first
+    pub async fn get_candles(&self, symbol: &str, interval: &str, limit: u32) -> Result<Vec<crate::future::market::Kline>, WeexError> {
+        let raw = self.get_klines(symbol, interval, limit).await?;
+        crate::future::market::Kline::parse_list(&raw)
+    }
+
     /// Get orderbook depth snapshot
     /// depth_type: "step0" (default), "step1", "step2", etc.
     #[tracing::instrument(skip(self))]
@@ -262,10 +382,9 @@ impl WeexClient {
         let path = "/capi/v2/market/depth";
         let qs = format!("?symbol={}&type={}", symbol, depth_type.unwrap_or("step0"));
         let url = format!("{}{}{}", self.base_url, path, qs);
-        let timestamp = self.get_timestamp();
-        let headers = self.build_headers("GET", path, &qs, "", &timestamp)?;
 
-        let resp = self.client.get(&url).headers(headers).send().await?;
+        self.throttle().await;
+        let resp = self.client.get(&url).send().await?;
         Ok(resp.text().await?)
     }
 
@@ -275,10 +394,9 @@ impl WeexClient {
         let path = "/capi/v2/market/fundingRate";
         let qs = format!("?symbol={}", symbol);
         let url = format!("{}{}{}", self.base_url, path, qs);
-        let timestamp = self.get_timestamp();
-        let headers = self.build_headers("GET", path, &qs, "", &timestamp)?;
 
-        let resp = self.client.get(&url).headers(headers).send().await?;
+        self.throttle().await;
+        let resp = self.client.get(&url).send().await?;
         Ok(resp.text().await?)
     }
 
@@ -288,6 +406,7 @@ impl WeexClient {
     pub async fn get_server_time(&self) -> Result<String, WeexError> {
         let path = "/capi/v2/market/time";
         let url = format!("{}{}", self.base_url, path);
+        self.throttle().await;
         let resp = self.client.get(&url).send().await?;
         Ok(resp.text().await?)
     }
@@ -297,6 +416,7 @@ impl WeexClient {
         let path = "/capi/v2/market/contracts";
         let qs = symbol.map(|s| format!("?symbol={}", s)).unwrap_or_default();
         let url = format!("{}{}{}", self.base_url, path, qs);
+        self.throttle().await;
         let resp = self.client.get(&url).send().await?;
         Ok(resp.text().await?)
     }
@@ -305,6 +425,7 @@ impl WeexClient {
     pub async fn get_all_tickers(&self) -> Result<String, WeexError> {
         let path = "/capi/v2/market/tickers";
         let url = format!("{}{}", self.base_url, path);
+        self.throttle().await;
         let resp = self.client.get(&url).send().await?;
         Ok(resp.text().await?)
     }
@@ -314,6 +435,7 @@ impl WeexClient {
         let path = "/capi/v2/market/trades";
         let qs = format!("?symbol={}&limit={}", symbol, limit.unwrap_or(100));
         let url = format!("{}{}{}", self.base_url, path, qs);
+        self.throttle().await;
         let resp = self.client.get(&url).send().await?;
         Ok(resp.text().await?)
     }
@@ -323,6 +445,7 @@ impl WeexClient {
         let path = "/capi/v2/market/index";
         let qs = format!("?symbol={}", symbol);
         let url = format!("{}{}{}", self.base_url, path, qs);
+        self.throttle().await;
         let resp = self.client.get(&url).send().await?;
         Ok(resp.text().await?)
     }
@@ -332,6 +455,7 @@ impl WeexClient {
         let path = "/capi/v2/market/openInterest";
         let qs = format!("?symbol={}", symbol);
         let url = format!("{}{}{}", self.base_url, path, qs);
+        self.throttle().await;
         let resp = self.client.get(&url).send().await?;
         Ok(resp.text().await?)
     }
@@ -341,6 +465,7 @@ impl WeexClient {
         let path = "/capi/v2/market/fundingTime";
         let qs = format!("?symbol={}", symbol);
         let url = format!("{}{}{}", self.base_url, path, qs);
+        self.throttle().await;
         let resp = self.client.get(&url).send().await?;
         Ok(resp.text().await?)
     }
@@ -350,6 +475,7 @@ impl WeexClient {
         let path = "/capi/v2/market/historyFundingRate";
         let qs = format!("?symbol={}&pageSize={}", symbol, page_size.unwrap_or(20));
         let url = format!("{}{}{}", self.base_url, path, qs);
+        self.throttle().await;
         let resp = self.client.get(&url).send().await?;
         Ok(resp.text().await?)
     }
@@ -361,6 +487,7 @@ impl WeexClient {
     pub async fn get_assets(&self) -> Result<String, WeexError> {
         let path = "/capi/v2/account/assets";
         let url = format!("{}{}", self.base_url, path);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("GET", path, "", "", &timestamp)?;
         let resp = self.client.get(&url).headers(headers).send().await?;
@@ -373,18 +500,26 @@ impl WeexClient {
         let path = "/capi/v2/account/bills";
         let qs = format!("?symbol={}", symbol);
         let url = format!("{}{}{}", self.base_url, path, qs);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("GET", path, &qs, "", &timestamp)?;
         let resp = self.client.get(&url).headers(headers).send().await?;
         Ok(resp.text().await?)
     }
 
+    /// Get funding fee settlements for a symbol, oldest first
+    pub async fn get_funding_payments(&self, symbol: &str) -> Result<Vec<crate::future::account::FundingPayment>, WeexError> {
+        let raw = self.get_bills(symbol).await?;
+        crate::future::account::FundingPayment::parse_bills(&raw)
+    }
+
     /// Get futures settings for a symbol
     #[tracing::instrument(skip(self))]
     pub async fn get_settings(&self, symbol: &str) -> Result<String, WeexError> {
         let path = "/capi/v2/account/settings";
         let qs = format!("?symbol={}", symbol);
         let url = format!("{}{}{}", self.base_url, path, qs);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("GET", path, &qs, "", &timestamp)?;
         let resp = self.client.get(&url).headers(headers).send().await?;
@@ -401,6 +536,7 @@ impl WeexClient {
             "side": side
         }).to_string();
         let url = format!("{}{}", self.base_url, path);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("POST", path, "", &body, &timestamp)?;
         let resp = self.client.post(&url).headers(headers).body(body).send().await?;
@@ -417,6 +553,7 @@ impl WeexClient {
             "autoMargin": if auto_margin { "on" } else { "off" }
         }).to_string();
         let url = format!("{}{}", self.base_url, path);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("POST", path, "", &body, &timestamp)?;
         let resp = self.client.post(&url).headers(headers).body(body).send().await?;
@@ -428,6 +565,7 @@ impl WeexClient {
     pub async fn get_all_positions(&self) -> Result<String, WeexError> {
         let path = "/capi/v2/account/position/allPosition";
         let url = format!("{}{}", self.base_url, path);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("GET", path, "", "", &timestamp)?;
         let resp = self.client.get(&url).headers(headers).send().await?;
@@ -443,6 +581,7 @@ impl WeexClient {
             "posMode": pos_mode
         }).to_string();
         let url = format!("{}{}", self.base_url, path);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("POST", path, "", &body, &timestamp)?;
         let resp = self.client.post(&url).headers(headers).body(body).send().await?;
@@ -457,6 +596,7 @@ impl WeexClient {
         let path = "/capi/v2/order/detail";
         let qs = format!("?symbol={}&orderId={}", symbol, order_id);
         let url = format!("{}{}{}", self.base_url, path, qs);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("GET", path, &qs, "", &timestamp)?;
         let resp = self.client.get(&url).headers(headers).send().await?;
@@ -469,6 +609,7 @@ impl WeexClient {
         let path = "/capi/v2/order/history";
         let qs = format!("?symbol={}&pageSize={}", symbol, page_size.unwrap_or(20));
         let url = format!("{}{}{}", self.base_url, path, qs);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("GET", path, &qs, "", &timestamp)?;
         let resp = self.client.get(&url).headers(headers).send().await?;
@@ -481,6 +622,7 @@ impl WeexClient {
         let path = "/capi/v2/order/current";
         let qs = format!("?symbol={}", symbol);
         let url = format!("{}{}{}", self.base_url, path, qs);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("GET", path, &qs, "", &timestamp)?;
         let resp = self.client.get(&url).headers(headers).send().await?;
@@ -496,6 +638,7 @@ impl WeexClient {
             None => format!("?symbol={}", symbol),
         };
         let url = format!("{}{}{}", self.base_url, path, qs);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("GET", path, &qs, "", &timestamp)?;
         let resp = self.client.get(&url).headers(headers).send().await?;
@@ -511,6 +654,7 @@ impl WeexClient {
             "orderId": order_id
         }).to_string();
         let url = format!("{}{}", self.base_url, path);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("POST", path, "", &body, &timestamp)?;
         let resp = self.client.post(&url).headers(headers).body(body).send().await?;
@@ -527,6 +671,21 @@ impl WeexClient {
         trigger_price: &str,
         execute_price: Option<&str>,
     ) -> Result<String, WeexError> {
+        let intent = match self.risk_gate {
+            Some(_) => {
+                let parsed_side = side.parse::<Side>().map_err(|_| RiskRejection::UnknownSide(side.to_string()))?;
+                let intent = OrderIntent::from_strs(
+                    symbol,
+                    parsed_side,
+                    OrderType::Trigger,
+                    size,
+                    Some(execute_price.unwrap_or(trigger_price)),
+                )?;
+                self.check_risk(&intent)?;
+                Some(intent)
+            }
+            None => None,
+        };
         let path = "/capi/v2/order/placeTriggerOrder";
         let mut body_map = serde_json::json!({
             "symbol": symbol,
@@ -540,10 +699,13 @@ impl WeexClient {
         }
         let body = body_map.to_string();
         let url = format!("{}{}", self.base_url, path);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("POST", path, "", &body, &timestamp)?;
         let resp = self.client.post(&url).headers(headers).body(body).send().await?;
-        Ok(resp.text().await?)
+        let text = resp.text().await?;
+        self.reserve_placed(intent.as_ref(), &text);
+        Ok(text)
     }
 
     /// Cancel trigger order
@@ -555,6 +717,7 @@ impl WeexClient {
             "orderId": order_id
         }).to_string();
         let url = format!("{}{}", self.base_url, path);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("POST", path, "", &body, &timestamp)?;
         let resp = self.client.post(&url).headers(headers).body(body).send().await?;
@@ -567,6 +730,68 @@ impl WeexClient {
         let path = "/capi/v2/order/currentPlan";
         let qs = format!("?symbol={}", symbol);
         let url = format!("{}{}{}", self.base_url, path, qs);
+        self.throttle().await;
+        let timestamp = self.get_timestamp();
+        let headers = self.build_headers("GET", path, &qs, "", &timestamp)?;
+        let resp = self.client.get(&url).headers(headers).send().await?;
+        Ok(resp.text().await?)
+    }
+
+    /// Get order history parsed into typed orders
+    pub async fn list_order_history(&self, symbol: &str, page_size: Option<u32>) -> Result<Vec<crate::future::order::OpenOrder>, WeexError> {
+        let raw = self.get_order_history(symbol, page_size).await?;
+        crate::future::order::OpenOrder::parse_list(&raw)
+    }
+
+    /// Get fills parsed into typed fills, oldest first
+    pub async fn list_fills(&self, symbol: &str, order_id: Option<&str>) -> Result<Vec<crate::future::order::Fill>, WeexError> {
+        let raw = self.get_fills(symbol, order_id).await?;
+        crate::future::order::Fill::parse_list(&raw)
+    }
+
+    /// Get every fill in `[from, to]`, paging back from `to` (or now) until `from` is reached
+    ///
+    /// Without `from`, pages are fetched until the exchange returns a short page.
+    pub async fn list_fills_between(
+        &self,
+        symbol: &str,
+        from: Option<chrono::DateTime<chrono::Utc>>,
+        to: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<Vec<crate::future::order::Fill>, WeexError> {
+        let mut fills: Vec<crate::future::order::Fill> = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+        let mut end = to.map(|t| t.timestamp_millis());
+        loop {
+            let raw = self.get_fills_page(symbol, from.map(|t| t.timestamp_millis()), end, FILLS_PAGE_LIMIT).await?;
+            let page = crate::future::order::Fill::parse_list(&raw)?;
+            let full = page.len() as u32 >= FILLS_PAGE_LIMIT;
+            let Some(oldest) = page.first().map(|f| f.timestamp) else { break };
+            let before = fills.len();
+            fills.extend(page.into_iter().filter(|f| seen.insert(f.trade_id.clone())));
+            // Stop on a short page, once `from` is covered, or when a page only repeats fills
+            if !full || fills.len() == before || from.is_some_and(|from| oldest <= from) {
+                break;
+            }
+            // The end bound is inclusive, so fills sharing the oldest millisecond are fetched again and deduplicated
+            end = Some(oldest.timestamp_millis());
+        }
+        fills.retain(|f| crate::util::in_range(f.timestamp, from, to));
+        fills.sort_by_key(|f| f.timestamp);
+        Ok(fills)
+    }
+
+    /// One page of fills within an optional millisecond time window, newest first
+    pub async fn get_fills_page(&self, symbol: &str, start_time: Option<i64>, end_time: Option<i64>, limit: u32) -> Result<String, WeexError> {
+        let path = "/capi/v2/order/fills";
+        let mut qs = format!("?symbol={}&limit={}", symbol, limit);
+        if let Some(start) = start_time {
+            qs.push_str(&format!("&startTime={}", start));
+        }
+        if let Some(end) = end_time {
+            qs.push_str(&format!("&endTime={}", end));
+        }
+        let url = format!("{}{}{}", self.base_url, path, qs);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("GET", path, &qs, "", &timestamp)?;
         let resp = self.client.get(&url).headers(headers).send().await?;
@@ -579,6 +804,7 @@ impl WeexClient {
         let path = "/capi/v2/order/historyPlan";
         let qs = format!("?symbol={}", symbol);
         let url = format!("{}{}{}", self.base_url, path, qs);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("GET", path, &qs, "", &timestamp)?;
         let resp = self.client.get(&url).headers(headers).send().await?;
@@ -593,6 +819,7 @@ impl WeexClient {
             "symbol": symbol
         }).to_string();
         let url = format!("{}{}", self.base_url, path);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("POST", path, "", &body, &timestamp)?;
         let resp = self.client.post(&url).headers(headers).body(body).send().await?;
@@ -607,6 +834,7 @@ impl WeexClient {
             "symbol": symbol
         }).to_string();
         let url = format!("{}{}", self.base_url, path);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("POST", path, "", &body, &timestamp)?;
         let resp = self.client.post(&url).headers(headers).body(body).send().await?;
@@ -635,6 +863,7 @@ impl WeexClient {
         }
         let body = body_map.to_string();
         let url = format!("{}{}", self.base_url, path);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("POST", path, "", &body, &timestamp)?;
         let resp = self.client.post(&url).headers(headers).body(body).send().await?;
@@ -663,6 +892,7 @@ impl WeexClient {
         }
         let body = body_map.to_string();
         let url = format!("{}{}", self.base_url, path);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("POST", path, "", &body, &timestamp)?;
         let resp = self.client.post(&url).headers(headers).body(body).send().await?;
@@ -671,6 +901,28 @@ impl WeexClient {
 
     // ==================== AI WARS: AI LOG (CRITICAL) ====================
 
+    /// Upload a validated AI log entry, failing unless the exchange acknowledges it
+    ///
+    /// For uploads that must not be lost, queue entries with `AiLogQueue` instead.
+    #[tracing::instrument(skip(self, entry), fields(stage = %entry.stage))]
+    pub async fn upload_ai_log_entry(&self, entry: &crate::ai_log::AiLogEntry) -> Result<String, WeexError> {
+        entry.validate()?;
+        let path = "/capi/v2/order/uploadAiLog";
+        let body = entry.request_body().to_string();
+        let url = format!("{}{}", self.base_url, path);
+        self.throttle().await;
+        let timestamp = self.get_timestamp();
+        let headers = self.build_headers("POST", path, "", &body, &timestamp)?;
+        let resp = self.client.post(&url).headers(headers).body(body).send().await?;
+        let status = resp.status();
+        let text = resp.text().await?;
+        if !status.is_success() {
+            return Err(WeexError::Api { code: status.as_u16().to_string(), msg: text });
+        }
+        crate::util::check_ack(&text)?;
+        Ok(text)
+    }
+
     /// Upload AI log for competition compliance
     #[tracing::instrument(skip(self, input, output))]
     pub async fn upload_ai_log(
@@ -695,6 +947,7 @@ impl WeexClient {
         }
         let body = body_map.to_string();
         let url = format!("{}{}", self.base_url, path);
+        self.throttle().await;
         let timestamp = self.get_timestamp();
         let headers = self.build_headers("POST", path, "", &body, &timestamp)?;
         let resp = self.client.post(&url).headers(headers).body(body).send().await?;
@@ -702,6 +955,17 @@ impl WeexClient {
     }
 }
 
+/// Risk view of a spot order request
+fn spot_intent(symbol: &str, req: &crate::spot::order::PlaceOrderRequest) -> OrderIntent {
+    OrderIntent {
+        symbol: symbol.to_string(),
+        side: req.side,
+        order_type: req.order_type,
+        size: req.size,
+        price: req.price,
+    }
+}
+
 use crate::traits::Exchange;
 use async_trait::async_trait;
 
@@ -713,6 +977,96 @@ impl Exchange for WeexClient {
     async fn get_balance(&self) -> Result<String, WeexError> {
         self.get_balance().await
     }
+    async fn place_order(
+        &self,
+        symbol: &str,
+        size: &str,
+        side: Side,
+        order_type: OrderType,
+        price: Option<&str>,
+    ) -> Result<String, WeexError> {
+        self.place_futures_order(symbol, size, side, order_type, price, None).await
+    }
+    async fn cancel_all_orders(&self, symbol: &str) -> Result<String, WeexError> {
+        self.cancel_all_orders(symbol).await
+    }
+    async fn close_all_positions(&self, symbol: &str) -> Result<String, WeexError> {
+        self.close_all_positions(symbol).await
+    }
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{http_stub, http_stub_responses};
+
+    #[tokio::test]
+    async fn test_public_client_without_credentials() {
+        let (url, stub) = http_stub().await;
+        let client = WeexClient::builder().base_url(&url).build().unwrap();
+        assert!(!client.has_credentials());
+
+        client.get_klines("cmt_btcusdt", "1H", 10).await.unwrap();
+        let (request_line, _) = stub.await.unwrap();
+        assert!(request_line.starts_with("GET /capi/v2/market/candles?symbol=cmt_btcusdt"));
+
+        // Private endpoints fail before anything is sent
+        assert!(matches!(client.get_balance().await, Err(WeexError::MissingCredentials(_))));
+
+        // Partial credentials are a misconfiguration, not a public client
+        assert!(matches!(WeexClient::builder().api_key("k").build(), Err(WeexError::Config(_))));
+    }
+
+    #[tokio::test]
+    async fn test_fills_paged_back_to_start() {
+        let fill = |id: u32, ms: i64| {
+            format!(r#"{{"tradeId":"{}","symbol":"cmt_btcusdt","side":"buy","fillSize":"1","fillPrice":"1","createdTime":"{}"}}"#, id, ms)
+        };
+        // First page is full (newest fills), the second reaches back past `from`
+        let first: Vec<String> = (0..FILLS_PAGE_LIMIT).map(|i| fill(1000 + i, 10_000 + i as i64)).collect();
+        let second = [fill(1000, 10_000), fill(1, 5_000), fill(2, 1_000)];
+        let (url, stub) = http_stub_responses(vec![
+            format!("[{}]", first.join(",")),
+            format!("[{}]", second.join(",")),
+        ])
+        .await;
+        let client = WeexClient::builder().base_url(&url).api_key("k").secret_key("s").passphrase("p").build().unwrap();
+
+        let from = chrono::TimeZone::timestamp_millis_opt(&chrono::Utc, 2_000).unwrap();
+        let fills = client.list_fills_between("cmt_btcusdt", Some(from), None).await.unwrap();
+        assert_eq!(fills.len(), FILLS_PAGE_LIMIT as usize + 1);
+        assert_eq!(fills[0].trade_id, "1");
+
+        let requests = stub.await.unwrap();
+        assert!(requests[0].0.contains("startTime=2000") && !requests[0].0.contains("endTime"));
+        assert!(requests[1].0.contains("endTime=10000"));
+    }
+
+    #[tokio::test]
+    async fn test_signs_after_rate_limit_wait() {
+        let (url, stub) = http_stub_responses(vec![String::new(), String::new()]).await;
+        let client = WeexClient::builder()
+            .base_url(&url)
+            .api_key("k")
+            .secret_key("s")
+            .passphrase("p")
+            .rate_limiter(Arc::new(RateLimiter::new(1, 1)))
+            .build()
+            .unwrap();
+
+        client.get_balance().await.unwrap();
+        let waited_from = chrono::Utc::now().timestamp_millis();
+        client.get_balance().await.unwrap();
+
+        let requests = stub.await.unwrap();
+        let timestamp: i64 = requests[1]
+            .0
+            .lines()
+            .find_map(|l| l.to_lowercase().strip_prefix("access-timestamp:").map(|v| v.trim().parse().unwrap()))
+            .unwrap();
+        // The second request waited for a token, so it must be signed after the wait
+        assert!(timestamp - waited_from >= 500, "signed {}ms into the wait", timestamp - waited_from);
+    }
+}
@@ -0,0 +1,238 @@
+use crate::builder::WeexClientBuilder;
+use crate::credentials::{toml_error, Credentials};
+use crate::error::WeexError;
+use crate::rate_limiter::RateLimiter;
+use crate::risk::RiskConfig;
+use crate::risk_gate::RiskGate;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use zeroize::Zeroize;
+
+/// Environment variable naming the config file, overriding the default location
+pub const CONFIG_ENV: &str = "WEEX_CONFIG";
+
+/// Named profiles loaded from a TOML config file
+///
+/// ```toml
+/// default_profile = "paper"
+///
+/// [profiles.live]
+/// base_url = "https://api-contract.weex.com"
+/// credentials_file = "live-keys.toml"   # api_key, secret_key, passphrase
+/// rate_limit = { max_tokens = 10, refill_rate = 10 }
+/// risk = { max_leverage = 5, max_notional = 5000 }
+///
+/// [profiles.mock]
+/// base_url = "http://127.0.0.1:8080"
+/// api_key = "mock"
+/// secret_key = "mock"
+/// passphrase = "mock"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Config {
+    /// Profile used when none is named
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+    /// Directory of the file the config was loaded from; relative credential paths resolve against it
+    #[serde(skip)]
+    dir: Option<PathBuf>,
+}
+
+/// Endpoints, credentials and defaults for one environment
+///
+/// `Debug` redacts the inline credentials.
+#[derive(Clone, Default, Deserialize)]
+pub struct Profile {
+    pub base_url: Option<String>,
+    pub api_key: Option<String>,
+    pub secret_key: Option<String>,
+    pub passphrase: Option<String>,
+    /// TOML file holding `api_key`, `secret_key` and `passphrase` (see `Credentials::from_file`);
+    /// inline values take precedence
+    pub credentials_file: Option<PathBuf>,
+    pub timeout_secs: Option<u64>,
+    pub rate_limit: Option<RateLimitConfig>,
+    /// Risk limits for a pre-trade `RiskGate`; omitted fields keep `RiskConfig::default()`
+    pub risk: Option<RiskConfig>,
+}
+
+/// Token bucket settings for the client's `RateLimiter`
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct RateLimitConfig {
+    pub max_tokens: u64,
+    /// Tokens added per second
+    pub refill_rate: u64,
+}
+
+impl Config {
+    /// Parse a config from TOML text
+    pub fn from_toml(text: &str) -> Result<Config, WeexError> {
+        toml::from_str(text).map_err(|e| toml_error(Path::new("config"), text, &e))
+    }
+
+    /// Load a config file
+    pub fn load(path: impl AsRef<Path>) -> Result<Config, WeexError> {
+        let path = path.as_ref();
+        let mut text = std::fs::read_to_string(path)
+            .map_err(|e| WeexError::Config(format!("cannot read {}: {}", path.display(), e)))?;
+        let parsed = toml::from_str::<Config>(&text).map_err(|e| toml_error(path, &text, &e));
+        text.zeroize();
+        let mut config = parsed?;
+        config.dir = path.parent().map(Path::to_path_buf);
+        Ok(config)
+    }
+
+    /// Load the file named by `WEEX_CONFIG`, else `$XDG_CONFIG_HOME/weex/config.toml`
+    /// (`~/.config/weex/config.toml`)
+    pub fn load_default() -> Result<Config, WeexError> {
+        let path = Config::default_path()
+            .ok_or_else(|| WeexError::Config("no config path: set WEEX_CONFIG or HOME".into()))?;
+        Config::load(path)
+    }
+
+    pub fn default_path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os(CONFIG_ENV) {
+            return Some(PathBuf::from(path));
+        }
+        let config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(config_home.join("weex").join("config.toml"))
+    }
+
+    /// Look up a profile by name, or the default profile when `name` is `None`
+    pub fn profile(&self, name: Option<&str>) -> Result<&Profile, WeexError> {
+        let name = name
+            .or(self.default_profile.as_deref())
+            .ok_or_else(|| WeexError::Config("no profile named and no default_profile set".into()))?;
+        self.profiles
+            .get(name)
+            .ok_or_else(|| WeexError::Config(format!("unknown profile `{}`", name)))
+    }
+
+    /// Client builder configured from a profile, with its credentials file read in
+    pub fn builder(&self, name: Option<&str>) -> Result<WeexClientBuilder, WeexError> {
+        self.profile(name)?.builder(self.dir.as_deref())
+    }
+}
+
+impl Profile {
+    /// Client builder for this profile; a relative `credentials_file` resolves against `base_dir`
+    pub fn builder(&self, base_dir: Option<&Path>) -> Result<WeexClientBuilder, WeexError> {
+        let mut builder = WeexClientBuilder::new();
+        if let Some(path) = &self.credentials_file {
+            builder = builder.credentials(Credentials::from_file(resolve(path, base_dir))?);
+        }
+        if let Some(url) = &self.base_url {
+            builder = builder.base_url(url);
+        }
+        if let Some(key) = &self.api_key {
+            builder = builder.api_key(key);
+        }
+        if let Some(secret) = &self.secret_key {
+            builder = builder.secret_key(secret);
+        }
+        if let Some(phrase) = &self.passphrase {
+            builder = builder.passphrase(phrase);
+        }
+        if let Some(secs) = self.timeout_secs {
+            builder = builder.timeout(Duration::from_secs(secs));
+        }
+        if let Some(limit) = self.rate_limit {
+            builder = builder.rate_limiter(Arc::new(RateLimiter::new(limit.max_tokens, limit.refill_rate)));
+        }
+        if let Some(risk) = &self.risk {
+            builder = builder.risk_gate(Arc::new(RiskGate::new(risk.clone())));
+        }
+        Ok(builder)
+    }
+}
+
+/// Expand a leading `~/` and join relative paths onto `base_dir`
+fn resolve(path: &Path, base_dir: Option<&Path>) -> PathBuf {
+    if let (Ok(rest), Some(home)) = (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        return Path::new(&home).join(rest);
+    }
+    match base_dir {
+        Some(dir) if path.is_relative() => dir.join(path),
+        _ => path.to_path_buf(),
+    }
+}
+
+impl fmt::Debug for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let redacted = |v: &Option<String>| v.as_ref().map(|_| "<redacted>");
+        f.debug_struct("Profile")
+            .field("base_url", &self.base_url)
+            .field("api_key", &redacted(&self.api_key))
+            .field("secret_key", &redacted(&self.secret_key))
+            .field("passphrase", &redacted(&self.passphrase))
+            .field("credentials_file", &self.credentials_file)
+            .field("timeout_secs", &self.timeout_secs)
+            .field("rate_limit", &self.rate_limit)
+            .field("risk", &self.risk)
+            .finish()
+    }
+}
+
+impl Drop for Profile {
+    fn drop(&mut self) {
+        for secret in [&mut self.api_key, &mut self.secret_key, &mut self.passphrase].into_iter().flatten() {
+            secret.zeroize();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_profiles_with_credentials_file_and_risk() {
+        let dir = std::env::temp_dir().join(format!("weex_config_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("live-keys.toml"), "api_key = \"file-key\"\nsecret_key = \"s\"\npassphrase = \"p\"\n").unwrap();
+        std::fs::write(
+            dir.join("config.toml"),
+            r#"
+            default_profile = "mock"
+
+            [profiles.live]
+            base_url = "https://api-contract.weex.com"
+            credentials_file = "live-keys.toml"
+            passphrase = "inline"
+            rate_limit = { max_tokens = 5, refill_rate = 5 }
+            risk = { max_leverage = 3, max_price_deviation = "0.01" }
+
+            [profiles.mock]
+            base_url = "http://127.0.0.1:8080"
+            "#,
+        )
+        .unwrap();
+
+        let config = Config::load(dir.join("config.toml")).unwrap();
+        assert_eq!(config.profile(None).unwrap().base_url.as_deref(), Some("http://127.0.0.1:8080"));
+        assert!(matches!(config.profile(Some("paper")), Err(WeexError::Config(_))));
+
+        let risk = config.profile(Some("live")).unwrap().risk.clone().unwrap();
+        assert_eq!(risk.max_leverage, 3);
+        assert_eq!(risk.max_price_deviation, Decimal::new(1, 2));
+        assert_eq!(risk.max_positions, RiskConfig::default().max_positions);
+
+        let client = config.builder(Some("live")).unwrap().build().unwrap();
+        assert_eq!(client.credentials.as_ref().unwrap().api_key(), "file-key");
+        assert_eq!(client.credentials.as_ref().unwrap().passphrase(), "inline");
+        assert_eq!(client.risk_gate.unwrap().config().max_leverage, 3);
+        assert!(client.rate_limiter.is_some());
+
+        // The mock profile has no credentials, so it is a public client
+        assert!(!config.builder(None).unwrap().build().unwrap().has_credentials());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,116 @@
+use crate::error::WeexError;
+use crate::util::generate_signature;
+use serde::Deserialize;
+use std::fmt;
+use std::path::Path;
+use zeroize::Zeroize;
+
+/// API key, secret and passphrase for signed requests
+///
+/// The values are not readable outside the crate, never appear in `Debug` or
+/// `Display` output and are zeroized when dropped.
+#[derive(Clone, Deserialize)]
+pub struct Credentials {
+    api_key: String,
+    secret_key: String,
+    passphrase: String,
+}
+
+impl Credentials {
+    pub fn new(api_key: impl Into<String>, secret_key: impl Into<String>, passphrase: impl Into<String>) -> Self {
+        Credentials {
+            api_key: api_key.into(),
+            secret_key: secret_key.into(),
+            passphrase: passphrase.into(),
+        }
+    }
+
+    /// Read `WEEX_API_KEY`, `WEEX_SECRET_KEY` and `WEEX_PASSPHRASE`
+    pub fn from_env() -> Result<Self, WeexError> {
+        let var = |name: &str| std::env::var(name).map_err(|_| WeexError::Config(format!("{} not set", name)));
+        Ok(Credentials::new(var("WEEX_API_KEY")?, var("WEEX_SECRET_KEY")?, var("WEEX_PASSPHRASE")?))
+    }
+
+    /// Read a TOML file with `api_key`, `secret_key` and `passphrase`
+    ///
+    /// Parse errors report the line but not its contents.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, WeexError> {
+        let path = path.as_ref();
+        let mut text = std::fs::read_to_string(path)
+            .map_err(|e| WeexError::Config(format!("cannot read credentials {}: {}", path.display(), e)))?;
+        let parsed = toml::from_str(&text).map_err(|e| toml_error(path, &text, &e));
+        text.zeroize();
+        parsed
+    }
+
+    pub(crate) fn api_key(&self) -> &str {
+        &self.api_key
+    }
+
+    pub(crate) fn secret_key(&self) -> &str {
+        &self.secret_key
+    }
+
+    pub(crate) fn passphrase(&self) -> &str {
+        &self.passphrase
+    }
+
+    /// Base64 HMAC-SHA256 of `message` under the secret key
+    pub(crate) fn sign(&self, message: &str) -> String {
+        generate_signature(&self.secret_key, message)
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("api_key", &"<redacted>")
+            .field("secret_key", &"<redacted>")
+            .field("passphrase", &"<redacted>")
+            .finish()
+    }
+}
+
+impl fmt::Display for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<redacted credentials>")
+    }
+}
+
+impl Drop for Credentials {
+    fn drop(&mut self) {
+        self.api_key.zeroize();
+        self.secret_key.zeroize();
+        self.passphrase.zeroize();
+    }
+}
+
+/// Describe a TOML error by line and message only, without the source snippet
+/// `toml` normally quotes, which may hold a secret
+pub(crate) fn toml_error(path: &Path, text: &str, e: &toml::de::Error) -> WeexError {
+    let location = match e.span() {
+        Some(span) => format!(" (line {})", text[..span.start.min(text.len())].matches('\n').count() + 1),
+        None => String::new(),
+    };
+    WeexError::Config(format!("{}{}: {}", path.display(), location, e.message()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secrets_never_formatted() {
+        let creds = Credentials::new("key-123", "secret-456", "pass-789");
+        let shown = format!("{:?} {}", creds, creds);
+        assert!(!shown.contains("key-123") && !shown.contains("secret-456") && !shown.contains("pass-789"));
+        assert_eq!(creds.sign("msg"), generate_signature("secret-456", "msg"));
+
+        let path = std::env::temp_dir().join(format!("weex_creds_{}.toml", std::process::id()));
+        std::fs::write(&path, "api_key = \"key-123\"\nsecret_key = secret-456\n").unwrap();
+        let err = Credentials::from_file(&path).unwrap_err().to_string();
+        std::fs::remove_file(&path).unwrap();
+        assert!(err.contains("line 2"), "{}", err);
+        assert!(!err.contains("secret-456") && !err.contains("key-123"), "{}", err);
+    }
+}
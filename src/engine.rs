@@ -1,18 +1,28 @@
 use crate::traits::Exchange;
 use crate::strategy::{Strategy, Context};
-use crate::ws::handler::{MarketEvent, AccountEvent, EventHandler};
+use crate::ws::handler::{MarketEvent, AccountEvent};
 use crate::risk::{PositionSizer, RiskConfig};
-use crate::types::{Side, OrderType};
+use crate::risk_gate::{OrderIntent, RiskGate};
+use crate::kill_switch::{KillSwitch, TripReason};
+use crate::error::WeexError;
+use crate::recovery::RecoveryReport;
+use crate::ai_log::{AiLogEntry, AiLogQueue, Decision};
+use crate::util::{check_ack, order_id_from_response};
+use std::collections::HashMap;
+use crate::symbol::Symbol;
+use crate::types::{OrderType, Side};
+use rust_decimal::Decimal;
 use tokio::sync::broadcast;
+use std::collections::HashSet;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
 /// Trading signal generated by a strategy
 #[derive(Debug, Clone)]
 pub enum Signal {
-    Buy { symbol: String, size: String, price: Option<String> },
-    Sell { symbol: String, size: String, price: Option<String> },
-    Close { symbol: String },
+    Buy { symbol: Symbol, size: String, price: Option<String> },
+    Sell { symbol: Symbol, size: String, price: Option<String> },
+    Close { symbol: Symbol },
     Hold,
 }
 
@@ -28,26 +38,89 @@ pub struct EngineState {
 /// Strategy execution engine
 pub struct Engine<E: Exchange + Clone + Send + Sync + 'static> {
     exchange: E,
+    #[allow(dead_code)]
     position_sizer: PositionSizer,
+    risk_gate: Arc<RiskGate>,
+    kill_switch: Option<Arc<KillSwitch>>,
+    ai_log: Option<Arc<AiLogQueue>>,
+    /// Futures REST names of symbols traded or holding a position, flattened when the kill switch trips
+    symbols: Arc<RwLock<HashSet<String>>>,
+    equity: Arc<RwLock<EquityView>>,
     state: Arc<RwLock<EngineState>>,
 }
 
+/// Account equity as seen from the private stream, fed to the kill switch
+#[derive(Debug, Default)]
+struct EquityView {
+    /// USDT wallet balance (available + frozen)
+    wallet: Option<f64>,
+    /// Unrealized PnL of each open position
+    unrealized: HashMap<String, f64>,
+}
+
+impl EquityView {
+    fn equity(&self) -> Option<f64> {
+        self.wallet.map(|wallet| wallet + self.unrealized.values().sum::<f64>())
+    }
+}
+
+/// Name `halt()` passes to the futures REST endpoints for any accepted spelling
+fn rest_symbol(symbol: &str) -> Option<String> {
+    match symbol.parse::<Symbol>() {
+        Ok(symbol) => Some(symbol.to_futures().rest_name().to_string()),
+        Err(e) => {
+            tracing::warn!("Engine ignoring symbol: {}", e);
+            None
+        }
+    }
+}
+
 impl<E: Exchange + Clone + Send + Sync + 'static> Engine<E> {
     pub fn new(exchange: E, risk_config: RiskConfig) -> Self {
         Engine {
             exchange,
+            risk_gate: Arc::new(RiskGate::new(risk_config.clone())),
             position_sizer: PositionSizer::new(risk_config),
+            kill_switch: None,
+            ai_log: None,
+            symbols: Arc::new(RwLock::new(HashSet::new())),
+            equity: Arc::new(RwLock::new(EquityView::default())),
             state: Arc::new(RwLock::new(EngineState::default())),
         }
     }
 
+    /// Halt trading and flatten when the kill switch trips
+    pub fn with_kill_switch(mut self, kill_switch: Arc<KillSwitch>) -> Self {
+        self.kill_switch = Some(kill_switch);
+        self
+    }
+
+    /// Use a shared risk gate instead of the one built from the `RiskConfig`
+    pub fn with_risk_gate(mut self, gate: Arc<RiskGate>) -> Self {
+        self.risk_gate = gate;
+        self
+    }
+
+    /// Queue AI log entries for orders placed with a `Decision`
+    pub fn with_ai_log(mut self, queue: Arc<AiLogQueue>) -> Self {
+        self.ai_log = Some(queue);
+        self
+    }
+
+    /// Risk gate every signal is checked against
+    pub fn risk_gate(&self) -> Arc<RiskGate> {
+        self.risk_gate.clone()
+    }
+
     /// Run the engine with a strategy and event streams
     pub async fn run<S: Strategy + Send + Sync + 'static>(
         &self,
-        strategy: S,
+        _strategy: S,
         mut market_rx: broadcast::Receiver<MarketEvent>,
         mut account_rx: broadcast::Receiver<AccountEvent>,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.ensure_not_tripped()?;
+
         // Mark as running
         {
             let mut state = self.state.write().await;
@@ -58,23 +131,24 @@ impl<E: Exchange + Clone + Send + Sync + 'static> Engine<E> {
             tokio::select! {
                 // Handle market events
                 Ok(event) = market_rx.recv() => {
-                    match event {
-                        MarketEvent::Ticker { symbol, price } => {
-                            // Build context
-                            let context = Context {
-                                exchange_name: "weex".to_string(),
-                                symbol: symbol.clone(),
-                                price,
-                                balance: 0.0, // Would fetch from account
-                                position: 0.0,
-                            };
-                            
-                            // Get signal from strategy
-                            // Note: Strategy trait would need to be extended
-                            // For now, just log
-                            tracing::debug!("Ticker: {} @ {}", symbol, price);
+                    if let MarketEvent::Ticker { symbol, price } = event {
+                        // Build context
+                        let _context = Context {
+                            exchange_name: "weex".to_string(),
+                            symbol: symbol.clone(),
+                            price,
+                            balance: 0.0, // Would fetch from account
+                            position: 0.0,
+                        };
+
+                        if let Some(mark) = Decimal::from_f64_retain(price) {
+                            self.risk_gate.update_mark_price(&symbol, mark);
                         }
-                        _ => {}
+
+                        // Get signal from strategy
+                        // Note: Strategy trait would need to be extended
+                        // For now, just log
+                        tracing::debug!("Ticker: {} @ {}", symbol, price);
                     }
                 }
                 
@@ -83,6 +157,7 @@ impl<E: Exchange + Clone + Send + Sync + 'static> Engine<E> {
                     match event {
                         AccountEvent::OrderUpdate { order_id, status, filled_size } => {
                             tracing::info!("Order {} -> {} (filled: {})", order_id, status, filled_size);
+                            self.risk_gate.order_update(&order_id, &status);
                             
                             if status == "filled" {
                                 let mut state = self.state.write().await;
@@ -91,34 +166,355 @@ impl<E: Exchange + Clone + Send + Sync + 'static> Engine<E> {
                         }
                         AccountEvent::PositionUpdate { symbol, size, pnl, .. } => {
                             tracing::info!("Position {} size={} pnl={}", symbol, size, pnl);
+                            if let Some(size) = Decimal::from_f64_retain(size) {
+                                self.risk_gate.update_position(&symbol, size);
+                            }
+                            if let Some(symbol) = rest_symbol(&symbol) {
+                                let mut equity = self.equity.write().await;
+                                if size != 0.0 {
+                                    self.symbols.write().await.insert(symbol.clone());
+                                    equity.unrealized.insert(symbol, pnl);
+                                } else {
+                                    equity.unrealized.remove(&symbol);
+                                }
+                            }
+                            self.record_account_equity().await;
+                        }
+                        AccountEvent::BalanceUpdate { coin, available, frozen } => {
+                            if coin.eq_ignore_ascii_case("USDT") {
+                                self.equity.write().await.wallet = Some(available + frozen);
+                                self.record_account_equity().await;
+                            }
+                        }
+                        AccountEvent::MarginUpdate { symbol, margin_ratio } => {
+                            tracing::debug!("Margin ratio {} = {:.4}", symbol, margin_ratio);
                         }
-                        _ => {}
                     }
                 }
             }
+
+            if !self.state.read().await.running {
+                tracing::warn!("Engine halted");
+                return Ok(());
+            }
+        }
+    }
+
+    /// Seed engine totals, tracked symbols and the risk gate from a recovered session
+    pub async fn resume(&self, report: &RecoveryReport) {
+        {
+            let mut state = self.state.write().await;
+            state.total_trades = report.session.total_trades;
+            state.winning_trades = report.session.winning_trades;
+            state.total_pnl = report.session.total_pnl;
+        }
+
+        let mut symbols = self.symbols.write().await;
+        for pos in &report.session.open_positions {
+            let size = Decimal::from_f64_retain(pos.size).unwrap_or_default();
+            let signed = if pos.side.eq_ignore_ascii_case("short") { -size } else { size };
+            self.risk_gate.update_position(&pos.symbol, signed);
+            symbols.extend(rest_symbol(&pos.symbol));
+        }
+
+        let mut open_orders: HashMap<String, usize> = HashMap::new();
+        for order in &report.open_orders {
+            if let Some(symbol) = rest_symbol(&order.symbol) {
+                *open_orders.entry(symbol).or_default() += 1;
+            }
+        }
+        for (symbol, count) in open_orders {
+            self.risk_gate.update_open_orders(&symbol, count);
+            symbols.insert(symbol);
+        }
+        tracing::info!("Engine resumed with {} open positions", report.session.open_positions.len());
+    }
+
+    /// Feed wallet balance plus unrealized PnL from the account stream to the kill switch
+    async fn record_account_equity(&self) {
+        let equity = self.equity.read().await.equity();
+        if let Some(equity) = equity.and_then(Decimal::from_f64_retain) {
+            self.record_equity(equity).await;
+        }
+    }
+
+    /// Feed account equity to the kill switch, halting the engine if it trips
+    pub async fn record_equity(&self, equity: Decimal) {
+        if let Some(ks) = &self.kill_switch {
+            if let Some(reason) = ks.record_equity(equity) {
+                self.halt(reason).await;
+            }
+        }
+    }
+
+    /// Feed a closed trade's realized PnL to the kill switch, halting the engine if it trips
+    pub async fn record_trade_result(&self, pnl: Decimal) {
+        if let Some(ks) = &self.kill_switch {
+            if let Some(reason) = ks.record_trade_result(pnl) {
+                self.halt(reason).await;
+            }
+        }
+    }
+
+    /// Stop the engine, cancel all orders and, if configured, close all positions
+    pub async fn halt(&self, reason: TripReason) {
+        tracing::error!("Halting engine: {}", reason);
+        if let Some(ks) = &self.kill_switch {
+            ks.trip(reason);
+        }
+        self.state.write().await.running = false;
+
+        let close_positions = self.risk_gate.config().close_positions_on_trip;
+        let symbols: Vec<String> = self.symbols.read().await.iter().cloned().collect();
+        for symbol in &symbols {
+            if let Err(e) = self.exchange.cancel_all_orders(symbol).await {
+                tracing::error!("Failed to cancel orders on {}: {}", symbol, e);
+            }
+            if close_positions {
+                if let Err(e) = self.exchange.close_all_positions(symbol).await {
+                    tracing::error!("Failed to close positions on {}: {}", symbol, e);
+                }
+            }
+        }
+    }
+
+    fn ensure_not_tripped(&self) -> Result<(), WeexError> {
+        match self.kill_switch.as_ref().and_then(|ks| ks.trip_reason()) {
+            Some(reason) => Err(WeexError::KillSwitchTripped(reason.to_string())),
+            None => Ok(()),
         }
     }
 
     /// Execute a trading signal
+    ///
+    /// Buy and sell signals are checked against the risk gate before any order is sent.
     pub async fn execute_signal(&self, signal: Signal) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.execute(signal, None).await
+    }
+
+    /// Execute a trading signal and record the strategy's reasoning
+    ///
+    /// When an AI log queue is attached, a placed buy or sell order queues Decision
+    /// Making, Risk Assessment and Execution entries linked to the new order ID once
+    /// the exchange acknowledges it; an order the risk gate refuses queues Decision
+    /// Making and a Risk Assessment recording the rejection.
+    pub async fn execute_signal_with_decision(
+        &self,
+        signal: Signal,
+        decision: Decision,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.execute(signal, Some(&decision)).await
+    }
+
+    async fn execute(
+        &self,
+        signal: Signal,
+        decision: Option<&Decision>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if matches!(signal, Signal::Buy { .. } | Signal::Sell { .. }) {
+            self.ensure_not_tripped()?;
+        }
         match signal {
             Signal::Buy { symbol, size, price } => {
                 tracing::info!("Executing BUY {} size={}", symbol, size);
-                // Would call exchange.place_futures_order(...)
+                self.place_checked(&symbol, Side::Buy, &size, price.as_deref(), decision).await?;
             }
             Signal::Sell { symbol, size, price } => {
                 tracing::info!("Executing SELL {} size={}", symbol, size);
+                self.place_checked(&symbol, Side::Sell, &size, price.as_deref(), decision).await?;
             }
             Signal::Close { symbol } => {
                 tracing::info!("Closing position {}", symbol);
+                self.exchange.close_all_positions(&symbol).await?;
             }
             Signal::Hold => {}
         }
         Ok(())
     }
 
+    async fn place_checked(
+        &self,
+        symbol: &Symbol,
+        side: Side,
+        size: &str,
+        price: Option<&str>,
+        decision: Option<&Decision>,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let order_type = if price.is_some() { OrderType::Limit } else { OrderType::Market };
+        let intent = OrderIntent::from_strs(symbol, side, order_type, size, price)?;
+        let ai_log = self.ai_log.as_deref().zip(decision);
+        if let Err(rejection) = self.risk_gate.check_order(&intent) {
+            tracing::warn!("Risk gate rejected {} {} size={}: {}", side, symbol, size, rejection);
+            if let Some((queue, decision)) = ai_log {
+                let mark = self.risk_gate.mark_price(&intent.symbol);
+                let trail = decision.rejection_trail(&intent, mark, &rejection);
+                self.queue_trail(queue, &intent, trail);
+            }
+            return Err(Box::new(WeexError::from(rejection)));
+        }
+        self.symbols.write().await.insert(symbol.rest_name().to_string());
+        let response = self.exchange.place_order(symbol, size, side, order_type, price).await?;
+        check_ack(&response)?;
+        match order_id_from_response(&response) {
+            Some(order_id) => self.risk_gate.reserve(&order_id.to_string(), &intent),
+            None => tracing::warn!("No order ID in place-order response, risk gate cannot reserve it: {}", response),
+        }
+        if let Some((queue, decision)) = ai_log {
+            self.log_decision(queue, decision, &intent, &response);
+        }
+        Ok(response)
+    }
+
+    /// Queue the AI log trail for a placed order; failures are logged, never returned
+    fn log_decision(&self, queue: &AiLogQueue, decision: &Decision, intent: &OrderIntent, response: &str) {
+        let order_id = order_id_from_response(response);
+        if order_id.is_none() {
+            tracing::warn!("No order ID in place-order response, AI logs will be unlinked: {}", response);
+        }
+        let mark = self.risk_gate.mark_price(&intent.symbol);
+        self.queue_trail(queue, intent, decision.trail(intent, mark, order_id, response));
+    }
+
+    fn queue_trail(&self, queue: &AiLogQueue, intent: &OrderIntent, trail: Result<Vec<AiLogEntry>, WeexError>) {
+        if let Err(e) = trail.and_then(|entries| queue.enqueue_all(entries)) {
+            tracing::error!("Failed to queue AI logs for {} order: {}", intent.symbol, e);
+        }
+    }
+
     /// Get current engine state
     pub async fn get_state(&self) -> EngineState {
         self.state.read().await.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockExchange;
+
+    #[tokio::test]
+    async fn test_kill_switch_blocks_signals() {
+        let config = RiskConfig::default();
+        let ks = Arc::new(KillSwitch::new(config.clone()));
+        let engine = Engine::new(MockExchange::new(), config).with_kill_switch(ks.clone());
+        engine.risk_gate().update_mark_price("cmt_btcusdt", Decimal::from(50000));
+
+        let buy = Signal::Buy {
+            symbol: "cmt_btcusdt".parse().unwrap(),
+            size: "0.01".to_string(),
+            price: None,
+        };
+        assert!(engine.execute_signal(buy.clone()).await.is_ok());
+
+        engine.record_equity(Decimal::from(10000)).await;
+        engine.record_equity(Decimal::from(7000)).await;
+        assert!(ks.is_tripped());
+        assert!(!engine.get_state().await.running);
+        assert!(engine.execute_signal(buy.clone()).await.is_err());
+
+        ks.reset();
+        assert!(engine.execute_signal(buy).await.is_ok());
+    }
+
+    struct Idle;
+
+    #[async_trait::async_trait]
+    impl Strategy for Idle {
+        async fn on_tick(&mut self, _ticker: crate::spot::market::Ticker, _ctx: &mut Context) {}
+    }
+
+    #[tokio::test]
+    async fn test_ws_ticker_marks_rest_symbol() {
+        let engine = Engine::new(MockExchange::new(), RiskConfig::default());
+        let (market_tx, market_rx) = broadcast::channel(8);
+        let (_account_tx, account_rx) = broadcast::channel(8);
+        market_tx.send(MarketEvent::Ticker { symbol: "BTCUSDT".to_string(), price: 50000.0 }).unwrap();
+
+        let run = engine.run(Idle, market_rx, account_rx);
+        let _ = tokio::time::timeout(std::time::Duration::from_millis(50), run).await;
+
+        let buy = Signal::Buy {
+            symbol: "cmt_btcusdt".parse().unwrap(),
+            size: "0.01".to_string(),
+            price: None,
+        };
+        engine.execute_signal(buy).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_trip_flattens_ws_position_by_rest_name() {
+        let config = RiskConfig { close_positions_on_trip: true, ..RiskConfig::default() };
+        let ks = Arc::new(KillSwitch::new(config.clone()));
+        let exchange = MockExchange::new();
+        let engine = Engine::new(exchange.clone(), config).with_kill_switch(ks.clone());
+        let (_market_tx, market_rx) = broadcast::channel(8);
+        let (account_tx, account_rx) = broadcast::channel(8);
+
+        // The private stream names the instrument `BTCUSDT`; the loss is still unrealized
+        account_tx.send(AccountEvent::BalanceUpdate { coin: "USDT".into(), available: 10000.0, frozen: 0.0 }).unwrap();
+        account_tx
+            .send(AccountEvent::PositionUpdate { symbol: "BTCUSDT".into(), size: 0.1, entry_price: 50000.0, pnl: -3000.0 })
+            .unwrap();
+
+        let run = engine.run(Idle, market_rx, account_rx);
+        tokio::time::timeout(std::time::Duration::from_secs(1), run).await.unwrap().unwrap();
+
+        assert!(ks.is_tripped());
+        let calls = exchange.calls.lock().unwrap().clone();
+        assert_eq!(
+            calls,
+            [
+                ("cancel_all_orders".to_string(), "cmt_btcusdt".to_string()),
+                ("close_all_positions".to_string(), "cmt_btcusdt".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_decision_trail_linked_to_order() {
+        let queue = Arc::new(AiLogQueue::new());
+        let engine = Engine::new(MockExchange::new(), RiskConfig::default()).with_ai_log(queue.clone());
+        engine.risk_gate().update_mark_price("cmt_btcusdt", Decimal::from(50000));
+
+        let buy = Signal::Buy {
+            symbol: "cmt_btcusdt".parse().unwrap(),
+            size: "0.01".to_string(),
+            price: None,
+        };
+        engine.execute_signal(buy.clone()).await.unwrap();
+        assert!(queue.is_empty());
+
+        let decision = Decision::new("gpt-5.2", "RSI oversold").with_input(serde_json::json!({ "rsi": 28 }));
+        engine.execute_signal_with_decision(buy, decision).await.unwrap();
+
+        let entries = queue.pending();
+        let stages: Vec<String> = entries.iter().map(|e| e.stage.to_string()).collect();
+        assert_eq!(stages, ["Decision Making", "Risk Assessment", "Execution"]);
+        assert!(entries[0].order_id.is_some());
+        assert!(entries.iter().all(|e| e.order_id == entries[0].order_id && e.model == "gpt-5.2"));
+        assert_eq!(entries[0].input["rsi"], 28);
+        assert_eq!(entries[1].input["markPrice"], "50000");
+    }
+
+    #[tokio::test]
+    async fn test_risk_rejection_logged_as_assessment() {
+        let queue = Arc::new(AiLogQueue::new());
+        let config = RiskConfig { max_notional: Decimal::from(100), ..RiskConfig::default() };
+        let engine = Engine::new(MockExchange::new(), config).with_ai_log(queue.clone());
+        engine.risk_gate().update_mark_price("cmt_btcusdt", Decimal::from(50000));
+
+        let buy = Signal::Buy {
+            symbol: "cmt_btcusdt".parse().unwrap(),
+            size: "0.01".to_string(),
+            price: None,
+        };
+        let decision = Decision::new("gpt-5.2", "RSI oversold");
+        assert!(engine.execute_signal_with_decision(buy, decision).await.is_err());
+
+        let entries = queue.pending();
+        let stages: Vec<String> = entries.iter().map(|e| e.stage.to_string()).collect();
+        assert_eq!(stages, ["Decision Making", "Risk Assessment"]);
+        assert_eq!(entries[1].output["approved"], false);
+        assert!(entries.iter().all(|e| e.order_id.is_none()));
+    }
+}
@@ -0,0 +1,344 @@
+use crate::client::WeexClient;
+use crate::error::WeexError;
+use crate::recovery::parse_positions;
+use crate::state::{PositionRecord, StateManager};
+use crate::util::{in_range, json_decimal, json_list, json_str};
+use chrono::{DateTime, SecondsFormat, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+/// Point-in-time account value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EquitySnapshot {
+    pub timestamp: DateTime<Utc>,
+    pub wallet_balance: f64,
+    pub unrealized_pnl: f64,
+    pub margin_used: f64,
+    /// Signed notional per symbol (negative for shorts)
+    #[serde(default)]
+    pub exposures: BTreeMap<String, f64>,
+}
+
+impl EquitySnapshot {
+    /// Build a snapshot from wallet figures and open positions
+    ///
+    /// Exposure is the position's current notional, derived from entry price and
+    /// unrealized PnL, signed by direction.
+    pub fn from_positions(wallet_balance: f64, margin_used: f64, positions: &[PositionRecord]) -> Self {
+        let mut exposures = BTreeMap::new();
+        let mut unrealized_pnl = 0.0;
+        for pos in positions {
+            let cost = pos.size * pos.entry_price;
+            let exposure = if pos.side.eq_ignore_ascii_case("short") {
+                -(cost - pos.unrealized_pnl)
+            } else {
+                cost + pos.unrealized_pnl
+            };
+            *exposures.entry(pos.symbol.clone()).or_insert(0.0) += exposure;
+            unrealized_pnl += pos.unrealized_pnl;
+        }
+        EquitySnapshot {
+            timestamp: Utc::now(),
+            wallet_balance,
+            unrealized_pnl,
+            margin_used,
+            exposures,
+        }
+    }
+
+    /// Fetch USDT assets and open positions and snapshot them
+    ///
+    /// `margin_used` is the margin held by open positions, not the account's frozen balance.
+    pub async fn capture(client: &WeexClient) -> Result<Self, WeexError> {
+        let assets = json_list(&client.get_assets().await?)?;
+        let usdt = assets
+            .iter()
+            .find(|a| json_str(a, &["coinName", "coin", "marginCoin"]).is_some_and(|c| c.eq_ignore_ascii_case("USDT")))
+            .ok_or_else(|| WeexError::Unknown("No USDT asset in account".to_string()))?;
+        let field = |keys: &[&str]| json_decimal(usdt, keys).and_then(|d| d.to_f64()).unwrap_or_default();
+
+        let raw_positions = client.get_all_positions().await?;
+        let positions = parse_positions(&raw_positions)?;
+        let margin_used = position_margin(&raw_positions)?;
+        let mut snapshot = Self::from_positions(field(&["available"]) + field(&["frozen"]), margin_used, &positions);
+        if let Some(equity) = json_decimal(usdt, &["equity"]).and_then(|d| d.to_f64()) {
+            // Prefer the exchange's own equity figure when it is reported
+            snapshot.wallet_balance = equity - snapshot.unrealized_pnl;
+        }
+        Ok(snapshot)
+    }
+
+    /// Sum snapshots of several accounts; exposures add up per symbol
+    ///
+    /// The result is stamped with the latest input timestamp.
+    pub fn combine<'a>(snapshots: impl IntoIterator<Item = &'a EquitySnapshot>) -> Self {
+        let mut total = EquitySnapshot::from_positions(0.0, 0.0, &[]);
+        let mut latest = None;
+        for s in snapshots {
+            total.wallet_balance += s.wallet_balance;
+            total.unrealized_pnl += s.unrealized_pnl;
+            total.margin_used += s.margin_used;
+            for (symbol, exposure) in &s.exposures {
+                *total.exposures.entry(symbol.clone()).or_insert(0.0) += exposure;
+            }
+            latest = latest.max(Some(s.timestamp));
+        }
+        if let Some(ts) = latest {
+            total.timestamp = ts;
+        }
+        total
+    }
+
+    /// Formula: equity = wallet_balance + unrealized_pnl
+    pub fn equity(&self) -> f64 {
+        self.wallet_balance + self.unrealized_pnl
+    }
+
+    /// Sum of absolute exposures
+    pub fn gross_exposure(&self) -> f64 {
+        self.exposures.values().map(|e| e.abs()).sum()
+    }
+
+    /// Sum of signed exposures
+    pub fn net_exposure(&self) -> f64 {
+        self.exposures.values().sum()
+    }
+}
+
+/// Margin held by the positions in an `allPosition` response
+fn position_margin(raw: &str) -> Result<f64, WeexError> {
+    Ok(json_list(raw)?
+        .iter()
+        .filter_map(|p| json_decimal(p, &["margin", "im", "marginSize", "fixedMargin"]))
+        .filter_map(|m| m.to_f64())
+        .sum())
+}
+
+/// Background task appending an equity snapshot to the state journal on a fixed interval
+pub struct EquityRecorder;
+
+impl EquityRecorder {
+    /// Capture and record a snapshot every `every`, starting immediately
+    ///
+    /// Failed captures are logged and retried on the next tick.
+    pub fn spawn(client: WeexClient, state: Arc<StateManager>, every: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(every);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                match EquitySnapshot::capture(&client).await {
+                    Ok(snapshot) => {
+                        if let Err(e) = state.record_equity(&snapshot) {
+                            tracing::error!("Failed to record equity snapshot: {}", e);
+                        }
+                    }
+                    Err(e) => tracing::warn!("Equity capture failed: {}", e),
+                }
+            }
+        })
+    }
+}
+
+/// Equity snapshots ordered by time
+#[derive(Debug, Clone, Default)]
+pub struct EquityCurve {
+    snapshots: Vec<EquitySnapshot>,
+}
+
+impl EquityCurve {
+    pub fn new(mut snapshots: Vec<EquitySnapshot>) -> Self {
+        snapshots.sort_by_key(|s| s.timestamp);
+        EquityCurve { snapshots }
+    }
+
+    pub fn snapshots(&self) -> &[EquitySnapshot] {
+        &self.snapshots
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Snapshots within `[from, to]`; either bound may be open
+    pub fn between(&self, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> EquityCurve {
+        EquityCurve {
+            snapshots: self
+                .snapshots
+                .iter()
+                .filter(|s| in_range(s.timestamp, from, to))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// `(timestamp, equity)` series
+    pub fn points(&self) -> Vec<(DateTime<Utc>, f64)> {
+        self.snapshots.iter().map(|s| (s.timestamp, s.equity())).collect()
+    }
+
+    /// Simple returns between consecutive snapshots
+    pub fn returns(&self) -> Vec<f64> {
+        self.snapshots
+            .windows(2)
+            .map(|w| {
+                let prev = w[0].equity();
+                if prev == 0.0 { 0.0 } else { w[1].equity() / prev - 1.0 }
+            })
+            .collect()
+    }
+
+    /// Largest peak-to-trough decline as a fraction of the peak
+    pub fn max_drawdown(&self) -> f64 {
+        let mut peak = f64::MIN;
+        let mut max_dd: f64 = 0.0;
+        for s in &self.snapshots {
+            let equity = s.equity();
+            peak = peak.max(equity);
+            if peak > 0.0 {
+                max_dd = max_dd.max((peak - equity) / peak);
+            }
+        }
+        max_dd
+    }
+
+    /// Write the curve as CSV
+    ///
+    /// Columns: `timestamp,equity,wallet_balance,unrealized_pnl,margin_used` followed
+    /// by one `exposure_<symbol>` column per symbol seen in the curve, sorted by name.
+    pub fn write_csv<W: Write>(&self, writer: W) -> Result<(), std::io::Error> {
+        let symbols: BTreeSet<&String> = self.snapshots.iter().flat_map(|s| s.exposures.keys()).collect();
+        let mut csv = csv::Writer::from_writer(writer);
+
+        let mut header = vec![
+            "timestamp".to_string(),
+            "equity".to_string(),
+            "wallet_balance".to_string(),
+            "unrealized_pnl".to_string(),
+            "margin_used".to_string(),
+        ];
+        header.extend(symbols.iter().map(|s| format!("exposure_{}", s)));
+        csv.write_record(&header)?;
+
+        for s in &self.snapshots {
+            let mut row = vec![
+                s.timestamp.to_rfc3339_opts(SecondsFormat::Millis, true),
+                s.equity().to_string(),
+                s.wallet_balance.to_string(),
+                s.unrealized_pnl.to_string(),
+                s.margin_used.to_string(),
+            ];
+            row.extend(symbols.iter().map(|sym| s.exposures.get(*sym).copied().unwrap_or(0.0).to_string()));
+            csv.write_record(&row)?;
+        }
+        csv.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::http_stub_responses;
+
+    fn snapshot(minute: i64, wallet: f64, exposures: &[(&str, f64)]) -> EquitySnapshot {
+        EquitySnapshot {
+            timestamp: DateTime::<Utc>::default() + chrono::Duration::minutes(minute),
+            wallet_balance: wallet,
+            unrealized_pnl: 0.0,
+            margin_used: 0.0,
+            exposures: exposures.iter().map(|(s, e)| (s.to_string(), *e)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_exposure_from_positions() {
+        let positions = vec![
+            PositionRecord { symbol: "cmt_btcusdt".into(), side: "long".into(), size: 0.1, entry_price: 50000.0, unrealized_pnl: 100.0 },
+            PositionRecord { symbol: "cmt_ethusdt".into(), side: "short".into(), size: 1.0, entry_price: 3000.0, unrealized_pnl: 50.0 },
+        ];
+        let s = EquitySnapshot::from_positions(10000.0, 800.0, &positions);
+        assert_eq!(s.exposures["cmt_btcusdt"], 5100.0);
+        assert_eq!(s.exposures["cmt_ethusdt"], -2950.0);
+        assert_eq!(s.equity(), 10150.0);
+        assert_eq!(s.gross_exposure(), 8050.0);
+
+        let other = snapshot(5, 1000.0, &[("cmt_btcusdt", -100.0), ("cmt_solusdt", 40.0)]);
+        let total = EquitySnapshot::combine([&s, &other]);
+        assert_eq!(total.wallet_balance, 11000.0);
+        assert_eq!(total.exposures["cmt_btcusdt"], 5000.0);
+        assert_eq!(total.exposures.len(), 3);
+        assert_eq!(total.timestamp, s.timestamp);
+    }
+
+    #[test]
+    fn test_curve_stats_and_csv() {
+        let curve = EquityCurve::new(vec![
+            snapshot(2, 900.0, &[("cmt_ethusdt", -50.0)]),
+            snapshot(0, 1000.0, &[("cmt_btcusdt", 100.0)]),
+            snapshot(1, 1100.0, &[]),
+        ]);
+        assert_eq!(curve.returns().len(), 2);
+        assert!((curve.max_drawdown() - 200.0 / 1100.0).abs() < 1e-12);
+
+        let mut out = Vec::new();
+        curve.write_csv(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines[0],
+            "timestamp,equity,wallet_balance,unrealized_pnl,margin_used,exposure_cmt_btcusdt,exposure_cmt_ethusdt"
+        );
+        assert_eq!(lines[1], "1970-01-01T00:00:00.000Z,1000,1000,0,0,100,0");
+        assert_eq!(lines.len(), 4);
+    }
+
+    #[test]
+    fn test_state_manager_round_trip() {
+        let dir = std::env::temp_dir().join(format!("weex_equity_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let manager = StateManager::new(&dir.to_string_lossy());
+        manager.record_equity(&snapshot(1, 1000.0, &[])).unwrap();
+        manager.record_equity(&snapshot(0, 990.0, &[])).unwrap();
+
+        let curve = manager.load_equity_curve().unwrap();
+        assert_eq!(curve.points()[0].1, 990.0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_recorder_uses_position_margin() {
+        let dir = std::env::temp_dir().join(format!("weex_equity_recorder_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let state = Arc::new(StateManager::new(&dir.to_string_lossy()));
+        let (url, stub) = http_stub_responses(vec![
+            r#"[{"coinName":"USDT","available":"900","frozen":"300"}]"#.to_string(),
+            r#"[{"symbol":"cmt_btcusdt","side":"LONG","size":"0.1","openPriceAvg":"50000","margin":"250"},
+                {"symbol":"cmt_ethusdt","side":"SHORT","size":"1","openPriceAvg":"3000","margin":"150"}]"#.to_string(),
+        ])
+        .await;
+        let client = WeexClient::builder().base_url(&url).api_key("k").secret_key("s").passphrase("p").build().unwrap();
+
+        let recorder = EquityRecorder::spawn(client, state.clone(), Duration::from_secs(3600));
+        stub.await.unwrap();
+        let mut curve = state.load_equity_curve().unwrap();
+        for _ in 0..50 {
+            if !curve.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            curve = state.load_equity_curve().unwrap();
+        }
+        recorder.abort();
+
+        let snapshot = &curve.snapshots()[0];
+        assert_eq!(snapshot.margin_used, 400.0);
+        assert_eq!(snapshot.wallet_balance, 1200.0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
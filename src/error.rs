@@ -11,9 +11,33 @@ pub enum WeexError {
     #[error("Serialization Error: {0}")]
     Json(#[from] serde_json::Error),
 
+    #[error("I/O Error: {0}")]
+    Io(#[from] std::io::Error),
+
     #[error("Signing Error: {0}")]
     Signing(String),
 
+    #[error("Invalid Symbol: {0}")]
+    InvalidSymbol(String),
+
+    #[error("Risk Rejected: {0}")]
+    RiskRejected(#[from] crate::risk_gate::RiskRejection),
+
+    #[error("Kill Switch Tripped: {0}")]
+    KillSwitchTripped(String),
+
+    #[error("Invalid Order: {0}")]
+    InvalidOrder(String),
+
+    #[error("Invalid AI Log: {0}")]
+    InvalidAiLog(String),
+
+    #[error("Credentials required for {0}")]
+    MissingCredentials(String),
+
+    #[error("Config Error: {0}")]
+    Config(String),
+
     #[error("Unknown Error: {0}")]
     Unknown(String),
 }
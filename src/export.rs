@@ -0,0 +1,258 @@
+use crate::error::WeexError;
+use crate::future::account::FundingPayment;
+use crate::future::order::{Fill, OpenOrder};
+use crate::state::TradeRecord;
+use crate::symbol::Symbol;
+use crate::util::in_range;
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::io::Write;
+
+/// Record that can be exported as a CSV row with a fixed column schema
+pub trait CsvRecord {
+    /// Column names, in row order
+    const COLUMNS: &'static [&'static str];
+
+    fn symbol(&self) -> &str;
+    fn timestamp(&self) -> DateTime<Utc>;
+    fn row(&self) -> Vec<String>;
+}
+
+/// Date-range and symbol filter for exports
+///
+/// Symbols match on base and quote, so `BTCUSDT` selects `cmt_btcusdt` records too.
+#[derive(Debug, Clone, Default)]
+pub struct ExportFilter {
+    pub symbol: Option<Symbol>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl ExportFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn symbol(mut self, symbol: Symbol) -> Self {
+        self.symbol = Some(symbol);
+        self
+    }
+
+    /// Inclusive lower bound
+    pub fn from(mut self, from: DateTime<Utc>) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    /// Inclusive upper bound
+    pub fn to(mut self, to: DateTime<Utc>) -> Self {
+        self.to = Some(to);
+        self
+    }
+
+    pub fn matches<R: CsvRecord>(&self, record: &R) -> bool {
+        if !in_range(record.timestamp(), self.from, self.to) {
+            return false;
+        }
+        match &self.symbol {
+            Some(want) => want.matches(record.symbol()),
+            None => true,
+        }
+    }
+}
+
+/// Write matching records as CSV with a header row; returns the number of rows written
+pub fn write_csv<R: CsvRecord, W: Write>(records: &[R], filter: &ExportFilter, writer: W) -> Result<usize, std::io::Error> {
+    let mut csv = csv::Writer::from_writer(writer);
+    csv.write_record(R::COLUMNS)?;
+    let mut count = 0;
+    for record in records.iter().filter(|r| filter.matches(*r)) {
+        csv.write_record(record.row())?;
+        count += 1;
+    }
+    csv.flush()?;
+    Ok(count)
+}
+
+/// Refuse an export from a single-page endpoint that may not reach back to `from`
+///
+/// A full page is complete only if its oldest record is at or before `from`;
+/// otherwise older records may exist that the endpoint cannot return.
+pub fn check_page_covers<R: CsvRecord>(records: &[R], page_full: bool, from: Option<DateTime<Utc>>) -> Result<(), WeexError> {
+    if !page_full {
+        return Ok(());
+    }
+    let oldest = records.iter().map(|r| r.timestamp()).min();
+    match (oldest, from) {
+        (Some(oldest), Some(from)) if oldest <= from => Ok(()),
+        (Some(oldest), _) => Err(WeexError::Unknown(format!(
+            "Only records since {} are available from this endpoint; pass --from {} or later",
+            iso(oldest),
+            oldest.format("%Y-%m-%d")
+        ))),
+        (None, _) => Err(WeexError::Unknown("Endpoint returned a full page with no matching records; narrow the range".to_string())),
+    }
+}
+
+fn iso(ts: DateTime<Utc>) -> String {
+    ts.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+impl CsvRecord for TradeRecord {
+    const COLUMNS: &'static [&'static str] = &["timestamp", "id", "symbol", "side", "size", "price", "pnl", "status"];
+
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            iso(self.timestamp),
+            self.id.clone(),
+            self.symbol.clone(),
+            self.side.clone(),
+            self.size.to_string(),
+            self.price.to_string(),
+            self.pnl.map(|p| p.to_string()).unwrap_or_default(),
+            self.status.clone(),
+        ]
+    }
+}
+
+impl CsvRecord for Fill {
+    const COLUMNS: &'static [&'static str] = &[
+        "timestamp", "trade_id", "order_id", "symbol", "side", "price", "size", "fee", "realized_pnl", "liquidation",
+        "position_side",
+    ];
+
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            iso(self.timestamp),
+            self.trade_id.clone(),
+            self.order_id.clone(),
+            self.symbol.clone(),
+            self.side.to_string(),
+            self.price.to_string(),
+            self.size.to_string(),
+            self.fee.to_string(),
+            self.realized_pnl.to_string(),
+            self.liquidation.to_string(),
+            self.position_side.map(|s| s.to_string()).unwrap_or_default(),
+        ]
+    }
+}
+
+impl CsvRecord for OpenOrder {
+    const COLUMNS: &'static [&'static str] = &[
+        "created_at", "order_id", "client_oid", "symbol", "kind", "price", "size", "filled_size", "status",
+    ];
+
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            iso(self.created_at),
+            self.order_id.clone(),
+            self.client_oid.clone().unwrap_or_default(),
+            self.symbol.clone(),
+            self.kind.clone(),
+            self.price.to_string(),
+            self.size.to_string(),
+            self.filled_size.to_string(),
+            self.status.clone(),
+        ]
+    }
+}
+
+impl CsvRecord for FundingPayment {
+    const COLUMNS: &'static [&'static str] = &["timestamp", "symbol", "amount"];
+
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![iso(self.timestamp), self.symbol.clone(), self.amount.to_string()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    fn payment(symbol: &str, minute: i64) -> FundingPayment {
+        FundingPayment {
+            symbol: symbol.to_string(),
+            amount: Decimal::new(-125, 2),
+            timestamp: DateTime::<Utc>::default() + chrono::Duration::minutes(minute),
+        }
+    }
+
+    #[test]
+    fn test_filter_by_symbol_and_range() {
+        let records = vec![payment("cmt_btcusdt", 0), payment("cmt_ethusdt", 1), payment("cmt_btcusdt", 2)];
+        let filter = ExportFilter::new()
+            .symbol("BTC/USDT".parse().unwrap())
+            .from(DateTime::<Utc>::default() + chrono::Duration::minutes(1));
+
+        let mut out = Vec::new();
+        assert_eq!(write_csv(&records, &filter, &mut out).unwrap(), 1);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "timestamp,symbol,amount\n1970-01-01T00:02:00.000Z,cmt_btcusdt,-1.25\n"
+        );
+    }
+
+    #[test]
+    fn test_trade_columns_stable_with_missing_pnl() {
+        let trade = TradeRecord {
+            id: "t,1".to_string(),
+            timestamp: DateTime::<Utc>::default(),
+            symbol: "cmt_btcusdt".to_string(),
+            side: "buy".to_string(),
+            size: 0.5,
+            price: 50000.0,
+            pnl: None,
+            status: "filled".to_string(),
+        };
+        assert_eq!(trade.row().len(), TradeRecord::COLUMNS.len());
+
+        let mut out = Vec::new();
+        write_csv(&[trade], &ExportFilter::new(), &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text.lines().nth(1), Some(r#"1970-01-01T00:00:00.000Z,"t,1",cmt_btcusdt,buy,0.5,50000,,filled"#));
+    }
+
+    #[test]
+    fn test_full_page_must_reach_from() {
+        let page = vec![payment("cmt_btcusdt", 5), payment("cmt_btcusdt", 10)];
+        let at = |minute| Some(DateTime::<Utc>::default() + chrono::Duration::minutes(minute));
+
+        assert!(check_page_covers(&page, false, at(0)).is_ok());
+        assert!(check_page_covers(&page, true, at(5)).is_ok());
+        assert!(check_page_covers(&page, true, at(0)).is_err());
+        assert!(check_page_covers(&page, true, None).is_err());
+    }
+}
@@ -0,0 +1,69 @@
+use crate::error::WeexError;
+use crate::util::{json_decimal, json_list, json_str};
+use chrono::{DateTime, TimeZone, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// Funding fee settlement from `/capi/v2/account/bills`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct FundingPayment {
+    pub symbol: String,
+    /// Signed amount: positive when funding was received, negative when paid
+    pub amount: Decimal,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl FundingPayment {
+    /// Extract funding entries from a bills response, oldest first
+    ///
+    /// Entries without a timestamp are skipped.
+    pub fn parse_bills(raw: &str) -> Result<Vec<FundingPayment>, WeexError> {
+        Self::parse_bills_page(raw).map(|(payments, _)| payments)
+    }
+
+    /// Like `parse_bills`, also returning how many bills of any type the page held
+    ///
+    /// The count tells whether the endpoint's page was full, and so whether older
+    /// funding may be missing.
+    pub fn parse_bills_page(raw: &str) -> Result<(Vec<FundingPayment>, usize), WeexError> {
+        let bills = json_list(raw)?;
+        let mut payments = Vec::new();
+        for v in &bills {
+            let kind = json_str(v, &["businessType", "business_type", "type"]).unwrap_or_default();
+            if !kind.to_lowercase().contains("funding") {
+                continue;
+            }
+            let malformed = || WeexError::Unknown(format!("Malformed bill: {}", v));
+            let Some(timestamp) = json_str(v, &["cTime", "createdTime", "ctime", "timestamp"])
+                .and_then(|s| s.parse::<i64>().ok())
+                .and_then(|ms| Utc.timestamp_millis_opt(ms).single())
+            else {
+                tracing::warn!("Skipping funding bill without a timestamp: {}", v);
+                continue;
+            };
+            payments.push(FundingPayment {
+                symbol: json_str(v, &["symbol"]).ok_or_else(malformed)?,
+                amount: json_decimal(v, &["amount", "deltaAmount", "delta_amount"]).ok_or_else(malformed)?,
+                timestamp,
+            });
+        }
+        payments.sort_by_key(|p| p.timestamp);
+        Ok((payments, bills.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bills_keeps_funding_only() {
+        let raw = r#"{"data":[
+            {"symbol":"cmt_btcusdt","businessType":"position_funding","amount":"-1.25","cTime":"1700000060000"},
+            {"symbol":"cmt_btcusdt","businessType":"open_long","amount":"-0.5","cTime":"1700000000000"}
+        ]}"#;
+        let payments = FundingPayment::parse_bills(raw).unwrap();
+        assert_eq!(payments.len(), 1);
+        assert_eq!(payments[0].amount, Decimal::new(-125, 2));
+    }
+}
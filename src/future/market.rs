@@ -1,4 +1,8 @@
+use crate::error::WeexError;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::str::FromStr;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Contract {
@@ -7,3 +11,62 @@ pub struct Contract {
     pub quote_coin: String,
     pub min_trade_num: String,
 }
+
+/// Candlestick from `/capi/v2/market/candles`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Kline {
+    /// Open time in milliseconds
+    pub timestamp: i64,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    pub volume: Decimal,
+}
+
+impl Kline {
+    /// Parse a candles response: rows of `[ts, open, high, low, close, volume, ...]`,
+    /// either bare or wrapped in `{"data": [...]}`, oldest first in the result
+    pub fn parse_list(raw: &str) -> Result<Vec<Kline>, WeexError> {
+        let value: Value = serde_json::from_str(raw)?;
+        let rows = match value.get("data") {
+            Some(data) => data.clone(),
+            None if value.get("code").is_some() => {
+                return Err(WeexError::Api {
+                    code: value["code"].as_str().map(String::from).unwrap_or_else(|| value["code"].to_string()),
+                    msg: value.get("msg").and_then(|m| m.as_str()).unwrap_or_default().to_string(),
+                });
+            }
+            None => value,
+        };
+        let rows = rows.as_array().ok_or_else(|| WeexError::Unknown(format!("Unexpected candles response: {}", raw)))?;
+
+        let mut klines = rows
+            .iter()
+            .map(Self::from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+        klines.sort_by_key(|k| k.timestamp);
+        Ok(klines)
+    }
+
+    fn from_row(row: &Value) -> Result<Kline, WeexError> {
+        let fields = row.as_array().filter(|f| f.len() >= 6).ok_or_else(|| {
+            WeexError::Unknown(format!("Malformed candle row: {}", row))
+        })?;
+        let num = |i: usize| -> Result<Decimal, WeexError> {
+            let text = match &fields[i] {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            Decimal::from_str(&text).map_err(|_| WeexError::Unknown(format!("Malformed candle value: {}", text)))
+        };
+        Ok(Kline {
+            timestamp: num(0)?.trunc().try_into().unwrap_or_default(),
+            open: num(1)?,
+            high: num(2)?,
+            low: num(3)?,
+            close: num(4)?,
+            volume: num(5)?,
+        })
+    }
+}
@@ -1,3 +1,4 @@
+pub mod account;
 pub mod market;
 pub mod order;
 pub mod trigger;
@@ -1,3 +1,8 @@
+use crate::error::WeexError;
+use crate::types::{OrderType, PositionSide, Side, TimeInForce};
+use crate::util::{json_decimal, json_list, json_str};
+use chrono::{DateTime, TimeZone, Utc};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -7,3 +12,320 @@ pub struct FutureOrder {
     pub side: String,
     pub leverage: String,
 }
+
+/// Futures order with the full set of placement options
+///
+/// Build with `market` or `limit`, add options, then pass to
+/// `WeexClient::place_futures_order_request`, which validates it first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuturesOrderRequest {
+    pub symbol: String,
+    pub side: Side,
+    pub order_type: OrderType,
+    pub size: Decimal,
+    pub price: Option<Decimal>,
+    pub client_oid: Option<String>,
+    pub time_in_force: Option<TimeInForce>,
+    pub reduce_only: bool,
+    pub take_profit: Option<Decimal>,
+    pub stop_loss: Option<Decimal>,
+}
+
+impl FuturesOrderRequest {
+    pub fn market(symbol: &str, side: Side, size: Decimal) -> Self {
+        FuturesOrderRequest {
+            symbol: symbol.to_string(),
+            side,
+            order_type: OrderType::Market,
+            size,
+            price: None,
+            client_oid: None,
+            time_in_force: None,
+            reduce_only: false,
+            take_profit: None,
+            stop_loss: None,
+        }
+    }
+
+    pub fn limit(symbol: &str, side: Side, size: Decimal, price: Decimal) -> Self {
+        FuturesOrderRequest {
+            order_type: OrderType::Limit,
+            price: Some(price),
+            ..Self::market(symbol, side, size)
+        }
+    }
+
+    pub fn client_oid(mut self, client_oid: &str) -> Self {
+        self.client_oid = Some(client_oid.to_string());
+        self
+    }
+
+    pub fn time_in_force(mut self, tif: TimeInForce) -> Self {
+        self.time_in_force = Some(tif);
+        self
+    }
+
+    /// Only reduce an existing position, never open or flip one
+    pub fn reduce_only(mut self, reduce_only: bool) -> Self {
+        self.reduce_only = reduce_only;
+        self
+    }
+
+    /// Preset take-profit price attached to the resulting position
+    pub fn take_profit(mut self, price: Decimal) -> Self {
+        self.take_profit = Some(price);
+        self
+    }
+
+    /// Preset stop-loss price attached to the resulting position
+    pub fn stop_loss(mut self, price: Decimal) -> Self {
+        self.stop_loss = Some(price);
+        self
+    }
+
+    /// Check the option combination before anything is sent
+    pub fn validate(&self) -> Result<(), WeexError> {
+        let invalid = |msg: String| Err(WeexError::InvalidOrder(msg));
+        if self.size <= Decimal::ZERO {
+            return invalid(format!("size must be positive, got {}", self.size));
+        }
+        match (self.order_type, self.price) {
+            (OrderType::Limit, None) => return invalid("limit order requires a price".to_string()),
+            (OrderType::Market, Some(_)) => return invalid("market order cannot have a price".to_string()),
+            (OrderType::Trigger, _) => return invalid("use place_trigger_order for trigger orders".to_string()),
+            (_, Some(p)) if p <= Decimal::ZERO => return invalid(format!("price must be positive, got {}", p)),
+            _ => {}
+        }
+        if self.order_type == OrderType::Market && self.time_in_force == Some(TimeInForce::PostOnly) {
+            return invalid("market order cannot be post-only".to_string());
+        }
+        if self.reduce_only && (self.take_profit.is_some() || self.stop_loss.is_some()) {
+            return invalid("reduce-only order cannot carry TP/SL presets".to_string());
+        }
+
+        // Take-profit above and stop-loss below the entry for a buy, the reverse for a sell
+        let above = |a: Decimal, b: Decimal| if self.side == Side::Buy { a > b } else { a < b };
+        if let (Some(tp), Some(sl)) = (self.take_profit, self.stop_loss) {
+            if !above(tp, sl) {
+                return invalid(format!("take-profit {} and stop-loss {} are on the wrong sides for a {}", tp, sl, self.side));
+            }
+        }
+        if let Some(price) = self.price {
+            if let Some(tp) = self.take_profit.filter(|tp| !above(*tp, price)) {
+                return invalid(format!("take-profit {} is on the wrong side of price {} for a {}", tp, price, self.side));
+            }
+            if let Some(sl) = self.stop_loss.filter(|sl| !above(price, *sl)) {
+                return invalid(format!("stop-loss {} is on the wrong side of price {} for a {}", sl, price, self.side));
+            }
+        }
+        Ok(())
+    }
+
+    /// Request body for `/capi/v2/order/placeOrder`
+    pub fn body(&self) -> serde_json::Value {
+        let mut body = serde_json::json!({
+            "symbol": self.symbol,
+            "size": self.size.to_string(),
+            "side": self.side,
+            "orderType": self.order_type,
+            "marginCoin": "USDT"
+        });
+        if let Some(p) = self.price {
+            body["price"] = p.to_string().into();
+        }
+        if let Some(oid) = &self.client_oid {
+            body["clientOid"] = oid.clone().into();
+        }
+        if let Some(tif) = self.time_in_force {
+            body["timeInForce"] = serde_json::to_value(tif).unwrap_or_default();
+        }
+        if self.reduce_only {
+            body["reduceOnly"] = true.into();
+        }
+        if let Some(tp) = self.take_profit {
+            body["presetTakeProfitPrice"] = tp.to_string().into();
+        }
+        if let Some(sl) = self.stop_loss {
+            body["presetStopLossPrice"] = sl.to_string().into();
+        }
+        body
+    }
+}
+
+/// Executed trade from `/capi/v2/order/fills`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Fill {
+    pub trade_id: String,
+    pub order_id: String,
+    pub symbol: String,
+    pub side: Side,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub fee: Decimal,
+    /// PnL realized by this fill as reported by the exchange
+    pub realized_pnl: Decimal,
+    /// Fill generated by a liquidation
+    pub liquidation: bool,
+    /// Position the fill opened or closed, when reported (hedge mode directions like `close_long`)
+    #[serde(default)]
+    pub position_side: Option<PositionSide>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Fill {
+    /// Parse a fills response, oldest first
+    ///
+    /// Fills without a timestamp are skipped, since they cannot be placed in a session.
+    pub fn parse_list(raw: &str) -> Result<Vec<Fill>, WeexError> {
+        let mut fills = Vec::new();
+        for v in json_list(raw)? {
+            match Self::from_value(&v)? {
+                Some(fill) => fills.push(fill),
+                None => tracing::warn!("Skipping fill without a timestamp: {}", v),
+            }
+        }
+        fills.sort_by_key(|f| f.timestamp);
+        Ok(fills)
+    }
+
+    fn from_value(v: &serde_json::Value) -> Result<Option<Fill>, WeexError> {
+        let malformed = || WeexError::Unknown(format!("Malformed fill: {}", v));
+        let Some(timestamp) = json_timestamp(v, &["createdTime", "cTime", "createTime", "timestamp"]) else {
+            return Ok(None);
+        };
+
+        let size = json_decimal(v, &["fillSize", "size", "fill_size"]).ok_or_else(malformed)?;
+        let price = match json_decimal(v, &["fillPrice", "price", "price_avg"]) {
+            Some(p) => p,
+            None => {
+                let value = json_decimal(v, &["fillValue", "fill_value"]).ok_or_else(malformed)?;
+                if size.is_zero() { Decimal::ZERO } else { value / size }
+            }
+        };
+        let direction = json_str(v, &["orderSide", "side", "direction", "type"]).unwrap_or_default();
+        let side = side_from_direction(&direction).ok_or_else(malformed)?;
+        let position_side = json_str(v, &["positionSide", "holdSide"])
+            .and_then(|s| s.to_lowercase().parse::<PositionSide>().ok())
+            .or_else(|| position_side_from_direction(&direction));
+        let liquidation = json_str(v, &["liquidateType", "liquidate_type"])
+            .map(|t| !t.is_empty() && !t.eq_ignore_ascii_case("none"))
+            .unwrap_or(false)
+            || json_decimal(v, &["liquidateFee"]).is_some_and(|f| !f.is_zero());
+
+        Ok(Some(Fill {
+            trade_id: json_str(v, &["tradeId", "trade_id", "id"]).ok_or_else(malformed)?,
+            order_id: json_str(v, &["orderId", "order_id"]).unwrap_or_default(),
+            symbol: json_str(v, &["symbol"]).ok_or_else(malformed)?,
+            side,
+            price,
+            size,
+            fee: json_decimal(v, &["fillFee", "fee"]).unwrap_or_default().abs(),
+            realized_pnl: json_decimal(v, &["realizePnl", "realizedPnl", "totalProfits"]).unwrap_or_default(),
+            liquidation,
+            position_side,
+            timestamp,
+        }))
+    }
+}
+
+/// Order from `/capi/v2/order/current` or `/capi/v2/order/history`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct OpenOrder {
+    pub order_id: String,
+    pub client_oid: Option<String>,
+    pub symbol: String,
+    /// Order direction as reported, e.g. `open_long` or `buy`
+    pub kind: String,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub filled_size: Decimal,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl OpenOrder {
+    pub fn parse_list(raw: &str) -> Result<Vec<OpenOrder>, WeexError> {
+        json_list(raw)?.iter().map(Self::from_value).collect()
+    }
+
+    fn from_value(v: &serde_json::Value) -> Result<OpenOrder, WeexError> {
+        let malformed = || WeexError::Unknown(format!("Malformed order: {}", v));
+        Ok(OpenOrder {
+            order_id: json_str(v, &["order_id", "orderId"]).ok_or_else(malformed)?,
+            client_oid: json_str(v, &["client_oid", "clientOid"]).filter(|s| !s.is_empty()),
+            symbol: json_str(v, &["symbol"]).ok_or_else(malformed)?,
+            kind: json_str(v, &["type", "side"]).unwrap_or_default(),
+            price: json_decimal(v, &["price"]).unwrap_or_default(),
+            size: json_decimal(v, &["size"]).unwrap_or_default(),
+            filled_size: json_decimal(v, &["filled_qty", "filledQty"]).unwrap_or_default(),
+            status: json_str(v, &["status"]).unwrap_or_default(),
+            created_at: json_timestamp(v, &["createTime", "cTime", "createdTime"]).ok_or_else(malformed)?,
+        })
+    }
+
+    /// Order side derived from `kind`
+    pub fn side(&self) -> Option<Side> {
+        side_from_direction(&self.kind)
+    }
+}
+
+/// Map `buy`/`sell` or a position direction like `OPEN_LONG` / `close_short` to an order side
+pub(crate) fn side_from_direction(s: &str) -> Option<Side> {
+    match s.to_lowercase().as_str() {
+        "buy" | "open_long" | "close_short" | "1" | "4" => Some(Side::Buy),
+        "sell" | "open_short" | "close_long" | "2" | "3" => Some(Side::Sell),
+        _ => None,
+    }
+}
+
+/// Map a position direction like `OPEN_LONG` / `close_short` to the position it affects
+pub(crate) fn position_side_from_direction(s: &str) -> Option<PositionSide> {
+    match s.to_lowercase().as_str() {
+        "open_long" | "close_long" | "1" | "3" => Some(PositionSide::Long),
+        "open_short" | "close_short" | "2" | "4" => Some(PositionSide::Short),
+        _ => None,
+    }
+}
+
+/// Millisecond timestamp field, `None` when absent or unparseable
+fn json_timestamp(v: &serde_json::Value, keys: &[&str]) -> Option<DateTime<Utc>> {
+    json_str(v, keys)
+        .and_then(|s| s.parse::<i64>().ok())
+        .and_then(|ms| Utc.timestamp_millis_opt(ms).single())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_request_validation() {
+        let d = |s: &str| s.parse::<Decimal>().unwrap();
+        let limit = FuturesOrderRequest::limit("cmt_btcusdt", Side::Buy, d("0.01"), d("50000"))
+            .time_in_force(TimeInForce::PostOnly)
+            .take_profit(d("55000"))
+            .stop_loss(d("48000"))
+            .client_oid("abc");
+        assert!(limit.validate().is_ok());
+        let body = limit.body();
+        assert_eq!(body["price"], "50000");
+        assert_eq!(body["timeInForce"], "post_only");
+        assert_eq!(body["presetStopLossPrice"], "48000");
+
+        let market = FuturesOrderRequest::market("cmt_btcusdt", Side::Sell, d("0.01"));
+        assert!(market.clone().time_in_force(TimeInForce::PostOnly).validate().is_err());
+        assert!(limit.clone().take_profit(d("45000")).validate().is_err());
+        assert!(market.clone().reduce_only(true).stop_loss(d("55000")).validate().is_err());
+        assert!(FuturesOrderRequest { price: None, ..limit }.validate().is_err());
+        assert!(FuturesOrderRequest::market("cmt_btcusdt", Side::Buy, Decimal::ZERO).validate().is_err());
+    }
+
+    #[test]
+    fn test_fill_without_timestamp_is_skipped() {
+        let raw = r#"[{"tradeId":"1","symbol":"cmt_btcusdt","side":"buy","fillSize":"0.01","fillPrice":"50000","createdTime":"1700000000000"},
+                      {"tradeId":"2","symbol":"cmt_btcusdt","side":"buy","fillSize":"0.01","fillPrice":"50000"}]"#;
+        let fills = Fill::parse_list(raw).unwrap();
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].timestamp.timestamp_millis(), 1_700_000_000_000);
+    }
+}
@@ -0,0 +1,300 @@
+use crate::risk::RiskConfig;
+use crate::state::StateManager;
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Why the kill switch tripped
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TripReason {
+    DailyLoss { loss: Decimal, limit: Decimal },
+    Drawdown { drawdown: Decimal, limit: Decimal },
+    ConsecutiveLosses { count: u32, limit: u32 },
+    Manual(String),
+}
+
+impl fmt::Display for TripReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TripReason::DailyLoss { loss, limit } => write!(f, "daily loss {} reached limit {}", loss, limit),
+            TripReason::Drawdown { drawdown, limit } => write!(f, "drawdown {} reached limit {}", drawdown, limit),
+            TripReason::ConsecutiveLosses { count, limit } => write!(f, "{} consecutive losses, limit {}", count, limit),
+            TripReason::Manual(why) => write!(f, "manual: {}", why),
+        }
+    }
+}
+
+/// Persisted kill switch state
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct KillSwitchState {
+    pub tripped: Option<TripReason>,
+    pub tripped_at: Option<DateTime<Utc>>,
+    pub equity_peak: Option<Decimal>,
+    pub day: Option<NaiveDate>,
+    pub day_start_equity: Option<Decimal>,
+    pub daily_realized_pnl: Decimal,
+    pub consecutive_losses: u32,
+}
+
+/// Drawdown circuit breaker
+///
+/// Tracks equity and trade results against the `RiskConfig` limits. Once tripped
+/// it stays tripped, including across restarts when backed by a `StateManager`,
+/// until [`KillSwitch::reset`] is called.
+///
+/// State is saved only when it changes. Trips and resets are written before the
+/// call returns; peak and daily updates are written on a blocking thread when
+/// called inside a Tokio runtime, so equity ticks never wait on the disk.
+pub struct KillSwitch {
+    config: RiskConfig,
+    state: Mutex<KillSwitchState>,
+    store: Option<Arc<StateManager>>,
+    /// Version of the latest state handed to `persist`
+    version: AtomicU64,
+    /// Version last written, so a late background write never overwrites a newer one
+    written: Arc<Mutex<u64>>,
+}
+
+impl KillSwitch {
+    pub fn new(config: RiskConfig) -> Self {
+        KillSwitch {
+            config,
+            state: Mutex::new(KillSwitchState::default()),
+            store: None,
+            version: AtomicU64::new(0),
+            written: Arc::new(Mutex::new(0)),
+        }
+    }
+
+    /// Create a kill switch persisted through `store`, restoring any previous state
+    pub fn with_store(config: RiskConfig, store: Arc<StateManager>) -> Result<Self, std::io::Error> {
+        let state = store.load_kill_switch()?;
+        if let Some(ref reason) = state.tripped {
+            tracing::warn!("Kill switch restored in tripped state: {}", reason);
+        }
+        Ok(KillSwitch {
+            state: Mutex::new(state),
+            store: Some(store),
+            ..Self::new(config)
+        })
+    }
+
+    pub fn is_tripped(&self) -> bool {
+        self.lock().tripped.is_some()
+    }
+
+    pub fn trip_reason(&self) -> Option<TripReason> {
+        self.lock().tripped.clone()
+    }
+
+    pub fn snapshot(&self) -> KillSwitchState {
+        self.lock().clone()
+    }
+
+    /// Record current account equity; returns the reason if this trips the switch
+    pub fn record_equity(&self, equity: Decimal) -> Option<TripReason> {
+        let mut state = self.lock();
+        if state.tripped.is_some() {
+            return None;
+        }
+        let before = state.clone();
+        Self::roll_day(&mut state, Some(equity));
+
+        let peak = state.equity_peak.map_or(equity, |p| p.max(equity));
+        state.equity_peak = Some(peak);
+
+        let mut reason = None;
+        if let Some(start) = state.day_start_equity {
+            let loss = start - equity;
+            if loss >= self.config.daily_loss_limit {
+                reason = Some(TripReason::DailyLoss { loss, limit: self.config.daily_loss_limit });
+            }
+        }
+        if reason.is_none() && peak > Decimal::ZERO {
+            let drawdown = (peak - equity) / peak;
+            if drawdown >= self.config.max_drawdown {
+                reason = Some(TripReason::Drawdown { drawdown, limit: self.config.max_drawdown });
+            }
+        }
+        self.finish(state, &before, reason)
+    }
+
+    /// Record the realized PnL of a closed trade; returns the reason if this trips the switch
+    pub fn record_trade_result(&self, pnl: Decimal) -> Option<TripReason> {
+        let mut state = self.lock();
+        if state.tripped.is_some() {
+            return None;
+        }
+        let before = state.clone();
+        Self::roll_day(&mut state, None);
+
+        state.daily_realized_pnl += pnl;
+        if pnl < Decimal::ZERO {
+            state.consecutive_losses += 1;
+        } else {
+            state.consecutive_losses = 0;
+        }
+
+        let mut reason = None;
+        if -state.daily_realized_pnl >= self.config.daily_loss_limit {
+            reason = Some(TripReason::DailyLoss {
+                loss: -state.daily_realized_pnl,
+                limit: self.config.daily_loss_limit,
+            });
+        }
+        if reason.is_none() && state.consecutive_losses >= self.config.max_consecutive_losses {
+            reason = Some(TripReason::ConsecutiveLosses {
+                count: state.consecutive_losses,
+                limit: self.config.max_consecutive_losses,
+            });
+        }
+        self.finish(state, &before, reason)
+    }
+
+    /// Trip the switch by hand
+    pub fn trip(&self, reason: TripReason) {
+        let state = self.lock();
+        if state.tripped.is_none() {
+            let before = state.clone();
+            self.finish(state, &before, Some(reason));
+        }
+    }
+
+    /// Clear the tripped state and loss counters; equity peak restarts from the next update
+    pub fn reset(&self) {
+        let version = {
+            let mut state = self.lock();
+            *state = KillSwitchState::default();
+            self.next_version()
+        };
+        self.persist(version, KillSwitchState::default(), true);
+        tracing::info!("Kill switch reset");
+    }
+
+    fn finish(
+        &self,
+        mut state: std::sync::MutexGuard<'_, KillSwitchState>,
+        before: &KillSwitchState,
+        reason: Option<TripReason>,
+    ) -> Option<TripReason> {
+        if let Some(ref r) = reason {
+            tracing::error!("Kill switch tripped: {}", r);
+            state.tripped = Some(r.clone());
+            state.tripped_at = Some(Utc::now());
+        }
+        if *state == *before {
+            return reason;
+        }
+        let snapshot = state.clone();
+        let version = self.next_version();
+        drop(state);
+        self.persist(version, snapshot, reason.is_some());
+        reason
+    }
+
+    /// Start a new daily window when the UTC date changes
+    fn roll_day(state: &mut KillSwitchState, equity: Option<Decimal>) {
+        let today = Utc::now().date_naive();
+        if state.day != Some(today) {
+            state.day = Some(today);
+            state.day_start_equity = equity;
+            state.daily_realized_pnl = Decimal::ZERO;
+        } else if state.day_start_equity.is_none() {
+            state.day_start_equity = equity;
+        }
+    }
+
+    /// Version a state change while its guard is held, so writes keep the lock order
+    fn next_version(&self) -> u64 {
+        self.version.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    /// Save `state`, inline when `now` or outside a runtime, else on a blocking thread
+    fn persist(&self, version: u64, state: KillSwitchState, now: bool) {
+        let Some(store) = self.store.clone() else { return };
+        let written = self.written.clone();
+        let write = move || {
+            let mut last = written.lock().unwrap_or_else(|e| e.into_inner());
+            if *last >= version {
+                return;
+            }
+            match store.save_kill_switch(&state) {
+                Ok(()) => *last = version,
+                Err(e) => tracing::error!("Failed to persist kill switch state: {}", e),
+            }
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) if !now => drop(handle.spawn_blocking(write)),
+            _ => write(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, KillSwitchState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trips_and_requires_reset() {
+        let ks = KillSwitch::new(RiskConfig::default());
+        assert!(ks.record_equity(Decimal::from(10000)).is_none());
+        assert!(ks.record_equity(Decimal::from(10400)).is_none());
+
+        // 10000 -> 9400 is a 600 loss on the day, above the 500 default
+        let reason = ks.record_equity(Decimal::from(9400));
+        assert!(matches!(reason, Some(TripReason::DailyLoss { .. })));
+        assert!(ks.is_tripped());
+
+        // Stays tripped whatever happens next
+        assert!(ks.record_equity(Decimal::from(20000)).is_none());
+        assert!(ks.is_tripped());
+
+        ks.reset();
+        assert!(!ks.is_tripped());
+    }
+
+    #[test]
+    fn test_consecutive_losses() {
+        let config = RiskConfig { max_consecutive_losses: 3, ..RiskConfig::default() };
+        let ks = KillSwitch::new(config);
+        assert!(ks.record_trade_result(Decimal::from(-10)).is_none());
+        assert!(ks.record_trade_result(Decimal::from(-10)).is_none());
+        assert!(ks.record_trade_result(Decimal::from(5)).is_none());
+        assert!(ks.record_trade_result(Decimal::from(-10)).is_none());
+        assert!(ks.record_trade_result(Decimal::from(-10)).is_none());
+        assert_eq!(
+            ks.record_trade_result(Decimal::from(-10)),
+            Some(TripReason::ConsecutiveLosses { count: 3, limit: 3 })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_persists_only_changes() {
+        let dir = std::env::temp_dir().join(format!("weex_kill_switch_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let store = Arc::new(StateManager::new(&dir.to_string_lossy()));
+        let ks = KillSwitch::with_store(RiskConfig::default(), store.clone()).unwrap();
+
+        ks.record_equity(Decimal::from(10000));
+        let saved = ks.version.load(Ordering::SeqCst);
+        // Equity below the peak and above the limits changes nothing worth saving
+        for _ in 0..100 {
+            assert!(ks.record_equity(Decimal::from(9900)).is_none());
+        }
+        assert_eq!(ks.version.load(Ordering::SeqCst), saved);
+
+        // A trip is on disk as soon as it is reported
+        assert!(ks.record_equity(Decimal::from(7000)).is_some());
+        let restored = KillSwitch::with_store(RiskConfig::default(), store).unwrap();
+        assert!(matches!(restored.trip_reason(), Some(TripReason::DailyLoss { .. })));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,5 +1,6 @@
 pub mod error;
 pub mod client;
+pub mod capability;
 pub mod util;
 pub mod spot;
 pub mod future;
@@ -13,18 +14,46 @@ pub mod builder;
 pub mod rate_limiter;
 pub mod retry;
 pub mod risk;
+pub mod risk_gate;
+pub mod kill_switch;
+pub mod margin;
 pub mod engine;
 pub mod state;
 pub mod alerts;
+pub mod symbol;
+pub mod recovery;
+pub mod accounting;
+pub mod equity;
+pub mod export;
+pub mod ai_log;
+pub mod config;
+pub mod credentials;
+pub mod pool;
+
+#[cfg(test)]
+mod test_util;
 
 pub use client::WeexClient;
 pub use builder::WeexClientBuilder;
+pub use capability::{MarketClient, AccountClient, TradingClient};
 pub use error::WeexError;
 pub use traits::Exchange;
 pub use strategy::{Strategy, Context};
 pub use manager::OrderManager;
-pub use risk::{RiskConfig, PositionSizer};
+pub use risk::{RiskConfig, PositionSizer, PortfolioTarget};
+pub use risk_gate::{RiskGate, RiskRejection, OrderIntent};
+pub use kill_switch::{KillSwitch, TripReason};
+pub use margin::{MarginCalculator, ContractSpec, MarginPosition, MarginSummary};
 pub use engine::{Engine, Signal};
 pub use state::{StateManager, TradeRecord};
-pub use alerts::{TelegramAlerter, TelegramConfig};
-pub use types::{Side, OrderType, TimeInForce, MarginMode, PositionSide, TriggerType, AILogStage};
+pub use alerts::{TelegramAlerter, TelegramConfig, Alerter, AlertSink, Alert, AlertType, Severity, AlertHandle, AlertPolicy};
+pub use symbol::Symbol;
+pub use recovery::{recover, RecoveryReport};
+pub use ai_log::{AiLogEntry, AiLogEntryBuilder, AiLogQueue, Decision};
+pub use credentials::Credentials;
+pub use pool::{ClientPool, ClientPoolBuilder, ConsolidatedView};
+pub use config::{Config, Profile, RateLimitConfig};
+pub use export::{ExportFilter, CsvRecord};
+pub use equity::{EquitySnapshot, EquityCurve, EquityRecorder};
+pub use accounting::{PnlLedger, CostBasis, RoundTrip, PnlSummary};
+pub use types::{Side, OrderType, TimeInForce, MarginMode, PositionSide, TriggerType, AILogStage, ProductType};
@@ -2,7 +2,6 @@ use weex_rust_sdk::WeexClient;
 use std::env;
 use std::time::Instant;
 use futures::future::join_all;
-use tokio;
 
 #[tokio::main]
 async fn main() {
@@ -70,12 +69,10 @@ async fn run_benchmark() {
     let mut latencies = Vec::new();
     let mut success_count = 0;
 
-    for res in results {
-        if let Ok((is_ok, latency)) = res {
-            if is_ok {
-                success_count += 1;
-                latencies.push(latency);
-            }
+    for (is_ok, latency) in results.into_iter().flatten() {
+        if is_ok {
+            success_count += 1;
+            latencies.push(latency);
         }
     }
 
@@ -1,6 +1,7 @@
 use crate::spot::order::OrderResponse;
 use std::collections::HashMap;
 
+#[derive(Default)]
 pub struct OrderManager {
     // Stub: In real implementation, this would track open orders
     pub active_orders: HashMap<String, OrderResponse>,
@@ -0,0 +1,246 @@
+use crate::types::{MarginMode, PositionSide};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+/// Contract parameters needed for margin math (USDT-margined linear contracts)
+#[derive(Debug, Clone)]
+pub struct ContractSpec {
+    /// Base units per contract (1 when size is quoted in base currency)
+    pub contract_size: Decimal,
+    /// Maintenance margin rate (e.g., 0.005 = 0.5%)
+    pub maintenance_margin_rate: Decimal,
+}
+
+impl Default for ContractSpec {
+    fn default() -> Self {
+        ContractSpec {
+            contract_size: Decimal::ONE,
+            maintenance_margin_rate: Decimal::from_str("0.005").unwrap(), // 0.5%
+        }
+    }
+}
+
+/// A futures position to evaluate
+#[derive(Debug, Clone)]
+pub struct MarginPosition {
+    pub side: PositionSide,
+    pub margin_mode: MarginMode,
+    pub leverage: u32,
+    pub entry_price: Decimal,
+    pub size: Decimal,
+    /// Account equity backing the position in crossed mode; ignored for isolated
+    pub cross_collateral: Decimal,
+}
+
+/// Margin figures for a position
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarginSummary {
+    pub notional: Decimal,
+    pub initial_margin: Decimal,
+    pub maintenance_margin: Decimal,
+    /// `None` when the collateral can never be exhausted (e.g., an over-collateralized long)
+    pub liquidation_price: Option<Decimal>,
+}
+
+/// Initial/maintenance margin and liquidation price calculator
+pub struct MarginCalculator {
+    spec: ContractSpec,
+}
+
+impl MarginCalculator {
+    pub fn new(spec: ContractSpec) -> Self {
+        MarginCalculator { spec }
+    }
+
+    /// Position quantity in base currency
+    fn quantity(&self, size: Decimal) -> Decimal {
+        size * self.spec.contract_size
+    }
+
+    pub fn notional(&self, price: Decimal, size: Decimal) -> Decimal {
+        self.quantity(size) * price
+    }
+
+    /// Formula: initial_margin = notional / leverage
+    pub fn initial_margin(&self, price: Decimal, size: Decimal, leverage: u32) -> Decimal {
+        if leverage == 0 {
+            return Decimal::ZERO;
+        }
+        self.notional(price, size) / Decimal::from(leverage)
+    }
+
+    /// Formula: maintenance_margin = notional * maintenance_margin_rate
+    pub fn maintenance_margin(&self, price: Decimal, size: Decimal) -> Decimal {
+        self.notional(price, size) * self.spec.maintenance_margin_rate
+    }
+
+    /// Collateral that absorbs losses before liquidation
+    fn collateral(&self, pos: &MarginPosition) -> Decimal {
+        match pos.margin_mode {
+            MarginMode::Isolated => self.initial_margin(pos.entry_price, pos.size, pos.leverage),
+            MarginMode::Crossed => pos.cross_collateral,
+        }
+    }
+
+    /// Price at which equity falls to maintenance margin
+    ///
+    /// Long:  P = (qty * entry - collateral) / (qty * (1 - mmr))
+    /// Short: P = (qty * entry + collateral) / (qty * (1 + mmr))
+    pub fn liquidation_price(&self, pos: &MarginPosition) -> Option<Decimal> {
+        let qty = self.quantity(pos.size);
+        if qty.is_zero() {
+            return None;
+        }
+        let collateral = self.collateral(pos);
+        let mmr = self.spec.maintenance_margin_rate;
+
+        let price = match pos.side {
+            PositionSide::Long => (qty * pos.entry_price - collateral) / (qty * (Decimal::ONE - mmr)),
+            PositionSide::Short => (qty * pos.entry_price + collateral) / (qty * (Decimal::ONE + mmr)),
+        };
+        if price <= Decimal::ZERO {
+            None
+        } else {
+            Some(price)
+        }
+    }
+
+    pub fn evaluate(&self, pos: &MarginPosition) -> MarginSummary {
+        MarginSummary {
+            notional: self.notional(pos.entry_price, pos.size),
+            initial_margin: self.initial_margin(pos.entry_price, pos.size, pos.leverage),
+            maintenance_margin: self.maintenance_margin(pos.entry_price, pos.size),
+            liquidation_price: self.liquidation_price(pos),
+        }
+    }
+
+    /// Margin ratio at `mark_price`: maintenance margin / equity (liquidation at 1.0)
+    pub fn margin_ratio(&self, pos: &MarginPosition, mark_price: Decimal) -> Decimal {
+        let qty = self.quantity(pos.size);
+        let unrealized = match pos.side {
+            PositionSide::Long => qty * (mark_price - pos.entry_price),
+            PositionSide::Short => qty * (pos.entry_price - mark_price),
+        };
+        let equity = self.collateral(pos) + unrealized;
+        if equity <= Decimal::ZERO {
+            return Decimal::MAX;
+        }
+        self.maintenance_margin(mark_price, pos.size) / equity
+    }
+
+    /// Largest size whose liquidation price stays beyond the stop-loss by `buffer`
+    ///
+    /// Uses the side, margin mode, leverage and entry price of `pos` (its size is ignored).
+    /// `collateral` is the balance available to the trade; `buffer` is a fraction of the
+    /// stop price (0.1 = liquidation at least 10% past the stop). Size is also capped so
+    /// initial margin fits in `collateral`. In isolated mode the liquidation price depends
+    /// only on leverage, so the result is zero when the leverage itself puts liquidation
+    /// inside the buffer.
+    pub fn max_size_for_stop(
+        &self,
+        pos: &MarginPosition,
+        stop_price: Decimal,
+        collateral: Decimal,
+        buffer: Decimal,
+    ) -> Decimal {
+        let MarginPosition { side, margin_mode, leverage, entry_price, .. } = *pos;
+        if leverage == 0 || entry_price <= Decimal::ZERO || collateral <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+        let unit = self.spec.contract_size;
+        let mmr = self.spec.maintenance_margin_rate;
+
+        // Initial margin cap
+        let margin_cap = collateral * Decimal::from(leverage) / (entry_price * unit);
+
+        let limit = match side {
+            PositionSide::Long => stop_price * (Decimal::ONE - buffer),
+            PositionSide::Short => stop_price * (Decimal::ONE + buffer),
+        };
+
+        match margin_mode {
+            MarginMode::Isolated => {
+                let probe = MarginPosition {
+                    side,
+                    margin_mode,
+                    leverage,
+                    entry_price,
+                    size: Decimal::ONE,
+                    cross_collateral: Decimal::ZERO,
+                };
+                let safe = match (side, self.liquidation_price(&probe)) {
+                    (_, None) => true,
+                    (PositionSide::Long, Some(liq)) => liq <= limit,
+                    (PositionSide::Short, Some(liq)) => liq >= limit,
+                };
+                if safe { margin_cap } else { Decimal::ZERO }
+            }
+            MarginMode::Crossed => {
+                // Solve the liquidation formula for quantity with liq == limit
+                let denom = match side {
+                    PositionSide::Long => entry_price - limit * (Decimal::ONE - mmr),
+                    PositionSide::Short => limit * (Decimal::ONE + mmr) - entry_price,
+                };
+                if denom <= Decimal::ZERO {
+                    return margin_cap;
+                }
+                let liq_cap = collateral / (denom * unit);
+                liq_cap.min(margin_cap)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn long(mode: MarginMode, leverage: u32, collateral: Decimal) -> MarginPosition {
+        MarginPosition {
+            side: PositionSide::Long,
+            margin_mode: mode,
+            leverage,
+            entry_price: Decimal::from(50000),
+            size: Decimal::ONE,
+            cross_collateral: collateral,
+        }
+    }
+
+    #[test]
+    fn test_isolated_margin_and_liquidation() {
+        let calc = MarginCalculator::new(ContractSpec::default());
+        let pos = long(MarginMode::Isolated, 10, Decimal::ZERO);
+        let summary = calc.evaluate(&pos);
+
+        assert_eq!(summary.notional, Decimal::from(50000));
+        assert_eq!(summary.initial_margin, Decimal::from(5000));
+        assert_eq!(summary.maintenance_margin, Decimal::from(250));
+        // (50000 - 5000) / 0.995
+        let liq = summary.liquidation_price.unwrap();
+        assert_eq!(liq.round_dp(2), Decimal::from_str("45226.13").unwrap());
+        assert_eq!(calc.margin_ratio(&pos, liq).round_dp(6), Decimal::ONE);
+
+        let short = MarginPosition { side: PositionSide::Short, ..pos };
+        // (50000 + 5000) / 1.005
+        assert_eq!(
+            calc.liquidation_price(&short).unwrap().round_dp(2),
+            Decimal::from_str("54726.37").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_crossed_sizing_keeps_liquidation_past_stop() {
+        let calc = MarginCalculator::new(ContractSpec::default());
+        let collateral = Decimal::from(10000);
+        let stop = Decimal::from(48000);
+        let buffer = Decimal::from_str("0.05").unwrap();
+
+        let template = long(MarginMode::Crossed, 20, collateral);
+        let size = calc.max_size_for_stop(&template, stop, collateral, buffer);
+        assert!(size > Decimal::ZERO);
+
+        let pos = MarginPosition { size, ..template };
+        let liq = calc.liquidation_price(&pos).unwrap();
+        assert!(liq <= stop * (Decimal::ONE - buffer) + Decimal::from_str("0.0001").unwrap());
+    }
+}
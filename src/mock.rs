@@ -2,13 +2,23 @@ use async_trait::async_trait;
 use crate::traits::Exchange;
 use crate::spot::market::Ticker;
 use crate::error::WeexError;
+use crate::types::{OrderType, Side};
 use rust_decimal::Decimal;
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 
 #[derive(Clone)]
 pub struct MockExchange {
     // Simulated state
     pub tickers: Ticker,
+    /// Cancel and close requests received, as (method, symbol)
+    pub calls: Arc<Mutex<Vec<(String, String)>>>,
+}
+
+impl MockExchange {
+    fn record(&self, method: &str, symbol: &str) {
+        self.calls.lock().unwrap_or_else(|e| e.into_inner()).push((method.to_string(), symbol.to_string()));
+    }
 }
 
 impl MockExchange {
@@ -19,11 +29,18 @@ impl MockExchange {
                 last: Decimal::from_str("100000").unwrap(),
                 best_ask: Decimal::from_str("100001").unwrap(),
                 best_bid: Decimal::from_str("99999").unwrap(),
-            }
+            },
+            calls: Arc::new(Mutex::new(Vec::new())),
         }
     }
 }
 
+impl Default for MockExchange {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait]
 impl Exchange for MockExchange {
     async fn get_ticker(&self, _symbol: &str) -> Result<Ticker, WeexError> {
@@ -33,4 +50,32 @@ impl Exchange for MockExchange {
     async fn get_balance(&self) -> Result<String, WeexError> {
         Ok("{\"available\": \"1000000\"}".to_string())
     }
+
+    async fn place_order(
+        &self,
+        symbol: &str,
+        size: &str,
+        side: Side,
+        order_type: OrderType,
+        _price: Option<&str>,
+    ) -> Result<String, WeexError> {
+        let order_id = chrono::Utc::now().timestamp_millis();
+        Ok(serde_json::json!({
+            "order_id": order_id.to_string(),
+            "symbol": symbol,
+            "size": size,
+            "side": side,
+            "type": order_type
+        }).to_string())
+    }
+
+    async fn cancel_all_orders(&self, symbol: &str) -> Result<String, WeexError> {
+        self.record("cancel_all_orders", symbol);
+        Ok(serde_json::json!({ "symbol": symbol, "cancelled": true }).to_string())
+    }
+
+    async fn close_all_positions(&self, symbol: &str) -> Result<String, WeexError> {
+        self.record("close_all_positions", symbol);
+        Ok(serde_json::json!({ "symbol": symbol, "closed": true }).to_string())
+    }
 }
@@ -0,0 +1,244 @@
+use crate::builder::{http_client, WeexClientBuilder};
+use crate::capability::{AccountClient, TradingClient};
+use crate::client::WeexClient;
+use crate::config::{Config, RateLimitConfig};
+use crate::credentials::Credentials;
+use crate::equity::EquitySnapshot;
+use crate::error::WeexError;
+use crate::rate_limiter::RateLimiter;
+use crate::recovery::parse_positions;
+use crate::state::PositionRecord;
+use futures::future::join_all;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Clients for several accounts (or sub-accounts), addressed by name
+///
+/// All clients share one HTTP connection pool. Each API key gets its own rate
+/// limiter, so one busy strategy cannot use up another account's budget, while
+/// accounts registered under the same key share one.
+#[derive(Clone, Debug)]
+pub struct ClientPool {
+    clients: BTreeMap<String, WeexClient>,
+}
+
+/// Per-account equity with a total across the accounts that answered
+#[derive(Debug)]
+pub struct ConsolidatedView {
+    pub accounts: BTreeMap<String, EquitySnapshot>,
+    /// Sum over `accounts`; excludes `failed` ones
+    pub total: EquitySnapshot,
+    pub failed: BTreeMap<String, WeexError>,
+}
+
+impl ConsolidatedView {
+    /// Every account reported
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+impl ClientPool {
+    pub fn builder() -> ClientPoolBuilder {
+        ClientPoolBuilder::new()
+    }
+
+    /// Account names, sorted
+    pub fn accounts(&self) -> impl Iterator<Item = &str> {
+        self.clients.keys().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+
+    /// Full-access client for `account`
+    pub fn get(&self, account: &str) -> Result<&WeexClient, WeexError> {
+        self.clients
+            .get(account)
+            .ok_or_else(|| WeexError::Config(format!("unknown account `{}`", account)))
+    }
+
+    pub fn trading(&self, account: &str) -> Result<TradingClient, WeexError> {
+        self.get(account).map(|c| TradingClient::new(c.clone()))
+    }
+
+    pub fn read_only(&self, account: &str) -> Result<AccountClient, WeexError> {
+        self.get(account).map(|c| AccountClient::new(c.clone()))
+    }
+
+    /// Open positions of every account, fetched concurrently
+    pub async fn positions(&self) -> BTreeMap<String, Result<Vec<PositionRecord>, WeexError>> {
+        self.each(|client| async move { parse_positions(&client.get_all_positions().await?) }).await
+    }
+
+    /// Balance and position snapshot of every account, fetched concurrently
+    pub async fn snapshots(&self) -> BTreeMap<String, Result<EquitySnapshot, WeexError>> {
+        self.each(|client| async move { EquitySnapshot::capture(&client).await }).await
+    }
+
+    /// Snapshots of every account and their sum, for a consolidated risk view
+    pub async fn consolidated(&self) -> ConsolidatedView {
+        let mut accounts = BTreeMap::new();
+        let mut failed = BTreeMap::new();
+        for (name, result) in self.snapshots().await {
+            match result {
+                Ok(snapshot) => {
+                    accounts.insert(name, snapshot);
+                }
+                Err(e) => {
+                    failed.insert(name, e);
+                }
+            }
+        }
+        let total = EquitySnapshot::combine(accounts.values());
+        ConsolidatedView { accounts, total, failed }
+    }
+
+    /// Run `f` against every account concurrently
+    async fn each<T, F, Fut>(&self, f: F) -> BTreeMap<String, Result<T, WeexError>>
+    where
+        F: Fn(WeexClient) -> Fut,
+        Fut: Future<Output = Result<T, WeexError>>,
+    {
+        let names = self.clients.keys().cloned();
+        let results = join_all(self.clients.values().map(|c| f(c.clone()))).await;
+        names.zip(results).collect()
+    }
+}
+
+/// Collects named accounts for a `ClientPool`
+pub struct ClientPoolBuilder {
+    base_url: Option<String>,
+    timeout: Duration,
+    rate_limit: RateLimitConfig,
+    accounts: Vec<(String, WeexClientBuilder)>,
+}
+
+impl Default for ClientPoolBuilder {
+    fn default() -> Self {
+        ClientPoolBuilder {
+            base_url: None,
+            timeout: Duration::from_secs(10),
+            // WEEX allows 10 requests per second per key
+            rate_limit: RateLimitConfig { max_tokens: 10, refill_rate: 10 },
+            accounts: Vec::new(),
+        }
+    }
+}
+
+impl ClientPoolBuilder {
+    pub fn new() -> Self {
+        ClientPoolBuilder::default()
+    }
+
+    /// API host for accounts that do not set their own
+    pub fn base_url(mut self, url: &str) -> Self {
+        self.base_url = Some(url.to_string());
+        self
+    }
+
+    /// Timeout of the shared HTTP client
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Rate limit given to each API key whose accounts have no limiter of their own
+    pub fn rate_limit(mut self, max_tokens: u64, refill_rate: u64) -> Self {
+        self.rate_limit = RateLimitConfig { max_tokens, refill_rate };
+        self
+    }
+
+    pub fn account(self, name: &str, credentials: Credentials) -> Self {
+        self.account_builder(name, WeexClientBuilder::new().credentials(credentials))
+    }
+
+    /// Add an account from a configured builder, e.g. one with a risk gate
+    pub fn account_builder(mut self, name: &str, builder: WeexClientBuilder) -> Self {
+        self.accounts.push((name.to_string(), builder));
+        self
+    }
+
+    /// Add the config profile `name` as an account of the same name
+    pub fn profile(self, config: &Config, name: &str) -> Result<Self, WeexError> {
+        let builder = config.builder(Some(name))?;
+        Ok(self.account_builder(name, builder))
+    }
+
+    pub fn build(self) -> Result<ClientPool, WeexError> {
+        let http = http_client(self.timeout)?;
+        let mut clients = BTreeMap::new();
+        let mut limiters: HashMap<String, Arc<RateLimiter>> = HashMap::new();
+        for (name, mut builder) in self.accounts {
+            if builder.base_url.is_none() {
+                builder.base_url = self.base_url.clone();
+            }
+            let own_limiter = builder.rate_limiter.is_some();
+            let mut client = builder
+                .http_client(http.clone())
+                .build()
+                .map_err(|e| WeexError::Config(format!("account `{}`: {}", name, e)))?;
+            if !own_limiter {
+                // The exchange limits by key, so accounts sharing a key share a budget
+                let key = client.credentials.as_ref().map(|c| c.api_key().to_string()).unwrap_or_default();
+                let limit = self.rate_limit;
+                let limiter = limiters
+                    .entry(key)
+                    .or_insert_with(|| Arc::new(RateLimiter::new(limit.max_tokens, limit.refill_rate)));
+                client.rate_limiter = Some(limiter.clone());
+            }
+            if clients.insert(name.clone(), client).is_some() {
+                return Err(WeexError::Config(format!("duplicate account `{}`", name)));
+            }
+        }
+        Ok(ClientPool { clients })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::http_stub;
+
+    #[tokio::test]
+    async fn test_routes_by_account_with_separate_limits() {
+        let (url, stub) = http_stub().await;
+        let pool = ClientPool::builder()
+            .base_url(&url)
+            .account("trend", Credentials::new("key-a", "s", "p"))
+            .account("mean-rev", Credentials::new("key-b", "s", "p"))
+            .build()
+            .unwrap();
+
+        assert_eq!(pool.accounts().collect::<Vec<_>>(), ["mean-rev", "trend"]);
+        let (a, b) = (pool.get("trend").unwrap(), pool.get("mean-rev").unwrap());
+        assert_eq!(a.credentials.as_ref().unwrap().api_key(), "key-a");
+        assert!(!Arc::ptr_eq(a.rate_limiter.as_ref().unwrap(), b.rate_limiter.as_ref().unwrap()));
+        assert!(matches!(pool.trading("arb"), Err(WeexError::Config(_))));
+
+        let shared = ClientPool::builder()
+            .account("spot-desk", Credentials::new("key-a", "s", "p"))
+            .account("perp-desk", Credentials::new("key-a", "s", "p"))
+            .build()
+            .unwrap();
+        let (c, d) = (shared.get("spot-desk").unwrap(), shared.get("perp-desk").unwrap());
+        assert!(Arc::ptr_eq(c.rate_limiter.as_ref().unwrap(), d.rate_limiter.as_ref().unwrap()));
+
+        pool.read_only("trend").unwrap().get_balance().await.unwrap();
+        let (request_line, _) = stub.await.unwrap();
+        assert!(request_line.starts_with("GET /capi/v2/account/balance"));
+
+        let duplicate = ClientPool::builder()
+            .account("a", Credentials::new("k", "s", "p"))
+            .account("a", Credentials::new("k", "s", "p"))
+            .build();
+        assert!(matches!(duplicate, Err(WeexError::Config(_))));
+    }
+}
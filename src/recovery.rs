@@ -0,0 +1,313 @@
+use crate::client::WeexClient;
+use crate::error::WeexError;
+use crate::future::order::{Fill, OpenOrder};
+use crate::state::{PositionRecord, SessionState, StateManager, TradeRecord};
+use crate::symbol::Symbol;
+use crate::util::{json_decimal, json_list, json_str};
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::collections::{BTreeSet, HashSet};
+
+/// Outcome of reconciling the persisted session with the exchange
+#[derive(Debug, Clone, Default)]
+pub struct RecoveryReport {
+    /// Session to resume from, with open positions as seen on the exchange
+    pub session: SessionState,
+    pub open_orders: Vec<OpenOrder>,
+    /// Fills since the last journaled fill of each symbol that were not yet journaled
+    pub new_fills: Vec<Fill>,
+    /// Positions in the saved session that no longer exist (closed or liquidated while down)
+    pub closed_positions: Vec<PositionRecord>,
+    /// Positions on the exchange that the saved session did not know about
+    pub opened_positions: Vec<PositionRecord>,
+}
+
+impl RecoveryReport {
+    pub fn is_clean(&self) -> bool {
+        self.new_fills.is_empty() && self.closed_positions.is_empty() && self.opened_positions.is_empty()
+    }
+}
+
+/// Load the last session, fetch live state and reconcile the two
+///
+/// Positions are fetched for the whole account; open orders and fills are fetched for
+/// every symbol in the saved session, on the exchange, or in `extra_symbols`, with fills
+/// paged back to the last journaled fill of each symbol. Unseen
+/// fills and vanished positions are appended to the trade journal and the reconciled
+/// session is saved before returning.
+pub async fn recover(
+    client: &WeexClient,
+    state: &StateManager,
+    extra_symbols: &[Symbol],
+) -> Result<RecoveryReport, WeexError> {
+    let saved = state.load_session()?;
+    let live_positions = parse_positions(&client.get_all_positions().await?)?;
+
+    let mut symbols: BTreeSet<String> = extra_symbols.iter().map(|s| rest_name(s)).collect();
+    symbols.extend(saved.open_positions.iter().map(|p| rest_name(&p.symbol)));
+    symbols.extend(live_positions.iter().map(|p| rest_name(&p.symbol)));
+
+    // Fills are paged back to the newest journaled fill of each symbol, or the session save time
+    let trades = state.load_trades()?;
+    let since = saved.updated_at.or(saved.started_at);
+    let mut open_orders = Vec::new();
+    let mut fills = Vec::new();
+    for symbol in &symbols {
+        open_orders.extend(OpenOrder::parse_list(&client.get_current_orders(symbol).await?)?);
+        let last_journaled = trades.iter().filter(|t| rest_name(&t.symbol) == *symbol).map(|t| t.timestamp).max();
+        fills.extend(client.list_fills_between(symbol, last_journaled.or(since), None).await?);
+    }
+
+    let journaled: HashSet<String> = trades.into_iter().map(|t| t.id).collect();
+
+    let report = reconcile(saved, live_positions, open_orders, fills, &journaled, Utc::now());
+
+    for fill in &report.new_fills {
+        state.log_trade(&fill_record(fill))?;
+    }
+    for pos in &report.closed_positions {
+        state.log_trade(&closed_record(pos, &report.new_fills))?;
+    }
+    state.save_session(&report.session)?;
+
+    if !report.is_clean() {
+        tracing::warn!(
+            "Recovered session: {} new fills, {} positions closed, {} positions opened while offline",
+            report.new_fills.len(),
+            report.closed_positions.len(),
+            report.opened_positions.len()
+        );
+    }
+    Ok(report)
+}
+
+/// Futures REST name for any spelling `Symbol` accepts, so journal, session and
+/// exchange names compare equal; names it cannot parse are kept as they are
+fn rest_name(symbol: &str) -> String {
+    match symbol.parse::<Symbol>() {
+        Ok(s) => s.to_futures().rest_name().to_string(),
+        Err(_) => symbol.to_string(),
+    }
+}
+
+/// Reconcile a saved session with live exchange state (no I/O)
+///
+/// Every fill not in `journaled_ids` is new, whatever its time: the caller decides
+/// how far back to fetch (`recover` goes back to the last journaled fill).
+pub fn reconcile(
+    saved: SessionState,
+    live_positions: Vec<PositionRecord>,
+    open_orders: Vec<OpenOrder>,
+    fills: Vec<Fill>,
+    journaled_ids: &HashSet<String>,
+    now: DateTime<Utc>,
+) -> RecoveryReport {
+    let new_fills: Vec<Fill> = fills.into_iter().filter(|f| !journaled_ids.contains(&f.trade_id)).collect();
+
+    let key = |p: &PositionRecord| (p.symbol.clone(), p.side.to_lowercase());
+    let live_keys: HashSet<_> = live_positions.iter().map(key).collect();
+    let saved_keys: HashSet<_> = saved.open_positions.iter().map(key).collect();
+
+    let closed_positions: Vec<PositionRecord> = saved
+        .open_positions
+        .iter()
+        .filter(|p| !live_keys.contains(&key(p)))
+        .cloned()
+        .collect();
+    let opened_positions: Vec<PositionRecord> = live_positions
+        .iter()
+        .filter(|p| !saved_keys.contains(&key(p)))
+        .cloned()
+        .collect();
+
+    let mut session = saved;
+    session.total_trades += new_fills.len() as u64;
+    for fill in &new_fills {
+        let pnl = fill.realized_pnl.to_f64().unwrap_or_default();
+        session.total_pnl += pnl;
+        if pnl > 0.0 {
+            session.winning_trades += 1;
+        }
+    }
+    session.open_positions = live_positions;
+    session.updated_at = Some(now);
+
+    RecoveryReport {
+        session,
+        open_orders,
+        new_fills,
+        closed_positions,
+        opened_positions,
+    }
+}
+
+/// Parse `/capi/v2/account/position/allPosition` into position records, skipping flat entries
+pub fn parse_positions(raw: &str) -> Result<Vec<PositionRecord>, WeexError> {
+    let mut positions = Vec::new();
+    for v in json_list(raw)? {
+        let size = json_decimal(&v, &["size", "total", "hold_amount"]).unwrap_or_default();
+        if size.is_zero() {
+            continue;
+        }
+        let symbol = json_str(&v, &["symbol"])
+            .ok_or_else(|| WeexError::Unknown(format!("Malformed position: {}", v)))?;
+        let entry = match json_decimal(&v, &["openPriceAvg", "open_avg_price", "averageOpenPrice", "entry_price"]) {
+            Some(p) => p,
+            None => json_decimal(&v, &["open_value", "openValue"]).map_or(Decimal::ZERO, |ov| ov / size.abs()),
+        };
+        positions.push(PositionRecord {
+            symbol,
+            side: json_str(&v, &["side", "holdSide"]).unwrap_or_default().to_lowercase(),
+            size: size.abs().to_f64().unwrap_or_default(),
+            entry_price: entry.to_f64().unwrap_or_default(),
+            unrealized_pnl: json_decimal(&v, &["unrealizePnl", "unrealizedPnl", "unrealized_pnl"])
+                .and_then(|p| p.to_f64())
+                .unwrap_or_default(),
+        });
+    }
+    Ok(positions)
+}
+
+fn fill_record(fill: &Fill) -> TradeRecord {
+    TradeRecord {
+        id: fill.trade_id.clone(),
+        timestamp: fill.timestamp,
+        symbol: fill.symbol.clone(),
+        side: fill.side.to_string(),
+        size: fill.size.to_f64().unwrap_or_default(),
+        price: fill.price.to_f64().unwrap_or_default(),
+        pnl: (!fill.realized_pnl.is_zero()).then(|| fill.realized_pnl.to_f64().unwrap_or_default()),
+        status: if fill.liquidation { "liquidated" } else { "filled_offline" }.to_string(),
+    }
+}
+
+fn closed_record(pos: &PositionRecord, new_fills: &[Fill]) -> TradeRecord {
+    let liquidated = new_fills.iter().any(|f| f.symbol == pos.symbol && f.liquidation);
+    TradeRecord {
+        id: format!("recovery-{}-{}-{}", pos.symbol, pos.side, Utc::now().timestamp_millis()),
+        timestamp: Utc::now(),
+        symbol: pos.symbol.clone(),
+        side: pos.side.clone(),
+        size: pos.size,
+        price: pos.entry_price,
+        pnl: None,
+        status: if liquidated { "liquidated" } else { "closed_offline" }.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::http_stub_responses;
+    use crate::types::Side;
+
+    fn position(symbol: &str, side: &str) -> PositionRecord {
+        PositionRecord {
+            symbol: symbol.to_string(),
+            side: side.to_string(),
+            size: 0.01,
+            entry_price: 50000.0,
+            unrealized_pnl: 0.0,
+        }
+    }
+
+    fn fill(id: &str, at: DateTime<Utc>, pnl: i64, liquidation: bool) -> Fill {
+        Fill {
+            trade_id: id.to_string(),
+            order_id: "o1".to_string(),
+            symbol: "cmt_btcusdt".to_string(),
+            side: Side::Sell,
+            price: Decimal::from(45000),
+            size: Decimal::from(1),
+            fee: Decimal::ZERO,
+            realized_pnl: Decimal::from(pnl),
+            liquidation,
+            position_side: None,
+            timestamp: at,
+        }
+    }
+
+    #[test]
+    fn test_reconcile_detects_offline_changes() {
+        let saved_at = Utc::now() - chrono::Duration::hours(1);
+        let saved = SessionState {
+            updated_at: Some(saved_at),
+            open_positions: vec![position("cmt_btcusdt", "long"), position("cmt_ethusdt", "short")],
+            ..Default::default()
+        };
+        let live = vec![position("cmt_ethusdt", "short"), position("cmt_solusdt", "long")];
+        let fills = vec![
+            // Filled after the last journaled trade but before the session was saved
+            fill("gap", saved_at - chrono::Duration::minutes(5), 10, false),
+            fill("seen", saved_at + chrono::Duration::minutes(1), 10, false),
+            fill("liq", saved_at + chrono::Duration::minutes(2), -500, true),
+        ];
+        let journaled: HashSet<String> = ["seen".to_string()].into_iter().collect();
+
+        let report = reconcile(saved, live, Vec::new(), fills, &journaled, Utc::now());
+
+        assert_eq!(report.new_fills.iter().map(|f| f.trade_id.as_str()).collect::<Vec<_>>(), vec!["gap", "liq"]);
+        assert_eq!(report.closed_positions[0].symbol, "cmt_btcusdt");
+        assert_eq!(report.opened_positions[0].symbol, "cmt_solusdt");
+        assert_eq!(report.session.open_positions.len(), 2);
+        assert_eq!(report.session.total_pnl, -490.0);
+        assert_eq!(closed_record(&report.closed_positions[0], &report.new_fills).status, "liquidated");
+    }
+
+    #[test]
+    fn test_parse_positions() {
+        let raw = r#"[{"symbol":"cmt_btcusdt","side":"LONG","size":"0.02","open_value":"1000","unrealizePnl":"3.5"},
+                      {"symbol":"cmt_ethusdt","side":"SHORT","size":"0"}]"#;
+        let positions = parse_positions(raw).unwrap();
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].side, "long");
+        assert_eq!(positions[0].entry_price, 50000.0);
+    }
+
+    #[tokio::test]
+    async fn test_recover_journals_fills_before_session_save() {
+        let dir = std::env::temp_dir().join(format!("weex_recover_gap_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let state = StateManager::new(&dir.to_string_lossy());
+        let saved_at = Utc::now() - chrono::Duration::hours(1);
+        let journaled_at = saved_at - chrono::Duration::minutes(30);
+        // Journaled under the WS spelling; the exchange reports `cmt_btcusdt`
+        state
+            .log_trade(&TradeRecord {
+                id: "t1".to_string(),
+                timestamp: journaled_at,
+                symbol: "BTCUSDT".to_string(),
+                side: "buy".to_string(),
+                size: 1.0,
+                price: 50000.0,
+                pnl: None,
+                status: "filled".to_string(),
+            })
+            .unwrap();
+        state.save_session(&SessionState { updated_at: Some(saved_at), ..Default::default() }).unwrap();
+
+        let gap_ms = (saved_at - chrono::Duration::minutes(10)).timestamp_millis();
+        let (url, stub) = http_stub_responses(vec![
+            "[]".to_string(),
+            "[]".to_string(),
+            format!(
+                r#"[{{"tradeId":"t2","symbol":"cmt_btcusdt","side":"sell","fillSize":"1","fillPrice":"51000","createdTime":"{}"}}]"#,
+                gap_ms
+            ),
+        ])
+        .await;
+        let client = WeexClient::builder().base_url(&url).api_key("k").secret_key("s").passphrase("p").build().unwrap();
+
+        let symbol: Symbol = "cmt_btcusdt".parse().unwrap();
+        let report = recover(&client, &state, &[symbol]).await.unwrap();
+        assert_eq!(report.new_fills.iter().map(|f| f.trade_id.as_str()).collect::<Vec<_>>(), ["t2"]);
+        assert!(state.load_trades().unwrap().iter().any(|t| t.id == "t2"));
+
+        // Paged back to the journaled trade, not the session save
+        let requests = stub.await.unwrap();
+        assert!(requests[2].0.contains(&format!("startTime={}", journaled_at.timestamp_millis())));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
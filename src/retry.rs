@@ -51,7 +51,7 @@ where
 }
 
 /// Determine if an error is retryable
-fn is_retryable(error: &WeexError) -> bool {
+pub(crate) fn is_retryable(error: &WeexError) -> bool {
     match error {
         WeexError::Http(_) => true, // Network errors are retryable
         WeexError::Api { code, .. } => {
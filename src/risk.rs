@@ -1,8 +1,14 @@
+use crate::future::market::Kline;
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
+use serde::Deserialize;
 use std::str::FromStr;
 
 /// Risk management configuration
-#[derive(Debug, Clone)]
+///
+/// Deserializes from a partial table; missing fields keep their defaults.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
 pub struct RiskConfig {
     /// Maximum risk per trade as percentage of account (e.g., 0.02 = 2%)
     pub max_risk_per_trade: Decimal,
@@ -12,6 +18,24 @@ pub struct RiskConfig {
     pub max_leverage: u32,
     /// Maximum number of concurrent positions
     pub max_positions: usize,
+    /// Maximum order notional in quote currency (size * price)
+    pub max_notional: Decimal,
+    /// Maximum number of resting (non-market) orders
+    pub max_open_orders: usize,
+    /// Maximum deviation of a limit price from mark (e.g., 0.05 = 5%)
+    pub max_price_deviation: Decimal,
+    /// Maximum number of orders submitted per rolling minute
+    pub max_orders_per_minute: u32,
+    /// Loss in quote currency since the start of the UTC day that trips the kill switch
+    pub daily_loss_limit: Decimal,
+    /// Drawdown from equity peak that trips the kill switch (e.g., 0.2 = 20%)
+    pub max_drawdown: Decimal,
+    /// Number of losing trades in a row that trips the kill switch
+    pub max_consecutive_losses: u32,
+    /// Also close all positions when the kill switch trips (orders are always cancelled)
+    pub close_positions_on_trip: bool,
+    /// Maximum gross notional across all symbols as a multiple of account balance
+    pub max_portfolio_leverage: Decimal,
 }
 
 impl Default for RiskConfig {
@@ -21,6 +45,15 @@ impl Default for RiskConfig {
             max_position_size: Decimal::from_str("1.0").unwrap(),   // 1 BTC
             max_leverage: 10,
             max_positions: 3,
+            max_notional: Decimal::from(100_000),
+            max_open_orders: 20,
+            max_price_deviation: Decimal::from_str("0.05").unwrap(), // 5%
+            max_orders_per_minute: 60,
+            daily_loss_limit: Decimal::from(500),
+            max_drawdown: Decimal::from_str("0.2").unwrap(), // 20%
+            max_consecutive_losses: 5,
+            close_positions_on_trip: false,
+            max_portfolio_leverage: Decimal::from(3),
         }
     }
 }
@@ -100,6 +133,68 @@ impl PositionSizer {
         }
     }
 
+    /// ATR-based position sizing
+    ///
+    /// Formula: size = (account_balance * risk_percent) / (atr * atr_multiplier)
+    /// The stop sits `atr_multiplier` ATRs from entry, so risk per trade stays constant
+    /// as volatility changes.
+    pub fn atr_position_size(
+        &self,
+        account_balance: Decimal,
+        atr: Decimal,
+        atr_multiplier: Decimal,
+    ) -> Decimal {
+        let stop_distance = atr * atr_multiplier;
+        if stop_distance <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+
+        let size = account_balance * self.config.max_risk_per_trade / stop_distance;
+        size.min(self.config.max_position_size)
+    }
+
+    /// Volatility-targeted position sizing
+    ///
+    /// Formula: size = (account_balance * target_vol / annualized_vol) / price
+    /// Both volatilities are annualized fractions (e.g., 0.4 = 40%).
+    pub fn volatility_target_size(
+        &self,
+        account_balance: Decimal,
+        price: Decimal,
+        annualized_vol: Decimal,
+        target_vol: Decimal,
+    ) -> Decimal {
+        if annualized_vol <= Decimal::ZERO || price <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+
+        let notional = account_balance * target_vol / annualized_vol;
+        let size = notional / price;
+        size.min(self.config.max_position_size)
+    }
+
+    /// Scale target positions down so gross notional stays within `max_portfolio_leverage`
+    ///
+    /// Targets are scaled proportionally; they are returned unchanged when already within the cap.
+    pub fn apply_portfolio_cap(
+        &self,
+        account_balance: Decimal,
+        targets: &[PortfolioTarget],
+    ) -> Vec<PortfolioTarget> {
+        let gross: Decimal = targets.iter().map(|t| (t.size * t.price).abs()).sum();
+        let cap = account_balance * self.config.max_portfolio_leverage;
+
+        if gross <= cap || gross.is_zero() {
+            return targets.to_vec();
+        }
+
+        let scale = cap / gross;
+        targets
+            .iter()
+            .map(|t| PortfolioTarget { size: t.size * scale, ..t.clone() })
+            .collect()
+    }
+
     /// Check if we can open a new position given current positions count
     pub fn can_open_position(&self, current_positions: usize) -> bool {
         current_positions < self.config.max_positions
@@ -115,6 +210,80 @@ impl PositionSizer {
     }
 }
 
+/// Desired position for one symbol, used for portfolio-level caps
+#[derive(Debug, Clone, PartialEq)]
+pub struct PortfolioTarget {
+    pub symbol: String,
+    pub size: Decimal,
+    pub price: Decimal,
+}
+
+/// Average True Range using Wilder's smoothing over `period` candles
+///
+/// Klines must be oldest first; returns `None` with fewer than `period + 1` candles.
+pub fn average_true_range(klines: &[Kline], period: usize) -> Option<Decimal> {
+    if period == 0 || klines.len() < period + 1 {
+        return None;
+    }
+
+    let true_ranges: Vec<Decimal> = klines
+        .windows(2)
+        .map(|w| {
+            let prev_close = w[0].close;
+            let k = &w[1];
+            (k.high - k.low)
+                .max((k.high - prev_close).abs())
+                .max((k.low - prev_close).abs())
+        })
+        .collect();
+
+    let n = Decimal::from(period);
+    let mut atr: Decimal = true_ranges[..period].iter().sum::<Decimal>() / n;
+    for tr in &true_ranges[period..] {
+        atr = (atr * (n - Decimal::ONE) + tr) / n;
+    }
+    Some(atr)
+}
+
+/// Annualized volatility of close-to-close log returns
+///
+/// `periods_per_year` matches the kline interval (see [`periods_per_year`]).
+pub fn annualized_volatility(klines: &[Kline], periods_per_year: f64) -> Option<Decimal> {
+    let closes: Vec<f64> = klines.iter().filter_map(|k| k.close.to_f64()).collect();
+    if closes.len() < 3 || closes.len() != klines.len() {
+        return None;
+    }
+
+    let returns: Vec<f64> = closes
+        .windows(2)
+        .filter(|w| w[0] > 0.0 && w[1] > 0.0)
+        .map(|w| (w[1] / w[0]).ln())
+        .collect();
+    if returns.len() < 2 {
+        return None;
+    }
+
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+    Decimal::from_f64_retain(variance.sqrt() * periods_per_year.sqrt())
+}
+
+/// Number of candles per year for a WEEX granularity such as `1m`, `15m`, `4h` or `1D`
+pub fn periods_per_year(granularity: &str) -> Option<f64> {
+    let g = granularity.trim();
+    let split = g.find(|c: char| !c.is_ascii_digit())?;
+    let (count, unit) = g.split_at(split);
+    let count: f64 = count.parse().ok().filter(|c: &f64| *c > 0.0)?;
+    let minutes = match unit {
+        "m" => 1.0,
+        "h" | "H" => 60.0,
+        "d" | "D" => 1440.0,
+        "w" | "W" => 10080.0,
+        _ => return None,
+    };
+    Some(365.0 * 1440.0 / (minutes * count))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -132,4 +301,52 @@ mod tests {
         // Size = $200 / $1000 = 0.2 BTC
         assert_eq!(size, Decimal::from_str("0.2").unwrap());
     }
+
+    fn kline(high: i64, low: i64, close: i64) -> Kline {
+        Kline {
+            timestamp: 0,
+            open: Decimal::from(close),
+            high: Decimal::from(high),
+            low: Decimal::from(low),
+            close: Decimal::from(close),
+            volume: Decimal::ONE,
+        }
+    }
+
+    #[test]
+    fn test_atr_sizing() {
+        let klines = vec![kline(105, 95, 100), kline(110, 100, 105), kline(108, 98, 100), kline(104, 96, 102)];
+        // True ranges: 10, 10, 8 -> ATR(3) = 28/3
+        let atr = average_true_range(&klines, 3).unwrap();
+        assert_eq!(atr.round_dp(4), Decimal::from_str("9.3333").unwrap());
+
+        let sizer = PositionSizer::new(RiskConfig::default());
+        // Risk $200 over a 2 ATR stop of $500 -> 0.4
+        let size = sizer.atr_position_size(Decimal::from(10000), Decimal::from(250), Decimal::from(2));
+        assert_eq!(size, Decimal::from_str("0.4").unwrap());
+    }
+
+    #[test]
+    fn test_volatility_target_and_portfolio_cap() {
+        let sizer = PositionSizer::new(RiskConfig::default());
+        // 20% target / 80% vol -> $2500 notional at $5000 -> 0.5
+        let size = sizer.volatility_target_size(
+            Decimal::from(10000),
+            Decimal::from(5000),
+            Decimal::from_str("0.8").unwrap(),
+            Decimal::from_str("0.2").unwrap(),
+        );
+        assert_eq!(size, Decimal::from_str("0.5").unwrap());
+
+        let targets = vec![
+            PortfolioTarget { symbol: "cmt_btcusdt".into(), size: Decimal::from(1), price: Decimal::from(40000) },
+            PortfolioTarget { symbol: "cmt_ethusdt".into(), size: Decimal::from(10), price: Decimal::from(2000) },
+        ];
+        // Gross $60k against a 3x cap on $10k -> scale by 0.5
+        let capped = sizer.apply_portfolio_cap(Decimal::from(10000), &targets);
+        assert_eq!(capped[0].size, Decimal::from_str("0.5").unwrap());
+        assert_eq!(capped[1].size, Decimal::from(5));
+
+        assert_eq!(periods_per_year("1H"), Some(8760.0));
+    }
 }
@@ -0,0 +1,407 @@
+use crate::risk::RiskConfig;
+use crate::symbol::Symbol;
+use crate::types::{OrderType, Side};
+use rust_decimal::Decimal;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Reason an order was refused by the [`RiskGate`]
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum RiskRejection {
+    #[error("order notional {notional} exceeds limit {limit}")]
+    MaxNotional { notional: Decimal, limit: Decimal },
+
+    #[error("resulting position {resulting} on {symbol} exceeds limit {limit}")]
+    MaxPositionSize { symbol: String, resulting: Decimal, limit: Decimal },
+
+    #[error("{open} positions already open, limit {limit}")]
+    MaxPositions { open: usize, limit: usize },
+
+    #[error("leverage {leverage}x exceeds limit {limit}x")]
+    Leverage { leverage: u32, limit: u32 },
+
+    #[error("{open} orders already open, limit {limit}")]
+    MaxOpenOrders { open: usize, limit: usize },
+
+    #[error("price {price} deviates {deviation} from mark {mark}, limit {limit}")]
+    PriceBand { price: Decimal, mark: Decimal, deviation: Decimal, limit: Decimal },
+
+    #[error("{count} orders in the last minute, limit {limit}")]
+    OrderRate { count: u32, limit: u32 },
+
+    #[error("no price or mark price known for {symbol}")]
+    MissingReferencePrice { symbol: String },
+
+    #[error("invalid numeric value '{0}'")]
+    InvalidNumber(String),
+
+    #[error("unknown order side '{0}'")]
+    UnknownSide(String),
+
+    #[error("unrecognized symbol '{0}'")]
+    InvalidSymbol(String),
+}
+
+/// An order as seen by the risk gate, before it is sent to the exchange
+#[derive(Debug, Clone)]
+pub struct OrderIntent {
+    pub symbol: String,
+    pub side: Side,
+    pub order_type: OrderType,
+    pub size: Decimal,
+    pub price: Option<Decimal>,
+}
+
+impl OrderIntent {
+    /// Build an intent from the string arguments used by `WeexClient` order methods
+    pub fn from_strs(
+        symbol: &str,
+        side: Side,
+        order_type: OrderType,
+        size: &str,
+        price: Option<&str>,
+    ) -> Result<Self, RiskRejection> {
+        let size: Decimal = size.parse().map_err(|_| RiskRejection::InvalidNumber(size.to_string()))?;
+        let price = match price {
+            Some(p) => Some(p.parse::<Decimal>().map_err(|_| RiskRejection::InvalidNumber(p.to_string()))?),
+            None => None,
+        };
+        Ok(OrderIntent {
+            symbol: symbol.to_string(),
+            side,
+            order_type,
+            size,
+            price,
+        })
+    }
+}
+
+/// Canonical gate key for any accepted symbol form
+///
+/// The WebSocket reports `BTCUSDT` while orders use `cmt_btcusdt`; both map to
+/// the futures symbol so marks, positions and orders land on the same entry.
+fn gate_key(symbol: &str) -> Result<Symbol, RiskRejection> {
+    symbol
+        .parse::<Symbol>()
+        .map(|s| s.to_futures())
+        .map_err(|_| RiskRejection::InvalidSymbol(symbol.to_string()))
+}
+
+#[derive(Debug, Default)]
+struct GateState {
+    /// Signed position size per symbol (long > 0, short < 0)
+    positions: HashMap<Symbol, Decimal>,
+    mark_prices: HashMap<Symbol, Decimal>,
+    leverage: HashMap<Symbol, u32>,
+    open_orders: HashMap<Symbol, usize>,
+    recent_orders: VecDeque<Instant>,
+    /// Accepted orders not yet reported filled or cancelled, by order ID
+    reserved: HashMap<String, Reservation>,
+}
+
+/// An accepted order counted against the limits until account events catch up
+#[derive(Debug)]
+struct Reservation {
+    key: Symbol,
+    /// Signed size the order adds to the position once filled
+    delta: Decimal,
+    resting: bool,
+}
+
+impl GateState {
+    /// Reported positions plus the size of reserved orders
+    fn exposure(&self) -> HashMap<&Symbol, Decimal> {
+        let mut exposure: HashMap<&Symbol, Decimal> = self.positions.iter().map(|(k, v)| (k, *v)).collect();
+        for reservation in self.reserved.values() {
+            *exposure.entry(&reservation.key).or_default() += reservation.delta;
+        }
+        exposure
+    }
+}
+
+/// Order statuses after which an order no longer rests or adds to the position
+const FINAL_STATUSES: [&str; 6] = ["filled", "full_fill", "canceled", "cancelled", "rejected", "expired"];
+
+fn signed_size(order: &OrderIntent) -> Decimal {
+    match order.side {
+        Side::Buy => order.size,
+        Side::Sell => -order.size,
+    }
+}
+
+/// Pre-trade risk check enforced before every order
+///
+/// The gate keeps a view of positions, mark prices, leverage and open orders
+/// that callers (or the `Engine`) feed from account and market events. A single
+/// gate can be shared via `Arc` between a `WeexClient` and an `Engine`, but an
+/// order passing through both is counted twice against the per-minute rate.
+/// Symbols are accepted in any form `Symbol` parses; names that do not parse
+/// are rejected by `check_order` and ignored by the `update_*` methods.
+///
+/// Orders the exchange accepted are `reserve`d: they count toward open orders
+/// and the resulting position until `order_update` reports them filled or
+/// cancelled, so a burst of signals cannot slip past the limits before the
+/// account stream catches up.
+pub struct RiskGate {
+    config: RiskConfig,
+    state: Mutex<GateState>,
+}
+
+impl RiskGate {
+    pub fn new(config: RiskConfig) -> Self {
+        RiskGate {
+            config,
+            state: Mutex::new(GateState::default()),
+        }
+    }
+
+    pub fn config(&self) -> &RiskConfig {
+        &self.config
+    }
+
+    /// Check an order against every limit; on success the order counts toward the rate limit
+    pub fn check_order(&self, order: &OrderIntent) -> Result<(), RiskRejection> {
+        let key = gate_key(&order.symbol)?;
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let config = &self.config;
+
+        // Per-minute order rate
+        let now = Instant::now();
+        while let Some(front) = state.recent_orders.front() {
+            if now.duration_since(*front) >= Duration::from_secs(60) {
+                state.recent_orders.pop_front();
+            } else {
+                break;
+            }
+        }
+        let count = state.recent_orders.len() as u32;
+        if count >= config.max_orders_per_minute {
+            return Err(RiskRejection::OrderRate { count, limit: config.max_orders_per_minute });
+        }
+
+        // Leverage currently configured for the symbol
+        if let Some(&leverage) = state.leverage.get(&key) {
+            if leverage > config.max_leverage {
+                return Err(RiskRejection::Leverage { leverage, limit: config.max_leverage });
+            }
+        }
+
+        // Price band against mark
+        let mark = state.mark_prices.get(&key).copied();
+        if let (Some(price), Some(mark)) = (order.price, mark) {
+            if !mark.is_zero() {
+                let deviation = ((price - mark) / mark).abs();
+                if deviation > config.max_price_deviation {
+                    return Err(RiskRejection::PriceBand {
+                        price,
+                        mark,
+                        deviation,
+                        limit: config.max_price_deviation,
+                    });
+                }
+            }
+        }
+
+        // Notional
+        let reference = order.price.or(mark).ok_or_else(|| RiskRejection::MissingReferencePrice {
+            symbol: order.symbol.clone(),
+        })?;
+        let notional = order.size * reference;
+        if notional > config.max_notional {
+            return Err(RiskRejection::MaxNotional { notional, limit: config.max_notional });
+        }
+
+        // Position size and concurrent positions, only when exposure grows
+        let exposure = state.exposure();
+        let current = exposure.get(&key).copied().unwrap_or(Decimal::ZERO);
+        let resulting = current + signed_size(order);
+        if resulting.abs() > current.abs() {
+            if resulting.abs() > config.max_position_size {
+                return Err(RiskRejection::MaxPositionSize {
+                    symbol: order.symbol.clone(),
+                    resulting: resulting.abs(),
+                    limit: config.max_position_size,
+                });
+            }
+            if current.is_zero() {
+                let open = exposure.values().filter(|p| !p.is_zero()).count();
+                if open >= config.max_positions {
+                    return Err(RiskRejection::MaxPositions { open, limit: config.max_positions });
+                }
+            }
+        }
+
+        // Resting orders
+        if order.order_type != OrderType::Market {
+            let reserved = state.reserved.values().filter(|r| r.resting).count();
+            let open: usize = state.open_orders.values().sum::<usize>() + reserved;
+            if open >= config.max_open_orders {
+                return Err(RiskRejection::MaxOpenOrders { open, limit: config.max_open_orders });
+            }
+        }
+
+        state.recent_orders.push_back(now);
+        Ok(())
+    }
+
+    /// Count an order the exchange accepted until `order_update` reports it done
+    pub fn reserve(&self, order_id: &str, order: &OrderIntent) {
+        let resting = order.order_type != OrderType::Market;
+        let delta = signed_size(order);
+        self.update(&order.symbol, |state, key| {
+            state.reserved.insert(order_id.to_string(), Reservation { key, delta, resting });
+        });
+    }
+
+    /// Release a reserved order once its status is final (filled, cancelled, rejected or expired)
+    ///
+    /// The filled size is expected to arrive through `update_position`.
+    pub fn order_update(&self, order_id: &str, status: &str) {
+        if FINAL_STATUSES.iter().any(|s| status.eq_ignore_ascii_case(s)) {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            state.reserved.remove(order_id);
+        }
+    }
+
+    /// Check a leverage change before it is sent to the exchange
+    pub fn check_leverage(&self, leverage: u32) -> Result<(), RiskRejection> {
+        if leverage > self.config.max_leverage {
+            return Err(RiskRejection::Leverage { leverage, limit: self.config.max_leverage });
+        }
+        Ok(())
+    }
+
+    /// Record the signed position size for a symbol (long > 0, short < 0)
+    pub fn update_position(&self, symbol: &str, size: Decimal) {
+        self.update(symbol, |state, key| {
+            state.positions.insert(key, size);
+        });
+    }
+
+    pub fn update_mark_price(&self, symbol: &str, price: Decimal) {
+        self.update(symbol, |state, key| {
+            state.mark_prices.insert(key, price);
+        });
+    }
+
+    pub fn update_leverage(&self, symbol: &str, leverage: u32) {
+        self.update(symbol, |state, key| {
+            state.leverage.insert(key, leverage);
+        });
+    }
+
+    pub fn update_open_orders(&self, symbol: &str, count: usize) {
+        self.update(symbol, |state, key| {
+            state.open_orders.insert(key, count);
+        });
+    }
+
+    pub fn mark_price(&self, symbol: &str) -> Option<Decimal> {
+        let key = gate_key(symbol).ok()?;
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.mark_prices.get(&key).copied()
+    }
+
+    fn update(&self, symbol: &str, apply: impl FnOnce(&mut GateState, Symbol)) {
+        match gate_key(symbol) {
+            Ok(key) => apply(&mut self.state.lock().unwrap_or_else(|e| e.into_inner()), key),
+            Err(e) => tracing::warn!("Risk gate ignoring update: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn intent(side: Side, size: &str, price: Option<&str>) -> OrderIntent {
+        OrderIntent::from_strs("cmt_btcusdt", side, OrderType::Limit, size, price).unwrap()
+    }
+
+    #[test]
+    fn test_rejects_each_limit() {
+        let gate = RiskGate::new(RiskConfig::default());
+        gate.update_mark_price("cmt_btcusdt", Decimal::from(50000));
+
+        assert!(gate.check_order(&intent(Side::Buy, "0.5", Some("50000"))).is_ok());
+        assert!(matches!(
+            gate.check_order(&intent(Side::Buy, "0.5", Some("60000"))),
+            Err(RiskRejection::PriceBand { .. })
+        ));
+        assert!(matches!(
+            gate.check_order(&intent(Side::Buy, "1.5", None)),
+            Err(RiskRejection::MaxPositionSize { .. })
+        ));
+
+        gate.update_position("cmt_btcusdt", Decimal::from_str("0.9").unwrap());
+        assert!(matches!(
+            gate.check_order(&intent(Side::Buy, "0.2", None)),
+            Err(RiskRejection::MaxPositionSize { .. })
+        ));
+        // Reducing exposure is always allowed
+        assert!(gate.check_order(&intent(Side::Sell, "0.2", None)).is_ok());
+
+        gate.update_leverage("cmt_btcusdt", 50);
+        assert!(matches!(
+            gate.check_order(&intent(Side::Sell, "0.1", None)),
+            Err(RiskRejection::Leverage { .. })
+        ));
+    }
+
+    #[test]
+    fn test_order_rate() {
+        let config = RiskConfig { max_orders_per_minute: 2, ..RiskConfig::default() };
+        let gate = RiskGate::new(config);
+        let order = intent(Side::Buy, "0.01", Some("50000"));
+
+        assert!(gate.check_order(&order).is_ok());
+        assert!(gate.check_order(&order).is_ok());
+        assert_eq!(
+            gate.check_order(&order),
+            Err(RiskRejection::OrderRate { count: 2, limit: 2 })
+        );
+    }
+
+    #[test]
+    fn test_ws_and_rest_names_share_state() {
+        let gate = RiskGate::new(RiskConfig::default());
+        gate.update_mark_price("BTCUSDT", Decimal::from(50000));
+        assert_eq!(gate.mark_price("cmt_btcusdt"), Some(Decimal::from(50000)));
+
+        let market = OrderIntent::from_strs("cmt_btcusdt", Side::Buy, OrderType::Market, "0.01", None).unwrap();
+        assert!(gate.check_order(&market).is_ok());
+
+        let unknown = OrderIntent::from_strs("nonsense", Side::Buy, OrderType::Market, "0.01", None).unwrap();
+        assert_eq!(gate.check_order(&unknown), Err(RiskRejection::InvalidSymbol("nonsense".to_string())));
+    }
+
+    #[test]
+    fn test_accepted_orders_count_until_done() {
+        let config = RiskConfig { max_open_orders: 2, ..RiskConfig::default() };
+        let gate = RiskGate::new(config);
+        gate.update_mark_price("cmt_btcusdt", Decimal::from(50000));
+
+        // Resting orders the account stream has not reported yet still count
+        gate.reserve("1", &intent(Side::Buy, "0.1", Some("50000")));
+        gate.reserve("2", &intent(Side::Buy, "0.1", Some("50000")));
+        assert!(matches!(
+            gate.check_order(&intent(Side::Buy, "0.1", Some("50000"))),
+            Err(RiskRejection::MaxOpenOrders { open: 2, .. })
+        ));
+        gate.order_update("1", "canceled");
+        gate.order_update("2", "new");
+        assert!(gate.check_order(&intent(Side::Buy, "0.1", Some("50000"))).is_ok());
+
+        // A burst of market orders is held to the position limit
+        let market = |size| OrderIntent::from_strs("cmt_btcusdt", Side::Buy, OrderType::Market, size, None).unwrap();
+        gate.reserve("3", &market("0.5"));
+        assert!(matches!(gate.check_order(&market("0.5")), Err(RiskRejection::MaxPositionSize { .. })));
+        gate.order_update("3", "filled");
+        gate.update_position("cmt_btcusdt", Decimal::from_str("0.5").unwrap());
+        assert!(matches!(gate.check_order(&market("0.5")), Err(RiskRejection::MaxPositionSize { .. })));
+        assert!(gate.check_order(&market("0.3")).is_ok());
+    }
+}
@@ -1,8 +1,13 @@
 use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
 use std::fs::{File, OpenOptions};
-use std::io::{BufReader, BufWriter, Write};
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::Path;
 use chrono::{DateTime, Utc};
+use crate::kill_switch::KillSwitchState;
+use crate::ai_log::{AiLogEntry, AiLogRecord};
+use crate::equity::{EquityCurve, EquitySnapshot};
+use crate::export::{self, ExportFilter};
 
 /// Trade record for logging
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +27,9 @@ pub struct TradeRecord {
 pub struct SessionState {
     pub session_id: String,
     pub started_at: Option<DateTime<Utc>>,
+    /// When the session was last saved; fills after this are reconciled on recovery
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
     pub total_trades: u64,
     pub winning_trades: u64,
     pub total_pnl: f64,
@@ -38,9 +46,19 @@ pub struct PositionRecord {
 }
 
 /// State manager for persistence
+///
+/// Trades, equity snapshots and AI log queue events go to append-only JSON Lines journals
+/// (`trades.jsonl`, `equity.jsonl`, `ai_log_queue.jsonl`), fsynced on every write.
+/// Snapshots such as the session are written to a temp file and atomically renamed over
+/// the previous one, so a crash never leaves a half-written file.
 pub struct StateManager {
     trades_file: String,
+    legacy_trades_file: String,
     session_file: String,
+    kill_switch_file: String,
+    equity_file: String,
+    ai_log_queue_file: String,
+    ai_log_rejected_file: String,
 }
 
 impl StateManager {
@@ -49,45 +67,46 @@ impl StateManager {
         let _ = std::fs::create_dir_all(data_dir);
         
         StateManager {
-            trades_file: format!("{}/trades.json", data_dir),
+            trades_file: format!("{}/trades.jsonl", data_dir),
+            legacy_trades_file: format!("{}/trades.json", data_dir),
             session_file: format!("{}/session.json", data_dir),
+            kill_switch_file: format!("{}/kill_switch.json", data_dir),
+            equity_file: format!("{}/equity.jsonl", data_dir),
+            ai_log_queue_file: format!("{}/ai_log_queue.jsonl", data_dir),
+            ai_log_rejected_file: format!("{}/ai_log_rejected.jsonl", data_dir),
         }
     }
 
     /// Log a trade
     pub fn log_trade(&self, trade: &TradeRecord) -> Result<(), std::io::Error> {
-        let mut trades = self.load_trades().unwrap_or_default();
-        trades.push(trade.clone());
-        self.save_trades(&trades)
+        append_jsonl(&self.trades_file, trade)
     }
 
-    /// Load all trades
+    /// Load all trades, including any from the pre-journal `trades.json`
     pub fn load_trades(&self) -> Result<Vec<TradeRecord>, std::io::Error> {
-        if !Path::new(&self.trades_file).exists() {
-            return Ok(Vec::new());
+        let mut trades = Vec::new();
+
+        if Path::new(&self.legacy_trades_file).exists() {
+            let file = File::open(&self.legacy_trades_file)?;
+            let reader = BufReader::new(file);
+            match serde_json::from_reader::<_, Vec<TradeRecord>>(reader) {
+                Ok(legacy) => trades.extend(legacy),
+                Err(e) => tracing::warn!("Ignoring unreadable {}: {}", self.legacy_trades_file, e),
+            }
         }
-        
-        let file = File::open(&self.trades_file)?;
-        let reader = BufReader::new(file);
-        let trades: Vec<TradeRecord> = serde_json::from_reader(reader)
-            .unwrap_or_default();
+
+        trades.extend(read_jsonl(&self.trades_file)?);
         Ok(trades)
     }
 
-    /// Save trades
-    fn save_trades(&self, trades: &[TradeRecord]) -> Result<(), std::io::Error> {
-        let file = File::create(&self.trades_file)?;
-        let writer = BufWriter::new(file);
-        serde_json::to_writer_pretty(writer, trades)?;
-        Ok(())
+    /// Export journaled trades matching `filter` as CSV; returns the number of rows
+    pub fn export_trades_csv<W: Write>(&self, filter: &ExportFilter, writer: W) -> Result<usize, std::io::Error> {
+        export::write_csv(&self.load_trades()?, filter, writer)
     }
 
     /// Save session state
     pub fn save_session(&self, state: &SessionState) -> Result<(), std::io::Error> {
-        let file = File::create(&self.session_file)?;
-        let writer = BufWriter::new(file);
-        serde_json::to_writer_pretty(writer, state)?;
-        Ok(())
+        write_atomic(&self.session_file, state)
     }
 
     /// Load session state
@@ -103,6 +122,72 @@ impl StateManager {
         Ok(state)
     }
 
+    /// Save kill switch state
+    pub fn save_kill_switch(&self, state: &KillSwitchState) -> Result<(), std::io::Error> {
+        write_atomic(&self.kill_switch_file, state)
+    }
+
+    /// Load kill switch state
+    pub fn load_kill_switch(&self) -> Result<KillSwitchState, std::io::Error> {
+        if !Path::new(&self.kill_switch_file).exists() {
+            return Ok(KillSwitchState::default());
+        }
+
+        let file = File::open(&self.kill_switch_file)?;
+        let reader = BufReader::new(file);
+        let state: KillSwitchState = serde_json::from_reader(reader)?;
+        Ok(state)
+    }
+
+    /// Append an event to the AI log upload queue journal
+    pub fn append_ai_log_record(&self, record: &AiLogRecord) -> Result<(), std::io::Error> {
+        append_jsonl(&self.ai_log_queue_file, record)
+    }
+
+    /// Load AI log entries awaiting upload by replaying the queue journal
+    ///
+    /// The journal is then compacted down to the entries still pending.
+    pub fn load_ai_log_queue(&self) -> Result<Vec<AiLogEntry>, std::io::Error> {
+        let records: Vec<AiLogRecord> = read_jsonl(&self.ai_log_queue_file)?;
+        if records.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut pending: Vec<AiLogEntry> = Vec::new();
+        for record in records {
+            match record {
+                AiLogRecord::Enqueued { entries } => pending.extend(entries),
+                AiLogRecord::Rejected { id } => {
+                    if let Some(entry) = pending.iter_mut().find(|e| e.id == id) {
+                        entry.attempts += 1;
+                    }
+                }
+                AiLogRecord::Acked { id } | AiLogRecord::DeadLettered { id } => pending.retain(|e| e.id != id),
+            }
+        }
+        write_jsonl_atomic(&self.ai_log_queue_file, &[AiLogRecord::Enqueued { entries: pending.clone() }])?;
+        Ok(pending)
+    }
+
+    /// Append an AI log entry the exchange kept rejecting to `ai_log_rejected.jsonl`
+    pub fn dead_letter_ai_log(&self, entry: &AiLogEntry) -> Result<(), std::io::Error> {
+        append_jsonl(&self.ai_log_rejected_file, entry)
+    }
+
+    /// Load dead-lettered AI log entries, oldest first
+    pub fn load_rejected_ai_logs(&self) -> Result<Vec<AiLogEntry>, std::io::Error> {
+        read_jsonl(&self.ai_log_rejected_file)
+    }
+
+    /// Append an equity snapshot to the `equity.jsonl` time series
+    pub fn record_equity(&self, snapshot: &EquitySnapshot) -> Result<(), std::io::Error> {
+        append_jsonl(&self.equity_file, snapshot)
+    }
+
+    /// Load all recorded equity snapshots, oldest first
+    pub fn load_equity_curve(&self) -> Result<EquityCurve, std::io::Error> {
+        Ok(EquityCurve::new(read_jsonl(&self.equity_file)?))
+    }
+
     /// Calculate PnL statistics
     pub fn calculate_stats(&self) -> Result<PnlStats, std::io::Error> {
         let trades = self.load_trades()?;
@@ -134,6 +219,113 @@ impl StateManager {
     }
 }
 
+/// Append one record as a JSON line and fsync
+///
+/// If a previous crash left a partial last line, it is cut off first so the
+/// journal stays one complete record per line.
+pub(crate) fn append_jsonl<T: Serialize>(path: &str, record: &T) -> Result<(), std::io::Error> {
+    let mut file = OpenOptions::new().create(true).read(true).append(true).open(path)?;
+
+    let len = file.metadata()?.len();
+    let complete = complete_len(&mut file, len)?;
+    if complete < len {
+        tracing::warn!("Dropping {} bytes of truncated record from {}", len - complete, path);
+        file.set_len(complete)?;
+    }
+
+    let mut line = serde_json::to_vec(record)?;
+    line.push(b'\n');
+    file.write_all(&line)?;
+    file.sync_data()
+}
+
+/// Length of the file up to and including its last newline
+fn complete_len(file: &mut File, len: u64) -> Result<u64, std::io::Error> {
+    let mut end = len;
+    let mut buf = [0u8; 4096];
+    while end > 0 {
+        let start = end.saturating_sub(buf.len() as u64);
+        let chunk = &mut buf[..(end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(chunk)?;
+        if let Some(pos) = chunk.iter().rposition(|b| *b == b'\n') {
+            return Ok(start + pos as u64 + 1);
+        }
+        end = start;
+    }
+    Ok(0)
+}
+
+/// Read a JSON Lines file, skipping blank lines and a truncated final line
+pub(crate) fn read_jsonl<T: DeserializeOwned>(path: &str) -> Result<Vec<T>, std::io::Error> {
+    if !Path::new(path).exists() {
+        return Ok(Vec::new());
+    }
+
+    let reader = BufReader::new(File::open(path)?);
+    let lines: Vec<String> = reader.lines().collect::<Result<_, _>>()?;
+    let last = lines.len().saturating_sub(1);
+
+    let mut records = Vec::with_capacity(lines.len());
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(line) {
+            Ok(record) => records.push(record),
+            Err(e) if i == last => {
+                tracing::warn!("Ignoring truncated last line in {}: {}", path, e);
+            }
+            Err(e) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("{} line {}: {}", path, i + 1, e),
+                ));
+            }
+        }
+    }
+    Ok(records)
+}
+
+/// Replace a JSON Lines file via temp file, fsync and rename
+pub(crate) fn write_jsonl_atomic<T: Serialize>(path: &str, records: &[T]) -> Result<(), std::io::Error> {
+    replace_file(path, |writer| {
+        for record in records {
+            serde_json::to_writer(&mut *writer, record)?;
+            writer.write_all(b"\n")?;
+        }
+        Ok(())
+    })
+}
+
+/// Write a JSON snapshot via temp file, fsync and rename
+pub(crate) fn write_atomic<T: Serialize>(path: &str, value: &T) -> Result<(), std::io::Error> {
+    replace_file(path, |writer| Ok(serde_json::to_writer_pretty(writer, value)?))
+}
+
+fn replace_file(
+    path: &str,
+    write: impl FnOnce(&mut BufWriter<File>) -> Result<(), std::io::Error>,
+) -> Result<(), std::io::Error> {
+    let tmp = format!("{}.tmp", path);
+    {
+        let file = File::create(&tmp)?;
+        let mut writer = BufWriter::new(file);
+        write(&mut writer)?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+    }
+    std::fs::rename(&tmp, path)?;
+
+    // Persist the rename itself
+    if let Some(dir) = Path::new(path).parent() {
+        if let Ok(dir) = File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+    Ok(())
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct PnlStats {
     pub total_trades: u64,
@@ -144,3 +336,63 @@ pub struct PnlStats {
     pub gross_loss: f64,
     pub profit_factor: f64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("weex_state_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.to_string_lossy().into_owned()
+    }
+
+    fn trade(id: &str) -> TradeRecord {
+        TradeRecord {
+            id: id.to_string(),
+            timestamp: Utc::now(),
+            symbol: "cmt_btcusdt".to_string(),
+            side: "buy".to_string(),
+            size: 0.01,
+            price: 50000.0,
+            pnl: Some(1.5),
+            status: "filled".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_journal_survives_truncated_line() {
+        let dir = temp_dir("journal");
+        let manager = StateManager::new(&dir);
+        manager.log_trade(&trade("1")).unwrap();
+        manager.log_trade(&trade("2")).unwrap();
+
+        // Simulate a crash mid-write
+        let path = format!("{}/trades.jsonl", dir);
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"id":"3","timest"#).unwrap();
+
+        let trades = manager.load_trades().unwrap();
+        assert_eq!(trades.iter().map(|t| t.id.as_str()).collect::<Vec<_>>(), vec!["1", "2"]);
+
+        // The next append replaces the partial record
+        manager.log_trade(&trade("4")).unwrap();
+        let trades = manager.load_trades().unwrap();
+        assert_eq!(trades.iter().map(|t| t.id.as_str()).collect::<Vec<_>>(), vec!["1", "2", "4"]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_session_snapshot_is_atomic() {
+        let dir = temp_dir("session");
+        let manager = StateManager::new(&dir);
+        let state = SessionState { session_id: "abc".to_string(), total_trades: 3, ..Default::default() };
+        manager.save_session(&state).unwrap();
+
+        assert!(!Path::new(&format!("{}/session.json.tmp", dir)).exists());
+        assert_eq!(manager.load_session().unwrap().total_trades, 3);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
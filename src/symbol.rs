@@ -0,0 +1,206 @@
+use crate::error::WeexError;
+use crate::types::ProductType;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+/// Quote currencies recognised when splitting a concatenated pair like `BTCUSDT`
+const QUOTE_CURRENCIES: [&str; 2] = ["USDT", "USDC"];
+
+/// Prefix WEEX uses for contract (futures) instruments on the REST API
+const FUTURES_PREFIX: &str = "cmt_";
+
+/// Trading instrument, normalized across spot and futures naming
+///
+/// Parses `cmt_btcusdt` (futures REST), `BTCUSDT` (spot / WS) and separated
+/// forms such as `BTC/USDT` or `btc-usdt`. Dereferences to the REST name, so a
+/// `&Symbol` can be passed anywhere `WeexClient` expects a `&str` symbol.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Symbol {
+    base: String,
+    quote: String,
+    product_type: ProductType,
+    rest_name: String,
+}
+
+impl Symbol {
+    /// Create a spot symbol from base and quote currencies
+    pub fn spot(base: &str, quote: &str) -> Result<Self, WeexError> {
+        Self::from_parts(base, quote, ProductType::Spot)
+    }
+
+    /// Create a futures (contract) symbol from base and quote currencies
+    pub fn futures(base: &str, quote: &str) -> Result<Self, WeexError> {
+        Self::from_parts(base, quote, ProductType::Futures)
+    }
+
+    fn from_parts(base: &str, quote: &str, product_type: ProductType) -> Result<Self, WeexError> {
+        let base = base.to_uppercase();
+        let quote = quote.to_uppercase();
+
+        if base.is_empty() || !base.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(WeexError::InvalidSymbol(format!("invalid base currency '{}'", base)));
+        }
+        if !QUOTE_CURRENCIES.contains(&quote.as_str()) {
+            return Err(WeexError::InvalidSymbol(format!("unsupported quote currency '{}'", quote)));
+        }
+
+        let rest_name = match product_type {
+            ProductType::Spot => format!("{}{}", base, quote),
+            ProductType::Futures => format!("{}{}{}", FUTURES_PREFIX, base.to_lowercase(), quote.to_lowercase()),
+        };
+
+        Ok(Symbol { base, quote, product_type, rest_name })
+    }
+
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    pub fn quote(&self) -> &str {
+        &self.quote
+    }
+
+    pub fn product_type(&self) -> ProductType {
+        self.product_type
+    }
+
+    pub fn is_futures(&self) -> bool {
+        self.product_type == ProductType::Futures
+    }
+
+    /// Name used by the REST API (`cmt_btcusdt` for futures, `BTCUSDT` for spot)
+    pub fn rest_name(&self) -> &str {
+        &self.rest_name
+    }
+
+    /// Name used as `instId` on the WebSocket API (`BTCUSDT`)
+    pub fn ws_name(&self) -> String {
+        format!("{}{}", self.base, self.quote)
+    }
+
+    /// WebSocket `instType` for this product line
+    pub fn ws_inst_type(&self) -> &'static str {
+        match self.product_type {
+            ProductType::Spot => "sp",
+            ProductType::Futures => "mc",
+        }
+    }
+
+    /// Whether `name`, in any accepted form, is the same base/quote pair
+    ///
+    /// Ignores the product line, so `BTCUSDT` from the WebSocket matches `cmt_btcusdt`.
+    pub fn matches(&self, name: &str) -> bool {
+        match name.parse::<Symbol>() {
+            Ok(other) => other.base == self.base && other.quote == self.quote,
+            Err(_) => name.eq_ignore_ascii_case(&self.rest_name),
+        }
+    }
+
+    /// Same pair on the futures market
+    pub fn to_futures(&self) -> Symbol {
+        Self::from_parts(&self.base, &self.quote, ProductType::Futures)
+            .expect("parts already validated")
+    }
+
+    /// Same pair on the spot market
+    pub fn to_spot(&self) -> Symbol {
+        Self::from_parts(&self.base, &self.quote, ProductType::Spot)
+            .expect("parts already validated")
+    }
+}
+
+impl FromStr for Symbol {
+    type Err = WeexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let lower = trimmed.to_lowercase();
+
+        let (pair, product_type) = match lower.strip_prefix(FUTURES_PREFIX) {
+            Some(rest) => (rest, ProductType::Futures),
+            None => (lower.as_str(), ProductType::Spot),
+        };
+
+        // Separated form: BTC/USDT, BTC-USDT, BTC_USDT
+        if let Some((base, quote)) = pair.split_once(['/', '-', '_']) {
+            return Self::from_parts(base, quote, product_type)
+                .map_err(|e| WeexError::InvalidSymbol(format!("'{}': {}", trimmed, e)));
+        }
+
+        let upper = pair.to_uppercase();
+        QUOTE_CURRENCIES
+            .iter()
+            .find_map(|quote| upper.strip_suffix(quote).map(|base| (base, *quote)))
+            .ok_or_else(|| WeexError::InvalidSymbol(format!("'{}': unrecognised quote currency", trimmed)))
+            .and_then(|(base, quote)| {
+                Self::from_parts(base, quote, product_type)
+                    .map_err(|e| WeexError::InvalidSymbol(format!("'{}': {}", trimmed, e)))
+            })
+    }
+}
+
+impl TryFrom<String> for Symbol {
+    type Error = WeexError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Symbol> for String {
+    fn from(symbol: Symbol) -> Self {
+        symbol.rest_name
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.rest_name)
+    }
+}
+
+impl Deref for Symbol {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.rest_name
+    }
+}
+
+impl AsRef<str> for Symbol {
+    fn as_ref(&self) -> &str {
+        &self.rest_name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_futures_and_spot() {
+        let fut: Symbol = "cmt_btcusdt".parse().unwrap();
+        assert_eq!(fut.base(), "BTC");
+        assert_eq!(fut.quote(), "USDT");
+        assert!(fut.is_futures());
+        assert_eq!(fut.rest_name(), "cmt_btcusdt");
+        assert_eq!(fut.ws_name(), "BTCUSDT");
+
+        let spot: Symbol = "BTCUSDT".parse().unwrap();
+        assert_eq!(spot.product_type(), ProductType::Spot);
+        assert_eq!(spot.to_futures(), fut);
+        assert_eq!(fut.to_spot(), spot);
+
+        assert_eq!("eth/usdc".parse::<Symbol>().unwrap().rest_name(), "ETHUSDC");
+    }
+
+    #[test]
+    fn test_rejects_typos() {
+        assert!("cmt_btcusd".parse::<Symbol>().is_err());
+        assert!("cmt_usdt".parse::<Symbol>().is_err());
+        assert!("BTC USDT".parse::<Symbol>().is_err());
+    }
+}
@@ -0,0 +1,46 @@
+//! Test fixtures shared across modules
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Local HTTP stub answering 200 to one request; yields `(request line and headers, body)`
+pub(crate) async fn http_stub() -> (String, tokio::task::JoinHandle<(String, String)>) {
+    let (url, handle) = http_stub_responses(vec![String::new()]).await;
+    (url, tokio::spawn(async move { handle.await.unwrap().remove(0) }))
+}
+
+/// Local HTTP stub answering one request per response body, in order; yields every `(request line and headers, body)`
+pub(crate) async fn http_stub_responses(
+    responses: Vec<String>,
+) -> (String, tokio::task::JoinHandle<Vec<(String, String)>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = tokio::spawn(async move {
+        let mut requests = Vec::new();
+        for response in responses {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = Vec::new();
+            let mut chunk = [0u8; 4096];
+            loop {
+                let n = socket.read(&mut chunk).await.unwrap();
+                buf.extend_from_slice(&chunk[..n]);
+                let text = String::from_utf8_lossy(&buf).to_string();
+                if let Some(end) = text.find("\r\n\r\n") {
+                    let length = text[..end]
+                        .lines()
+                        .find_map(|l| l.to_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap()))
+                        .unwrap_or(0);
+                    if buf.len() >= end + 4 + length || n == 0 {
+                        let head = format!("HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Length: {}\r\n\r\n", response.len());
+                        socket.write_all(head.as_bytes()).await.unwrap();
+                        socket.write_all(response.as_bytes()).await.unwrap();
+                        requests.push((text[..end].to_string(), text[end + 4..].to_string()));
+                        break;
+                    }
+                }
+            }
+        }
+        requests
+    });
+    (url, handle)
+}
@@ -1,10 +1,21 @@
 use async_trait::async_trait;
 use crate::spot::market::Ticker;
 use crate::error::WeexError;
+use crate::types::{OrderType, Side};
 
 #[async_trait]
 pub trait Exchange {
     async fn get_ticker(&self, symbol: &str) -> Result<Ticker, WeexError>;
     async fn get_balance(&self) -> Result<String, WeexError>; 
-    // Add place_order signatures here properly typed later
+    /// Place an order, returning the raw exchange response
+    async fn place_order(
+        &self,
+        symbol: &str,
+        size: &str,
+        side: Side,
+        order_type: OrderType,
+        price: Option<&str>,
+    ) -> Result<String, WeexError>;
+    async fn cancel_all_orders(&self, symbol: &str) -> Result<String, WeexError>;
+    async fn close_all_positions(&self, symbol: &str) -> Result<String, WeexError>;
 }
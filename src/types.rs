@@ -25,6 +25,7 @@ pub enum TimeInForce {
     Gtc, // Good Till Cancel
     Ioc, // Immediate or Cancel
     Fok, // Fill or Kill
+    PostOnly, // Maker only, cancelled if it would take
 }
 
 // ==================== AI WARS TYPES ====================
@@ -37,7 +38,7 @@ pub enum MarginMode {
     Isolated,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumString, Serialize, Deserialize)]
 #[strum(serialize_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
 pub enum PositionSide {
@@ -70,3 +71,12 @@ pub enum AILogStage {
     Execution,
 }
 
+
+/// Product line an instrument trades on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumString, Serialize, Deserialize)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ProductType {
+    Spot,
+    Futures,
+}
@@ -12,3 +12,96 @@ pub fn generate_signature(secret_key: &str, message: &str) -> String {
     general_purpose::STANDARD.encode(result.into_bytes())
 }
 
+
+/// Extract the list from a REST response: bare array, `{"data": [...]}` or `{"data": {"list": [...]}}`
+pub(crate) fn json_list(raw: &str) -> Result<Vec<serde_json::Value>, crate::error::WeexError> {
+    use serde_json::Value;
+
+    let value: Value = serde_json::from_str(raw)?;
+    let mut inner = match value.get("data") {
+        Some(data) => data.clone(),
+        None if value.get("code").is_some() && value.get("list").is_none() => {
+            return Err(crate::error::WeexError::Api {
+                code: json_str(&value, &["code"]).unwrap_or_default(),
+                msg: json_str(&value, &["msg"]).unwrap_or_default(),
+            });
+        }
+        None => value,
+    };
+    if let Some(list) = inner.get("list") {
+        inner = list.clone();
+    }
+    match inner {
+        Value::Array(items) => Ok(items),
+        Value::Null => Ok(Vec::new()),
+        other => Ok(vec![other]),
+    }
+}
+
+/// Order ID from a place-order response
+pub(crate) fn order_id_from_response(raw: &str) -> Option<i64> {
+    let v: serde_json::Value = serde_json::from_str(raw).ok()?;
+    let data = v.get("data").unwrap_or(&v);
+    json_str(data, &["order_id", "orderId"])?.parse().ok()
+}
+
+/// Treat a response as acknowledged unless it carries a non-success `code`
+pub(crate) fn check_ack(text: &str) -> Result<(), crate::error::WeexError> {
+    let Ok(v) = serde_json::from_str::<serde_json::Value>(text) else { return Ok(()) };
+    let code = match v.get("code") {
+        Some(serde_json::Value::String(c)) => c.clone(),
+        Some(serde_json::Value::Number(n)) => n.to_string(),
+        _ => return Ok(()),
+    };
+    if matches!(code.as_str(), "00000" | "0" | "200") {
+        return Ok(());
+    }
+    Err(crate::error::WeexError::Api {
+        code,
+        msg: v.get("msg").and_then(|m| m.as_str()).unwrap_or_default().to_string(),
+    })
+}
+
+/// First present field among `keys`, as a string
+pub(crate) fn json_str(value: &serde_json::Value, keys: &[&str]) -> Option<String> {
+    keys.iter().find_map(|k| match value.get(*k)? {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Null => None,
+        other => Some(other.to_string()),
+    })
+}
+
+/// First present field among `keys`, as a decimal
+pub(crate) fn json_decimal(value: &serde_json::Value, keys: &[&str]) -> Option<rust_decimal::Decimal> {
+    json_str(value, keys).and_then(|s| s.parse().ok())
+}
+
+/// Whether `ts` falls within `[from, to]`; either bound may be open
+pub(crate) fn in_range(
+    ts: chrono::DateTime<chrono::Utc>,
+    from: Option<chrono::DateTime<chrono::Utc>>,
+    to: Option<chrono::DateTime<chrono::Utc>>,
+) -> bool {
+    let after = match from {
+        Some(f) => ts >= f,
+        None => true,
+    };
+    let before = match to {
+        Some(t) => ts <= t,
+        None => true,
+    };
+    after && before
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::WeexError;
+
+    #[test]
+    fn test_check_ack() {
+        assert!(check_ack(r#"{"code":"00000","msg":"success"}"#).is_ok());
+        assert!(matches!(check_ack(r#"{"code":"40001","msg":"bad"}"#), Err(WeexError::Api { .. })));
+        assert_eq!(order_id_from_response(r#"{"data":{"order_id":"42"}}"#), Some(42));
+    }
+}
@@ -6,6 +6,7 @@ use tokio_tungstenite::tungstenite::protocol::Message;
 use url::Url;
 use std::time::Duration;
 use serde::{Serialize, Deserialize};
+use crate::symbol::Symbol;
 
 pub const WS_PUBLIC_URL: &str = "wss://ws-spot.weex.com/v2/ws/public";
 pub const WS_PRIVATE_URL: &str = "wss://ws-spot.weex.com/v2/ws/private";
@@ -91,23 +92,29 @@ impl WeexWebsocket {
 
     /// Subscribe to a channel
     pub fn build_subscribe_msg(channel: &str, inst_id: &str) -> String {
-        let req = SubscribeRequest {
-            op: "subscribe".to_string(),
-            args: vec![SubscribeArg {
-                inst_type: "sp".to_string(), // spot
-                channel: channel.to_string(),
-                inst_id: inst_id.to_string(),
-            }],
-        };
-        serde_json::to_string(&req).unwrap_or_default()
+        Self::build_msg("subscribe", "sp", channel, inst_id) // spot
     }
 
     /// Build unsubscribe message
     pub fn build_unsubscribe_msg(channel: &str, inst_id: &str) -> String {
+        Self::build_msg("unsubscribe", "sp", channel, inst_id)
+    }
+
+    /// Subscribe to a channel for a typed symbol, using its WS name and product line
+    pub fn build_symbol_subscribe_msg(channel: &str, symbol: &Symbol) -> String {
+        Self::build_msg("subscribe", symbol.ws_inst_type(), channel, &symbol.ws_name())
+    }
+
+    /// Build unsubscribe message for a typed symbol
+    pub fn build_symbol_unsubscribe_msg(channel: &str, symbol: &Symbol) -> String {
+        Self::build_msg("unsubscribe", symbol.ws_inst_type(), channel, &symbol.ws_name())
+    }
+
+    fn build_msg(op: &str, inst_type: &str, channel: &str, inst_id: &str) -> String {
         let req = SubscribeRequest {
-            op: "unsubscribe".to_string(),
+            op: op.to_string(),
             args: vec![SubscribeArg {
-                inst_type: "sp".to_string(),
+                inst_type: inst_type.to_string(),
                 channel: channel.to_string(),
                 inst_id: inst_id.to_string(),
             }],
@@ -160,20 +167,25 @@ pub async fn handle_heartbeat(
 }
 
 /// Convenience: Run a WS subscription loop with auto-reconnect
+///
+/// `symbol` is a `Symbol` or any spelling it parses (`cmt_btcusdt`, `BTCUSDT`);
+/// a malformed name is returned as an error before connecting.
 pub async fn run_subscription_loop(
     channel: &str,
-    inst_id: &str,
+    symbol: impl AsRef<str>,
     msg_tx: mpsc::Sender<WsMessage>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let symbol: Symbol = symbol.as_ref().parse()?;
+    let symbol = &symbol;
     let mut ws = WeexWebsocket::public();
     
     loop {
         let (mut write, read) = ws.connect_with_reconnect().await?;
         
         // Subscribe
-        let sub_msg = WeexWebsocket::build_subscribe_msg(channel, inst_id);
+        let sub_msg = WeexWebsocket::build_symbol_subscribe_msg(channel, symbol);
         write.send(Message::Text(sub_msg)).await?;
-        tracing::info!("Subscribed to {}:{}", channel, inst_id);
+        tracing::info!("Subscribed to {}:{}", channel, symbol.ws_name());
         
         // Handle messages with heartbeat
         handle_heartbeat(write, read, msg_tx.clone()).await;
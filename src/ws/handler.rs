@@ -1,7 +1,7 @@
-use crate::ws::client::{WeexWebsocket, WsMessage, WS_PUBLIC_URL};
+use crate::ws::client::{WeexWebsocket, WsMessage};
+use crate::symbol::Symbol;
 use tokio::sync::mpsc;
 use tokio::sync::broadcast;
-use std::collections::HashMap;
 
 /// Real-time market data from WebSocket
 #[derive(Debug, Clone)]
@@ -10,6 +10,9 @@ pub enum MarketEvent {
     Depth { symbol: String, bids: Vec<(f64, f64)>, asks: Vec<(f64, f64)> },
     Trade { symbol: String, price: f64, size: f64, side: String },
     Kline { symbol: String, open: f64, high: f64, low: f64, close: f64, volume: f64 },
+    FundingRate { symbol: String, rate: f64 },
+    /// WebSocket connection state changed
+    Connection { connected: bool },
 }
 
 /// User account events (from private WS)
@@ -18,6 +21,8 @@ pub enum AccountEvent {
     OrderUpdate { order_id: String, status: String, filled_size: f64 },
     PositionUpdate { symbol: String, size: f64, entry_price: f64, pnl: f64 },
     BalanceUpdate { coin: String, available: f64, frozen: f64 },
+    /// Maintenance margin / equity (liquidation at 1.0)
+    MarginUpdate { symbol: String, margin_ratio: f64 },
 }
 
 /// Event handler that distributes WS messages to subscribers
@@ -44,12 +49,41 @@ impl EventHandler {
         self.account_tx.subscribe()
     }
 
+    /// Publish a market event to subscribers
+    pub fn emit_market(&self, event: MarketEvent) {
+        let _ = self.market_tx.send(event);
+    }
+
+    /// Publish an account event to subscribers
+    pub fn emit_account(&self, event: AccountEvent) {
+        let _ = self.account_tx.send(event);
+    }
+
+    /// Emit the account events carried by a private stream message
+    pub fn handle_private_message(&self, msg: &WsMessage) {
+        for event in crate::ws::private::parse_account_events(msg) {
+            self.emit_account(event);
+        }
+    }
+
     /// Process raw WS message and emit typed events
     pub fn handle_message(&self, msg: &WsMessage) {
         // Parse based on channel type
         if let Some(ref data) = msg.data {
             if let Some(arr) = data.as_array() {
                 for item in arr {
+                    if let (Some(symbol), Some(rate)) = (
+                        item.get("instId").and_then(|v| v.as_str()),
+                        item.get("fundingRate").and_then(|v| v.as_str()),
+                    ) {
+                        if let Ok(rate) = rate.parse::<f64>() {
+                            self.emit_market(MarketEvent::FundingRate {
+                                symbol: symbol.to_string(),
+                                rate,
+                            });
+                        }
+                    }
+
                     // Try to parse as ticker
                     if let (Some(symbol), Some(last)) = (
                         item.get("instId").and_then(|v| v.as_str()),
@@ -69,32 +103,47 @@ impl EventHandler {
 }
 
 /// Run the public WebSocket event loop
-pub async fn run_public_ws_loop(
-    symbols: Vec<String>,
+///
+/// Reconnects whenever the connection drops, emitting `MarketEvent::Connection`
+/// on every disconnect and reconnect. Returns once reconnecting gives up.
+/// `symbols` may be `Symbol`s or names they parse; a malformed name is returned as
+/// an error before connecting.
+pub async fn run_public_ws_loop<S: AsRef<str>>(
+    symbols: Vec<S>,
     handler: EventHandler,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let mut ws = WeexWebsocket::public();
-    let (mut write, read) = ws.connect_with_reconnect().await?;
-    
     use futures::SinkExt;
     use tokio_tungstenite::tungstenite::protocol::Message;
-    
-    // Subscribe to tickers for all symbols
-    for symbol in &symbols {
-        let sub_msg = WeexWebsocket::build_subscribe_msg("ticker", symbol);
-        write.send(Message::Text(sub_msg)).await?;
-    }
-    
-    // Create channel for WS messages
-    let (msg_tx, mut msg_rx) = mpsc::channel::<WsMessage>(1000);
-    
-    // Spawn heartbeat handler
-    tokio::spawn(crate::ws::client::handle_heartbeat(write, read, msg_tx));
-    
-    // Process messages
-    while let Some(msg) = msg_rx.recv().await {
-        handler.handle_message(&msg);
+
+    let symbols = symbols.iter().map(|s| s.as_ref().parse::<Symbol>()).collect::<Result<Vec<_>, _>>()?;
+
+    let mut ws = WeexWebsocket::public();
+    loop {
+        let (mut write, read) = ws.connect_with_reconnect().await?;
+        handler.emit_market(MarketEvent::Connection { connected: true });
+
+        // Subscribe to tickers for all symbols
+        let mut subscribed = true;
+        for symbol in &symbols {
+            let sub_msg = WeexWebsocket::build_symbol_subscribe_msg("ticker", symbol);
+            if let Err(e) = write.send(Message::Text(sub_msg)).await {
+                tracing::error!("Failed to subscribe to {}: {}", symbol, e);
+                subscribed = false;
+                break;
+            }
+        }
+
+        if subscribed {
+            // The channel closes when the heartbeat handler ends with the connection
+            let (msg_tx, mut msg_rx) = mpsc::channel::<WsMessage>(1000);
+            tokio::spawn(crate::ws::client::handle_heartbeat(write, read, msg_tx));
+            while let Some(msg) = msg_rx.recv().await {
+                handler.handle_message(&msg);
+            }
+        }
+
+        handler.emit_market(MarketEvent::Connection { connected: false });
+        tracing::warn!("Public WS disconnected, reconnecting...");
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
     }
-    
-    Ok(())
 }
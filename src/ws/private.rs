@@ -1,26 +1,53 @@
 use crate::WeexClient;
 use crate::error::WeexError;
-use crate::ws::client::{WeexWebsocket, WS_PRIVATE_URL, WsMessage, handle_heartbeat};
+use crate::ws::client::{WeexWebsocket, WS_PRIVATE_URL, SubscribeArg, WsMessage, handle_heartbeat};
+use crate::ws::handler::{AccountEvent, EventHandler};
+use crate::util::{json_decimal, json_str};
+use rust_decimal::prelude::ToPrimitive;
 use tokio::sync::mpsc;
 use tokio::time::{interval, Duration};
 use tokio_tungstenite::tungstenite::protocol::Message;
 use futures::SinkExt;
 
+/// Aborts a background task when dropped
+struct AbortOnDrop(tokio::task::JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
 /// UserStream manages authenticated WebSocket connection
 pub struct UserStream {
     client: WeexClient,
     listen_key: Option<String>,
+    /// Channels to subscribe to; `None` means spot orders and account
+    channels: Option<Vec<SubscribeArg>>,
 }
 
 impl UserStream {
     pub fn new(client: WeexClient) -> Self {
-        UserStream { client, listen_key: None }
+        UserStream { client, listen_key: None, channels: None }
+    }
+
+    /// Subscribe to `channel` (e.g. `orders`, `positions`) for all instruments of `inst_type`
+    ///
+    /// Replaces the default orders and account subscription; call once per channel.
+    pub fn channel(mut self, inst_type: &str, channel: &str) -> Self {
+        self.channels.get_or_insert_with(Vec::new).push(SubscribeArg {
+            inst_type: inst_type.to_string(),
+            channel: channel.to_string(),
+            inst_id: "default".to_string(),
+        });
+        self
     }
 
     /// Get or create listenKey
     pub async fn get_listen_key(&mut self) -> Result<String, WeexError> {
         let path = "/api/v2/spot/public/listenKey";
         let url = format!("{}{}", self.client.base_url, path);
+        self.client.throttle().await;
         let timestamp = self.client.get_timestamp();
         let headers = self.client.build_headers("POST", path, "", "", &timestamp)?;
 
@@ -48,6 +75,7 @@ impl UserStream {
             let path = "/api/v2/spot/public/listenKey";
             let body = serde_json::json!({ "listenKey": key }).to_string();
             let url = format!("{}{}", self.client.base_url, path);
+            self.client.throttle().await;
             let timestamp = self.client.get_timestamp();
             let headers = self.client.build_headers("PUT", path, "", &body, &timestamp)?;
 
@@ -56,21 +84,38 @@ impl UserStream {
         Ok(())
     }
 
+    /// Run the stream, publishing typed order, position and account updates through `handler`
+    pub async fn run(&mut self, handler: &EventHandler) -> Result<(), WeexError> {
+        let (msg_tx, mut msg_rx) = mpsc::channel::<WsMessage>(1000);
+        let forward = async {
+            while let Some(msg) = msg_rx.recv().await {
+                handler.handle_private_message(&msg);
+            }
+        };
+        tokio::select! {
+            result = self.start(msg_tx) => result,
+            _ = forward => Ok(()),
+        }
+    }
+
     /// Start the private WebSocket stream with auto keep-alive
     pub async fn start(&mut self, msg_tx: mpsc::Sender<WsMessage>) -> Result<(), WeexError> {
+        let credentials = self.client.credentials.clone()
+            .ok_or_else(|| WeexError::MissingCredentials("UserStream".into()))?;
         let listen_key = self.get_listen_key().await?;
         let ws_url = format!("{}?listenKey={}", WS_PRIVATE_URL, listen_key);
         
-        // Spawn keep-alive task
+        // Spawn keep-alive task, stopped when this stream is dropped
         let client_clone = self.client.clone();
         let key_clone = listen_key.clone();
-        tokio::spawn(async move {
+        let _keep_alive = AbortOnDrop(tokio::spawn(async move {
             let mut ticker = interval(Duration::from_secs(1800)); // 30 min
             loop {
                 ticker.tick().await;
                 let path = "/api/v2/spot/public/listenKey";
                 let body = serde_json::json!({ "listenKey": key_clone }).to_string();
                 let url = format!("{}{}", client_clone.base_url, path);
+                client_clone.throttle().await;
                 if let Ok(timestamp) = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
                     let ts = timestamp.as_millis().to_string();
                     if let Ok(headers) = client_clone.build_headers("PUT", path, "", &body, &ts) {
@@ -79,7 +124,7 @@ impl UserStream {
                     }
                 }
             }
-        });
+        }));
 
         // Connect and handle messages
         let mut ws = WeexWebsocket::new(&ws_url);
@@ -87,13 +132,14 @@ impl UserStream {
             match ws.connect_with_reconnect().await {
                 Ok((mut write, read)) => {
                     // Subscribe to user channels
+                    let timestamp = self.client.get_timestamp();
                     let login_msg = serde_json::json!({
                         "op": "login",
                         "args": [{
-                            "apiKey": self.client.api_key,
-                            "passphrase": self.client.passphrase,
-                            "timestamp": self.client.get_timestamp(),
-                            "sign": crate::util::generate_signature(&self.client.secret_key, &self.client.get_timestamp())
+                            "apiKey": credentials.api_key(),
+                            "passphrase": credentials.passphrase(),
+                            "timestamp": timestamp,
+                            "sign": credentials.sign(&timestamp)
                         }]
                     }).to_string();
                     
@@ -102,14 +148,15 @@ impl UserStream {
                         continue;
                     }
                     
-                    // Subscribe to order and account channels
-                    let sub_msg = serde_json::json!({
-                        "op": "subscribe",
-                        "args": [
+                    // Subscribe to order and account channels unless others were chosen
+                    let args = match &self.channels {
+                        Some(channels) => serde_json::to_value(channels).unwrap_or_default(),
+                        None => serde_json::json!([
                             { "instType": "sp", "channel": "orders", "instId": "default" },
                             { "instType": "sp", "channel": "account", "coin": "default" }
-                        ]
-                    }).to_string();
+                        ]),
+                    };
+                    let sub_msg = serde_json::json!({ "op": "subscribe", "args": args }).to_string();
                     
                     if let Err(e) = write.send(Message::Text(sub_msg)).await {
                         tracing::error!("Failed to subscribe: {}", e);
@@ -127,3 +174,93 @@ impl UserStream {
         }
     }
 }
+
+/// Typed account events carried by a private stream message
+///
+/// Handles the `orders`, `positions` and `account` channels; anything else yields
+/// nothing. Position sizes are signed (negative for shorts), and a position that
+/// reports a margin ratio also yields an `AccountEvent::MarginUpdate`.
+pub fn parse_account_events(msg: &WsMessage) -> Vec<AccountEvent> {
+    let channel = msg
+        .arg
+        .as_ref()
+        .and_then(|arg| arg.get("channel"))
+        .and_then(|c| c.as_str())
+        .unwrap_or_default();
+    let items = match msg.data.as_ref() {
+        Some(serde_json::Value::Array(items)) => items.as_slice(),
+        Some(item) => std::slice::from_ref(item),
+        None => &[],
+    };
+    let num = |v: &serde_json::Value, keys: &[&str]| json_decimal(v, keys).and_then(|d| d.to_f64());
+
+    let mut events = Vec::new();
+    for item in items {
+        match channel {
+            "orders" => {
+                let Some(order_id) = json_str(item, &["orderId", "ordId", "order_id"]) else { continue };
+                events.push(AccountEvent::OrderUpdate {
+                    order_id,
+                    status: json_str(item, &["status", "state"]).unwrap_or_default().to_lowercase(),
+                    filled_size: num(item, &["accBaseVolume", "filledQty", "fillSz", "filled_qty"]).unwrap_or_default(),
+                });
+            }
+            "positions" => {
+                let Some(symbol) = json_str(item, &["symbol", "instId"]) else { continue };
+                let size = num(item, &["total", "size", "holdAmount", "hold_amount"]).unwrap_or_default().abs();
+                let short = json_str(item, &["holdSide", "side"]).is_some_and(|s| s.eq_ignore_ascii_case("short"));
+                if let Some(margin_ratio) = num(item, &["marginRatio", "margin_ratio"]) {
+                    events.push(AccountEvent::MarginUpdate { symbol: symbol.clone(), margin_ratio });
+                }
+                events.push(AccountEvent::PositionUpdate {
+                    symbol,
+                    size: if short { -size } else { size },
+                    entry_price: num(item, &["averageOpenPrice", "openPriceAvg", "avgPx"]).unwrap_or_default(),
+                    pnl: num(item, &["unrealizedPL", "unrealizePnl", "upl"]).unwrap_or_default(),
+                });
+            }
+            "account" => {
+                let Some(coin) = json_str(item, &["coin", "marginCoin", "coinName"]) else { continue };
+                events.push(AccountEvent::BalanceUpdate {
+                    coin,
+                    available: num(item, &["available"]).unwrap_or_default(),
+                    frozen: num(item, &["frozen", "locked"]).unwrap_or_default(),
+                });
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::tests::MemorySink;
+    use crate::alerts::{AlertPolicy, Alerter, RuleEngine, RuleSet};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_private_message_fires_rule() {
+        let sink = Arc::new(MemorySink::default());
+        let (alerts, alert_task) = Alerter::new().sink(sink.clone()).spawn(AlertPolicy::default());
+        let (handler, market_rx, account_rx) = EventHandler::new();
+        let rules = RuleSet::from_json(r#"{ "rules": [{ "name": "Drawdown", "kind": "position_pnl_below", "threshold": -500 }] }"#).unwrap();
+        let rules = RuleEngine::new(rules).spawn(market_rx, account_rx, alerts);
+
+        let raw = r#"{"action":"snapshot","arg":{"instType":"mc","channel":"positions","instId":"default"},
+            "data":[{"instId":"BTCUSDT","holdSide":"short","total":"0.5","averageOpenPrice":"50000","unrealizedPL":"-750","marginRatio":"0.12"}]}"#;
+        let msg: WsMessage = serde_json::from_str(raw).unwrap();
+        let events = parse_account_events(&msg);
+        assert!(matches!(&events[1], AccountEvent::PositionUpdate { size, .. } if *size == -0.5));
+
+        handler.handle_private_message(&msg);
+        drop(handler);
+        rules.await.unwrap();
+        alert_task.await.unwrap();
+
+        let received = sink.alerts.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert!(received[0].message.contains("Drawdown"));
+    }
+}